@@ -3,7 +3,8 @@ use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 use pyo3::types::PyModule;
 use headson_core::{
-    ArraySamplerStrategy, ColorMode, OutputTemplate, PriorityConfig, RenderConfig, Style,
+    ArraySamplerStrategy, ColorMode, IngestOptions, OutputTemplate, PriorityConfig, RenderConfig,
+    Style,
 };
 
 fn to_style(s: &str) -> Result<Style> {
@@ -109,12 +110,13 @@ fn summarize(
     let per_file_for_priority = budget.max(1);
     let prio = priority_config(per_file_for_priority, sampler);
     let input = text.as_bytes().to_vec();
+    let ingest = IngestOptions::default();
     py.detach(|| {
         match input_format.to_ascii_lowercase().as_str() {
-            "json" => headson_core::headson(input, &cfg, &prio, budget).map_err(to_pyerr),
-            "yaml" | "yml" => headson_core::headson_yaml(input, &cfg, &prio, budget)
+            "json" => headson_core::headson(input, &ingest, &cfg, &prio, budget).map_err(to_pyerr),
+            "yaml" | "yml" => headson_core::headson_yaml(input, &ingest, &cfg, &prio, budget)
                 .map_err(to_pyerr),
-            "text" => headson_core::headson_text(input, &cfg, &prio, budget).map_err(to_pyerr),
+            "text" => headson_core::headson_text(input, &ingest, &cfg, &prio, budget).map_err(to_pyerr),
             other => Err(to_pyerr(anyhow::anyhow!(
                 "unknown input_format: {} (expected 'json' | 'yaml' | 'text')",
                 other
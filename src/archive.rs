@@ -0,0 +1,136 @@
+use std::io::{self, Read, Seek};
+
+/// Archive containers whose members are previewed as a fileset.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Archive {
+    Tar,
+    Zip,
+}
+
+// Offset of the `ustar` magic in a POSIX or GNU tar header.
+const TAR_MAGIC_OFFSET: usize = 257;
+
+impl Archive {
+    /// Classify the first bytes of an input (after decompression, so a
+    /// `.tar.gz` reads as `Tar`). `None` when it is not an archive.
+    pub fn sniff(prefix: &[u8]) -> Option<Self> {
+        if prefix.starts_with(b"PK\x03\x04")
+            || prefix.starts_with(b"PK\x05\x06")
+        {
+            Some(Archive::Zip)
+        } else if prefix
+            .get(TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + 5)
+            .is_some_and(|magic| magic == b"ustar")
+        {
+            Some(Archive::Tar)
+        } else {
+            None
+        }
+    }
+
+    /// Regular files of the archive as `(member path, contents)` pairs in
+    /// archive order. Directories, links and other special entries are
+    /// left out, and compressed members (`logs/app.json.gz`) are
+    /// decompressed like any other input. Every member is read into memory.
+    pub fn members(
+        self,
+        reader: impl Read + Seek,
+    ) -> io::Result<Vec<(String, Vec<u8>)>> {
+        match self {
+            Archive::Tar => tar_members(reader),
+            Archive::Zip => zip_members(reader),
+        }
+    }
+}
+
+fn tar_members(reader: impl Read) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        members.push((name, read_member(entry)?));
+    }
+    Ok(members)
+}
+
+fn zip_members(
+    reader: impl Read + Seek,
+) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let mut members = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        if !file.is_file() {
+            continue;
+        }
+        let name = file.name().to_string();
+        members.push((name, read_member(file)?));
+    }
+    Ok(members)
+}
+
+fn read_member(member: impl Read) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    crate::compression::decompressing_reader(io::BufReader::new(member))?
+        .read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    fn tar_of(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, bytes) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *bytes).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn tar_members_keep_their_paths_and_order() {
+        let mut gz = flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        );
+        gz.write_all(b"{\"z\":1}").unwrap();
+        let gz = gz.finish().unwrap();
+        let tar = tar_of(&[("b/x.json", b"{}"), ("a.json.gz", &gz)]);
+        assert_eq!(Archive::sniff(&tar), Some(Archive::Tar));
+        let members = Archive::Tar.members(Cursor::new(tar)).unwrap();
+        assert_eq!(
+            members,
+            vec![
+                ("b/x.json".to_string(), b"{}".to_vec()),
+                ("a.json.gz".to_string(), b"{\"z\":1}".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn zip_members_skip_directories() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("docs/", options).unwrap();
+        zip.start_file("docs/a.yaml", options).unwrap();
+        zip.write_all(b"a: 1\n").unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+        assert_eq!(Archive::sniff(&bytes), Some(Archive::Zip));
+        let members = Archive::Zip.members(Cursor::new(bytes)).unwrap();
+        assert_eq!(
+            members,
+            vec![("docs/a.yaml".to_string(), b"a: 1\n".to_vec())]
+        );
+        assert_eq!(Archive::sniff(b"{\"a\": 1}"), None);
+    }
+}
@@ -0,0 +1,579 @@
+use anyhow::{Context, Result};
+use serde::Deserialize as _;
+use serde::de::value::{Error as ValueError, SeqDeserializer};
+use serde::de::{
+    DeserializeSeed, Error as _, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde_json::json;
+
+use crate::PriorityConfig;
+use crate::json_ingest::{
+    build_tree_arena_from_deserializer,
+    build_tree_arena_from_many_deserializers,
+};
+use crate::utils::text::to_hex;
+use crate::utils::tree_arena::JsonTreeArena;
+
+/// Build an arena from BSON bytes. A single document becomes the root
+/// object; concatenated documents (as written by `mongodump`) become a
+/// root array that is sampled like any other. Types without a JSON
+/// counterpart use MongoDB Extended JSON shapes such as `{"$oid": ...}`.
+pub fn build_bson_tree_arena_from_bytes(
+    bytes: &[u8],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let input = BsonInput::new(bytes)?;
+    build_tree_arena_from_deserializer(input, config)
+}
+
+pub fn build_bson_tree_arena_from_many(
+    inputs: &[(String, Vec<u8>)],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut parsed: Vec<(String, BsonInput<'_>)> =
+        Vec::with_capacity(inputs.len());
+    for (key, bytes) in inputs {
+        let input = BsonInput::new(bytes)
+            .with_context(|| format!("failed to parse {key}"))?;
+        parsed.push((key.clone(), input));
+    }
+    build_tree_arena_from_many_deserializers(parsed, config)
+}
+
+/// True when `bytes` are a sequence of well-framed BSON documents whose
+/// first document has valid top-level elements.
+pub(crate) fn is_bson_stream(bytes: &[u8]) -> bool {
+    let Ok(docs) = split_documents(bytes) else {
+        return false;
+    };
+    docs.first().is_some_and(|doc| {
+        let mut elements = Elements::new(body(doc));
+        std::iter::from_fn(|| elements.next_element().transpose())
+            .all(|e| e.is_ok())
+    })
+}
+
+/// True when `prefix`, cut off from a longer input, starts like a BSON
+/// document: a plausible length followed by a known element type and a
+/// key, or by the terminator of an empty document.
+pub(crate) fn is_bson_prefix(prefix: &[u8]) -> bool {
+    let Some(len) = read_len(prefix) else {
+        return false;
+    };
+    match prefix.get(4) {
+        Some(0) => len == 5,
+        Some(kind) => {
+            len > 5
+                && value_len(*kind, &[0; 16]).is_some()
+                && prefix[5..].contains(&0)
+        }
+        None => false,
+    }
+}
+
+// The whole input: one document, or a stream of them.
+enum BsonInput<'a> {
+    Document(&'a [u8]),
+    Stream(Vec<&'a [u8]>),
+}
+
+impl<'a> BsonInput<'a> {
+    fn new(bytes: &'a [u8]) -> Result<Self> {
+        let mut docs = split_documents(bytes)?;
+        Ok(match docs.len() {
+            1 => BsonInput::Document(docs.remove(0)),
+            _ => BsonInput::Stream(docs),
+        })
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for BsonInput<'_> {
+    type Error = ValueError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            BsonInput::Document(doc) => Document(doc).deserialize_any(visitor),
+            BsonInput::Stream(docs) => {
+                visitor.visit_seq(SeqDeserializer::<_, ValueError>::new(
+                    docs.into_iter().map(Document),
+                ))
+            }
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+fn read_len(bytes: &[u8]) -> Option<usize> {
+    let raw: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+    usize::try_from(i32::from_le_bytes(raw)).ok()
+}
+
+fn split_documents(bytes: &[u8]) -> Result<Vec<&[u8]>, ValueError> {
+    let mut docs = Vec::new();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let len = read_len(rest)
+            .filter(|len| *len >= 5 && *len <= rest.len())
+            .filter(|len| rest[len - 1] == 0)
+            .ok_or_else(|| {
+                ValueError::custom(format!(
+                    "malformed BSON document at byte {}",
+                    bytes.len() - rest.len()
+                ))
+            })?;
+        let (doc, tail) = rest.split_at(len);
+        docs.push(doc);
+        rest = tail;
+    }
+    Ok(docs)
+}
+
+// Elements of a framed document: everything between the length prefix
+// and the trailing NUL.
+fn body(doc: &[u8]) -> &[u8] {
+    &doc[4..doc.len() - 1]
+}
+
+fn cstring(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let end = bytes.iter().position(|b| *b == 0)?;
+    Some((&bytes[..end], &bytes[end + 1..]))
+}
+
+// Size of an element value of type `kind` that starts at `data`, or
+// `None` for unknown types and values running past the end.
+fn value_len(kind: u8, data: &[u8]) -> Option<usize> {
+    let len = match kind {
+        0x06 | 0x0A | 0x7F | 0xFF => 0,
+        0x08 => 1,
+        0x10 => 4,
+        0x01 | 0x09 | 0x11 | 0x12 => 8,
+        0x07 => 12,
+        0x13 => 16,
+        0x02 | 0x0D | 0x0E => 4 + read_len(data).filter(|n| *n >= 1)?,
+        0x03 | 0x04 | 0x0F => read_len(data).filter(|n| *n >= 5)?,
+        0x05 => 5 + read_len(data)?,
+        0x0C => 16 + read_len(data)?,
+        0x0B => {
+            let (_, after_pattern) = cstring(data)?;
+            let (_, after_options) = cstring(after_pattern)?;
+            data.len() - after_options.len()
+        }
+        _ => return None,
+    };
+    (len <= data.len()).then_some(len)
+}
+
+struct Element<'a> {
+    kind: u8,
+    data: &'a [u8],
+}
+
+struct Elements<'a> {
+    rest: &'a [u8],
+    value: Option<Element<'a>>,
+}
+
+impl<'a> Elements<'a> {
+    fn new(body: &'a [u8]) -> Self {
+        Self {
+            rest: body,
+            value: None,
+        }
+    }
+
+    fn next_element(
+        &mut self,
+    ) -> Result<Option<(String, Element<'a>)>, ValueError> {
+        let Some((&kind, after_kind)) = self.rest.split_first() else {
+            return Ok(None);
+        };
+        let malformed = || ValueError::custom("malformed BSON element");
+        let (key, data) = cstring(after_kind).ok_or_else(malformed)?;
+        let len = value_len(kind, data).ok_or_else(|| {
+            ValueError::custom(format!(
+                "bad BSON element of type 0x{kind:02x}"
+            ))
+        })?;
+        let (value, rest) = data.split_at(len);
+        self.rest = rest;
+        let key = String::from_utf8_lossy(key).into_owned();
+        Ok(Some((key, Element { kind, data: value })))
+    }
+}
+
+impl<'de> MapAccess<'de> for Elements<'_> {
+    type Error = ValueError;
+
+    fn next_key_seed<K>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let Some((key, element)) = self.next_element()? else {
+            return Ok(None);
+        };
+        self.value = Some(element);
+        seed.deserialize(key.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let element = self
+            .value
+            .take()
+            .ok_or_else(|| ValueError::custom("BSON value without a key"))?;
+        seed.deserialize(element)
+    }
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_> {
+    type Error = ValueError;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.next_element()? {
+            Some((_, element)) => seed.deserialize(element).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+// A framed document (length prefix through trailing NUL).
+struct Document<'a>(&'a [u8]);
+
+impl<'de> serde::Deserializer<'de> for Document<'_> {
+    type Error = ValueError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(Elements::new(body(self.0)))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, ValueError> for Document<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+fn le_bytes<const N: usize>(data: &[u8]) -> [u8; N] {
+    let mut out = [0u8; N];
+    out.copy_from_slice(&data[..N]);
+    out
+}
+
+// String-like values: int32 length (including the NUL), bytes, NUL.
+fn bson_string(data: &[u8]) -> String {
+    let len = data.len().saturating_sub(5);
+    String::from_utf8_lossy(&data[4..4 + len]).into_owned()
+}
+
+fn extended<'de, V>(
+    value: serde_json::Value,
+    visitor: V,
+) -> Result<V::Value, ValueError>
+where
+    V: Visitor<'de>,
+{
+    serde::Deserializer::deserialize_any(value, visitor)
+        .map_err(ValueError::custom)
+}
+
+impl<'de> serde::Deserializer<'de> for Element<'_> {
+    type Error = ValueError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let data = self.data;
+        match self.kind {
+            0x01 => visitor.visit_f64(f64::from_le_bytes(le_bytes(data))),
+            0x02 | 0x0E => visitor.visit_string(bson_string(data)),
+            0x03 => Document(data).deserialize_any(visitor),
+            0x04 => visitor.visit_seq(Elements::new(body(data))),
+            0x05 => binary(&data[5..], data[4], visitor),
+            0x08 => visitor.visit_bool(data[0] != 0),
+            0x10 => visitor.visit_i32(i32::from_le_bytes(le_bytes(data))),
+            0x12 => visitor.visit_i64(i64::from_le_bytes(le_bytes(data))),
+            0x06 | 0x0A => visitor.visit_unit(),
+            kind => extended(extended_value(kind, data)?, visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+// UUIDs (subtypes 3 and 4) are shown in their usual dashed form; other
+// payloads become hex like binary data in the other formats.
+fn binary<'de, V>(
+    payload: &[u8],
+    subtype: u8,
+    visitor: V,
+) -> Result<V::Value, ValueError>
+where
+    V: Visitor<'de>,
+{
+    if matches!(subtype, 3 | 4) && payload.len() == 16 {
+        let hex = to_hex(payload);
+        let uuid = format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        );
+        return extended(json!({ "$uuid": uuid }), visitor);
+    }
+    visitor.visit_bytes(payload)
+}
+
+fn extended_value(
+    kind: u8,
+    data: &[u8],
+) -> Result<serde_json::Value, ValueError> {
+    Ok(match kind {
+        0x07 => json!({ "$oid": to_hex(data) }),
+        0x09 => json!({
+            "$date": format_datetime(i64::from_le_bytes(le_bytes(data)))
+        }),
+        0x0B => {
+            let (pattern, rest) = cstring(data).unwrap_or_default();
+            let (options, _) = cstring(rest).unwrap_or_default();
+            json!({
+                "$regex": String::from_utf8_lossy(pattern),
+                "$options": String::from_utf8_lossy(options),
+            })
+        }
+        0x0C => {
+            let (name, id) = data.split_at(data.len() - 12);
+            json!({ "$ref": bson_string(name), "$id": { "$oid": to_hex(id) } })
+        }
+        0x0D => json!({ "$code": bson_string(data) }),
+        0x0F => code_with_scope(data)?,
+        0x11 => json!({ "$timestamp": {
+            "t": u32::from_le_bytes(le_bytes(&data[4..])),
+            "i": u32::from_le_bytes(le_bytes(data)),
+        }}),
+        0x13 => json!({ "$numberDecimal": format_decimal128(data) }),
+        0x7F => json!({ "$maxKey": 1 }),
+        0xFF => json!({ "$minKey": 1 }),
+        other => {
+            return Err(ValueError::custom(format!(
+                "unsupported BSON element type 0x{other:02x}"
+            )));
+        }
+    })
+}
+
+// int32 total length, code string, scope document.
+fn code_with_scope(data: &[u8]) -> Result<serde_json::Value, ValueError> {
+    let rest = &data[4..];
+    let code_len = value_len(0x02, rest)
+        .ok_or_else(|| ValueError::custom("malformed BSON code with scope"))?;
+    let (code, scope) = rest.split_at(code_len);
+    if read_len(scope) != Some(scope.len()) {
+        return Err(ValueError::custom("malformed BSON code with scope"));
+    }
+    let scope = serde_json::Value::deserialize(Document(scope))?;
+    Ok(json!({ "$code": bson_string(code), "$scope": scope }))
+}
+
+// Milliseconds since the Unix epoch as an RFC 3339 UTC timestamp, using
+// the proleptic Gregorian calendar (days-to-civil conversion).
+fn format_datetime(millis: i64) -> String {
+    let days = millis.div_euclid(86_400_000);
+    let ms_of_day = millis.rem_euclid(86_400_000);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    )
+}
+
+// IEEE 754-2008 decimal128 (BID encoding) rendered as the BSON spec's
+// canonical string: plain notation for small exponents, scientific
+// otherwise.
+fn format_decimal128(data: &[u8]) -> String {
+    const MAX_COEFFICIENT: u128 = 9_999_999_999_999_999_999_999_999_999_999;
+    const EXPONENT_BIAS: i64 = 6176;
+    let low = u64::from_le_bytes(le_bytes(data));
+    let high = u64::from_le_bytes(le_bytes(&data[8..]));
+    let sign = if high >> 63 == 1 { "-" } else { "" };
+    let (biased_exponent, coefficient) = if (high >> 61) & 0b11 == 0b11 {
+        match (high >> 58) & 0b1_1111 {
+            0b1_1111 => return "NaN".to_string(),
+            0b1_1110 => return format!("{sign}Infinity"),
+            // Coefficients in this form exceed the maximum: non-canonical.
+            _ => ((high >> 47) & 0x3fff, 0),
+        }
+    } else {
+        let coefficient =
+            (u128::from(high & 0x1_ffff_ffff_ffff) << 64) | u128::from(low);
+        (
+            (high >> 49) & 0x3fff,
+            if coefficient > MAX_COEFFICIENT {
+                0
+            } else {
+                coefficient
+            },
+        )
+    };
+    let exponent =
+        i64::try_from(biased_exponent).unwrap_or_default() - EXPONENT_BIAS;
+    format!("{sign}{}", decimal_digits(coefficient, exponent))
+}
+
+fn decimal_digits(coefficient: u128, exponent: i64) -> String {
+    let digits = coefficient.to_string();
+    let ndigits = i64::try_from(digits.len()).unwrap_or_default();
+    let adjusted = exponent + ndigits - 1;
+    if exponent > 0 || adjusted < -6 {
+        let (first, rest) = digits.split_at(1);
+        let dot = if rest.is_empty() { "" } else { "." };
+        let plus = if adjusted >= 0 { "+" } else { "" };
+        return format!("{first}{dot}{rest}E{plus}{adjusted}");
+    }
+    if exponent == 0 {
+        return digits;
+    }
+    let scale = usize::try_from(-exponent).unwrap_or_default();
+    if digits.len() > scale {
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        format!("{int}.{frac}")
+    } else {
+        format!("0.{}{digits}", "0".repeat(scale - digits.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::NodeKind;
+
+    // Frame raw elements as a document.
+    fn doc(elements: &[u8]) -> Vec<u8> {
+        let len = i32::try_from(elements.len() + 5).unwrap();
+        let mut out = len.to_le_bytes().to_vec();
+        out.extend_from_slice(elements);
+        out.push(0);
+        out
+    }
+
+    fn element(kind: u8, key: &str, value: &[u8]) -> Vec<u8> {
+        let mut out = vec![kind];
+        out.extend_from_slice(key.as_bytes());
+        out.push(0);
+        out.extend_from_slice(value);
+        out
+    }
+
+    fn to_json(bytes: &[u8]) -> serde_json::Value {
+        serde_json::Value::deserialize(BsonInput::new(bytes).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn documents_map_to_extended_json() {
+        let mut elements = element(0x07, "_id", &[0xab; 12]);
+        elements.extend(element(0x10, "n", &42i32.to_le_bytes()));
+        elements.extend(element(
+            0x09,
+            "at",
+            &1_700_000_000_123i64.to_le_bytes(),
+        ));
+        elements.extend(element(0x02, "s", b"\x03\0\0\0hi\0"));
+        elements.extend(element(0x04, "a", &doc(&element(0x08, "0", &[1]))));
+        assert_eq!(
+            to_json(&doc(&elements)),
+            json!({
+                "_id": {"$oid": "abababababababababababab"},
+                "n": 42,
+                "at": {"$date": "2023-11-14T22:13:20.123Z"},
+                "s": "hi",
+                "a": [true],
+            })
+        );
+    }
+
+    #[test]
+    fn concatenated_documents_become_an_array() {
+        let mut bytes = doc(&element(0x10, "i", &1i32.to_le_bytes()));
+        bytes.extend(doc(&element(0x10, "i", &2i32.to_le_bytes())));
+        assert!(is_bson_stream(&bytes));
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena = build_bson_tree_arena_from_bytes(&bytes, &cfg).unwrap();
+        let root = &arena.nodes[arena.root_id];
+        assert_eq!(root.kind, NodeKind::Array);
+        assert_eq!(root.array_len, Some(2));
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn decimal128_uses_canonical_strings() {
+        let dec = |coefficient: u128, exponent: i64| {
+            let biased = u128::try_from(exponent + 6176).unwrap();
+            (coefficient | (biased << 113)).to_le_bytes()
+        };
+        assert_eq!(format_decimal128(&dec(1, 0)), "1");
+        assert_eq!(format_decimal128(&dec(12345, -2)), "123.45");
+        assert_eq!(format_decimal128(&dec(5, -3)), "0.005");
+        assert_eq!(format_decimal128(&dec(1, -10)), "1E-10");
+        assert_eq!(format_decimal128(&dec(123, 3)), "1.23E+5");
+    }
+
+    #[test]
+    fn rejects_bad_framing() {
+        assert!(!is_bson_stream(b"{\"a\": 1}"));
+        assert!(!is_bson_stream(&[9, 0, 0, 0, 0x10, b'a', 0, 1]));
+        assert!(is_bson_prefix(
+            &doc(&element(0x10, "a", &[1, 0, 0, 0]))[..7]
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::{Context, Result, bail};
+use ciborium::Value;
+use serde::Deserialize as _;
+use serde::de::value::{
+    Error as ValueError, MapDeserializer, SeqDeserializer,
+};
+use serde::de::{IntoDeserializer, Visitor};
+
+use crate::PriorityConfig;
+use crate::json_ingest::{
+    build_tree_arena_from_deserializer,
+    build_tree_arena_from_many_deserializers,
+};
+use crate::utils::tree_arena::JsonTreeArena;
+
+/// Build an arena from a single CBOR data item. Tags are transparent
+/// (except bignums, which become numbers), byte strings become hex and
+/// map keys that are not text are rendered as JSON.
+pub fn build_cbor_tree_arena_from_bytes(
+    bytes: &[u8],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let value = decode_cbor(bytes)?;
+    build_tree_arena_from_deserializer(CborNode(&value), config)
+}
+
+pub fn build_cbor_tree_arena_from_many(
+    inputs: &[(String, Vec<u8>)],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut values: Vec<(String, Value)> = Vec::with_capacity(inputs.len());
+    for (key, bytes) in inputs {
+        let value = decode_cbor(bytes)
+            .with_context(|| format!("failed to parse {key}"))?;
+        values.push((key.clone(), value));
+    }
+    let nodes = values
+        .iter()
+        .map(|(key, value)| (key.clone(), CborNode(value)))
+        .collect();
+    build_tree_arena_from_many_deserializers(nodes, config)
+}
+
+fn decode_cbor(bytes: &[u8]) -> Result<Value> {
+    let mut rest = bytes;
+    let value: Value = ciborium::from_reader(&mut rest)
+        .map_err(|e| anyhow::anyhow!("invalid CBOR: {e}"))?;
+    if !rest.is_empty() {
+        bail!("trailing data after CBOR item ({} bytes)", rest.len());
+    }
+    Ok(value)
+}
+
+/// True when `bytes` hold exactly one well-formed CBOR data item.
+pub(crate) fn is_single_cbor_item(bytes: &[u8]) -> bool {
+    let mut rest = bytes;
+    let parsed: Result<serde::de::IgnoredAny, _> =
+        ciborium::from_reader(&mut rest);
+    parsed.is_ok() && rest.is_empty()
+}
+
+/// True when `prefix`, cut off from a longer input, reads as the start of
+/// a CBOR data item: decoding only fails for lack of data.
+pub(crate) fn is_cbor_prefix(prefix: &[u8]) -> bool {
+    let mut rest = prefix;
+    let parsed: Result<serde::de::IgnoredAny, _> =
+        ciborium::from_reader(&mut rest);
+    match parsed {
+        Ok(_) => rest.is_empty(),
+        Err(ciborium::de::Error::Io(e)) => {
+            e.kind() == std::io::ErrorKind::UnexpectedEof
+        }
+        Err(_) => false,
+    }
+}
+
+// Borrowed view of a decoded item handed to the shared serde builder.
+#[derive(Clone, Copy)]
+struct CborNode<'a>(&'a Value);
+
+// Bignums (tags 2 and 3) carry a big-endian magnitude; those that fit in
+// 128 bits are numbers, larger ones stay hex strings.
+fn bignum(tag: u64, value: &Value) -> Option<i128> {
+    const BIGPOS: u64 = 2;
+    const BIGNEG: u64 = 3;
+    let bytes = value.as_bytes()?;
+    if bytes.len() > 16 || !(tag == BIGPOS || tag == BIGNEG) {
+        return None;
+    }
+    let magnitude = bytes.iter().try_fold(0u128, |acc, b| {
+        acc.checked_mul(256)?.checked_add(*b as u128)
+    })?;
+    let n = i128::try_from(magnitude).ok()?;
+    Some(if tag == BIGNEG { -1 - n } else { n })
+}
+
+fn key_text(key: &Value) -> String {
+    match key {
+        Value::Text(s) => s.clone(),
+        other => serde_json::Value::deserialize(CborNode(other))
+            .map_or_else(|_| format!("{other:?}"), |v| v.to_string()),
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for CborNode<'_> {
+    type Error = ValueError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::Integer(i) => visitor.visit_i128(i128::from(*i)),
+            Value::Bytes(b) => visitor.visit_bytes(b),
+            Value::Float(f) => visitor.visit_f64(*f),
+            Value::Text(s) => visitor.visit_str(s),
+            Value::Bool(b) => visitor.visit_bool(*b),
+            Value::Tag(tag, inner) => match bignum(*tag, inner) {
+                Some(n) => visitor.visit_i128(n),
+                None => CborNode(inner).deserialize_any(visitor),
+            },
+            Value::Array(items) => visitor
+                .visit_seq(SeqDeserializer::new(items.iter().map(CborNode))),
+            Value::Map(entries) => visitor.visit_map(MapDeserializer::new(
+                entries.iter().map(|(k, v)| (key_text(k), CborNode(v))),
+            )),
+            // Null, undefined and other simple values.
+            _ => visitor.visit_unit(),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, ValueError> for CborNode<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::NodeKind;
+
+    fn encode(value: &Value) -> Vec<u8> {
+        let mut out = Vec::new();
+        ciborium::into_writer(value, &mut out).expect("encode");
+        out
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn tags_bytes_and_integer_keys() {
+        let value = Value::Map(vec![
+            (Value::Integer(7.into()), Value::Bytes(vec![0xab, 0x01])),
+            (
+                Value::Text("when".into()),
+                Value::Tag(0, Box::new(Value::Text("2024-01-01".into()))),
+            ),
+            (
+                Value::Text("big".into()),
+                Value::Tag(
+                    2,
+                    Box::new(Value::Bytes(vec![1, 0, 0, 0, 0, 0, 0, 0, 0])),
+                ),
+            ),
+        ]);
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena = build_cbor_tree_arena_from_bytes(&encode(&value), &cfg)
+            .expect("parse");
+        let root = &arena.nodes[arena.root_id];
+        assert_eq!(root.kind, NodeKind::Object);
+        let keys = &arena.obj_keys[root.obj_keys_start..][..3];
+        assert_eq!(keys, ["7", "when", "big"]);
+        let child =
+            |i: usize| &arena.nodes[arena.children[root.children_start + i]];
+        assert_eq!(child(0).string_value.as_deref(), Some("ab01"));
+        assert_eq!(child(1).string_value.as_deref(), Some("2024-01-01"));
+        assert_eq!(
+            child(2).atomic_token.as_deref(),
+            Some("18446744073709551616")
+        );
+    }
+
+    #[test]
+    fn validity_checks() {
+        let bytes = encode(&Value::Array(vec![Value::Bool(true); 3]));
+        assert!(is_single_cbor_item(&bytes));
+        assert!(!is_single_cbor_item(&bytes[..2]));
+        assert!(is_cbor_prefix(&bytes[..2]));
+        assert!(!is_cbor_prefix(&[0xff, 0x00]));
+    }
+}
@@ -0,0 +1,159 @@
+use std::io::{self, BufRead, Read};
+
+/// Compressed containers recognized from their magic bytes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Xz,
+    Bzip2,
+}
+
+// Filename suffixes of compressed files; stripping one leaves the name the
+// inner format is detected from.
+const SUFFIXES: &[&str] = &["gz", "gzip", "zst", "zstd", "xz", "bz2"];
+
+impl Compression {
+    /// Classify the first bytes of an input, `None` when it is not
+    /// compressed.
+    pub fn sniff(prefix: &[u8]) -> Option<Self> {
+        if prefix.starts_with(b"\x1f\x8b") {
+            Some(Compression::Gzip)
+        } else if prefix.starts_with(b"\x28\xb5\x2f\xfd") {
+            Some(Compression::Zstd)
+        } else if prefix.starts_with(b"\xfd7zXZ\0") {
+            Some(Compression::Xz)
+        } else if prefix.starts_with(b"BZh")
+            && prefix
+                .get(3)
+                .is_some_and(|level| (b'1'..=b'9').contains(level))
+        {
+            Some(Compression::Bzip2)
+        } else {
+            None
+        }
+    }
+
+    /// Wrap `reader` in a streaming decoder. Concatenated members, frames
+    /// and streams are decoded one after another, as `zcat` does.
+    pub fn decoder<'a>(
+        self,
+        reader: impl BufRead + 'a,
+    ) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Compression::Gzip => {
+                Box::new(flate2::bufread::MultiGzDecoder::new(reader))
+            }
+            Compression::Zstd => {
+                Box::new(zstd::stream::read::Decoder::with_buffer(reader)?)
+            }
+            Compression::Xz => Box::new(
+                liblzma::bufread::XzDecoder::new_multi_decoder(reader),
+            ),
+            Compression::Bzip2 => {
+                Box::new(bzip2::bufread::MultiBzDecoder::new(reader))
+            }
+        })
+    }
+}
+
+/// Read `reader` decompressed when it starts with the magic bytes of a
+/// supported compression format, and as it is otherwise. Decoding streams,
+/// so the compressed bytes are never held in memory as a whole; callers
+/// still read the decoded input to the end before parsing it.
+pub fn decompressing_reader<'a>(
+    mut reader: impl BufRead + 'a,
+) -> io::Result<Box<dyn Read + 'a>> {
+    match Compression::sniff(reader.fill_buf()?) {
+        Some(compression) => compression.decoder(reader),
+        None => Ok(Box::new(reader)),
+    }
+}
+
+/// `data.json.gz` → `data.json`: drop a compression suffix so the format
+/// is chosen from the inner extension.
+pub fn strip_compression_suffix(name: &str) -> &str {
+    match name.rsplit_once('.') {
+        Some((stem, ext))
+            if !stem.is_empty()
+                && SUFFIXES.iter().any(|s| ext.eq_ignore_ascii_case(s)) =>
+        {
+            stem
+        }
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn decompress(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        decompressing_reader(bytes)
+            .unwrap()
+            .read_to_end(&mut out)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn every_format_round_trips_through_its_magic_bytes() {
+        let text = b"{\"a\": [1, 2, 3]}";
+        let mut gz =
+            flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        gz.write_all(text).unwrap();
+        let gz = gz.finish().unwrap();
+        let zst = zstd::encode_all(&text[..], 0).unwrap();
+        let mut xz = liblzma::write::XzEncoder::new(Vec::new(), 6);
+        xz.write_all(text).unwrap();
+        let xz = xz.finish().unwrap();
+        let mut bz =
+            bzip2::write::BzEncoder::new(Vec::new(), Default::default());
+        bz.write_all(text).unwrap();
+        let bz = bz.finish().unwrap();
+        for (bytes, expected) in [
+            (&gz, Compression::Gzip),
+            (&zst, Compression::Zstd),
+            (&xz, Compression::Xz),
+            (&bz, Compression::Bzip2),
+        ] {
+            assert_eq!(Compression::sniff(bytes), Some(expected));
+            assert_eq!(decompress(bytes), text);
+        }
+        assert_eq!(decompress(text), text);
+        assert_eq!(Compression::sniff(b"BZh? not bzip2"), None);
+    }
+
+    #[test]
+    fn concatenated_gzip_members_are_read_in_sequence() {
+        let member = |text: &[u8]| {
+            let mut gz =
+                flate2::write::GzEncoder::new(Vec::new(), Default::default());
+            gz.write_all(text).unwrap();
+            gz.finish().unwrap()
+        };
+        let mut both = member(b"{\"a\":1}\n");
+        both.extend(member(b"{\"a\":2}\n"));
+        assert_eq!(decompress(&both), b"{\"a\":1}\n{\"a\":2}\n");
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn compression_suffix_is_stripped_once() {
+        assert_eq!(strip_compression_suffix("data.json.gz"), "data.json");
+        assert_eq!(strip_compression_suffix("logs.NDJSON.ZST"), "logs.NDJSON");
+        assert_eq!(strip_compression_suffix("a.yaml.xz"), "a.yaml");
+        assert_eq!(strip_compression_suffix("a.csv.bz2"), "a.csv");
+        assert_eq!(strip_compression_suffix("plain.json"), "plain.json");
+        assert_eq!(strip_compression_suffix(".gz"), ".gz");
+    }
+}
@@ -0,0 +1,220 @@
+use anyhow::Result;
+use serde::de::value::{Error as ValueError, MapDeserializer};
+use serde::de::{DeserializeSeed, Error as _, SeqAccess};
+
+use crate::PriorityConfig;
+use crate::json_ingest::{
+    build_tree_arena_from_many_seqs, build_tree_arena_from_seq,
+};
+use crate::utils::tree_arena::JsonTreeArena;
+
+pub fn build_csv_tree_arena_from_bytes(
+    bytes: &[u8],
+    delimiter: u8,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut rows = CsvRows::new(bytes, delimiter)?;
+    let mut arena = build_tree_arena_from_seq(&mut rows, config)?;
+    arena.preserve_source_key_order();
+    let table = arena.root_id;
+    keep_header_of_empty_table(&mut arena, table, rows.columns);
+    Ok(arena)
+}
+
+pub fn build_csv_tree_arena_from_many(
+    inputs: &[(String, Vec<u8>)],
+    delimiter: u8,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut readers: Vec<(String, CsvRows)> = Vec::with_capacity(inputs.len());
+    for (key, bytes) in inputs {
+        readers.push((key.clone(), CsvRows::new(bytes, delimiter)?));
+    }
+    let headers: Vec<Vec<String>> =
+        readers.iter().map(|(_, r)| r.columns.clone()).collect();
+    let mut arena = build_tree_arena_from_many_seqs(readers, config)?;
+    arena.preserve_source_key_order();
+    let root = &arena.nodes[arena.root_id];
+    let tables =
+        arena.children[root.children_start..][..root.children_len].to_vec();
+    for (table, columns) in tables.into_iter().zip(headers) {
+        keep_header_of_empty_table(&mut arena, table, columns);
+    }
+    Ok(arena)
+}
+
+// A file with a header but no data rows reads as an empty array; its
+// column names are kept beside it so CSV output can still show them.
+fn keep_header_of_empty_table(
+    arena: &mut JsonTreeArena,
+    table: usize,
+    columns: Vec<String>,
+) {
+    let n = &arena.nodes[table];
+    if n.array_len.unwrap_or(n.children_len) == 0 && !columns.is_empty() {
+        arena.csv_headers.insert(table, columns);
+    }
+}
+
+// Sequence access over the records of a delimited file. The first record
+// is the header; every following record is handed to the array samplers
+// as a map from column name to field, so rows arrive in the arena as
+// objects while keeping their original row indices.
+struct CsvRows<'a> {
+    reader: csv::Reader<&'a [u8]>,
+    columns: Vec<String>,
+    record: csv::StringRecord,
+}
+
+impl<'a> CsvRows<'a> {
+    fn new(bytes: &'a [u8], delimiter: u8) -> Result<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .from_reader(bytes);
+        let mut columns = Vec::new();
+        for name in reader.headers()? {
+            push_column(&mut columns, name);
+        }
+        Ok(Self {
+            reader,
+            columns,
+            record: csv::StringRecord::new(),
+        })
+    }
+
+    // Records longer than the header get positional names for the extra
+    // fields (`column7`, 1-based) so no data is dropped.
+    fn name_extra_columns(&mut self) {
+        for i in self.columns.len()..self.record.len() {
+            push_column(&mut self.columns, &format!("column{}", i + 1));
+        }
+    }
+}
+
+// Rows become objects, so every column needs its own key: a name already
+// taken gets the first free numeric suffix (`a,a` reads as `a`, `a_2`).
+fn push_column(columns: &mut Vec<String>, name: &str) {
+    let mut unique = name.to_owned();
+    let mut n = 2;
+    while columns.contains(&unique) {
+        unique = format!("{name}_{n}");
+        n += 1;
+    }
+    columns.push(unique);
+}
+
+impl<'de> SeqAccess<'de> for CsvRows<'_> {
+    type Error = ValueError;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if !self
+            .reader
+            .read_record(&mut self.record)
+            .map_err(ValueError::custom)?
+        {
+            return Ok(None);
+        }
+        self.name_extra_columns();
+        let fields = self
+            .columns
+            .iter()
+            .map(String::as_str)
+            .zip(self.record.iter());
+        seed.deserialize(MapDeserializer::new(fields)).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::NodeKind;
+
+    fn row_keys(arena: &JsonTreeArena, row: usize) -> Vec<&str> {
+        let root = &arena.nodes[arena.root_id];
+        let n = &arena.nodes[arena.children[root.children_start + row]];
+        arena.obj_keys[n.obj_keys_start..][..n.obj_keys_len]
+            .iter()
+            .map(String::as_str)
+            .collect()
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn rows_become_objects_in_column_order() {
+        let src = "name,age,city\nann,31,Oslo\nbob,42\ncid,7,Rome,extra\n";
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            build_csv_tree_arena_from_bytes(src.as_bytes(), b',', &cfg)
+                .expect("parse csv");
+        let root = &arena.nodes[arena.root_id];
+        assert_eq!(root.kind, NodeKind::Array);
+        assert_eq!(root.array_len, Some(3));
+        assert_eq!(row_keys(&arena, 0), ["name", "age", "city"]);
+        assert_eq!(row_keys(&arena, 1), ["name", "age"]);
+        assert_eq!(row_keys(&arena, 2), ["name", "age", "city", "column4"]);
+        let first = &arena.nodes[arena.children[root.children_start]];
+        assert!(first.preserve_key_order);
+    }
+
+    #[test]
+    fn repeated_headers_get_unique_names() {
+        let src = "a,a,column3,b,a\n1,2,3,4,5,6\n";
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            build_csv_tree_arena_from_bytes(src.as_bytes(), b',', &cfg)
+                .expect("parse csv");
+        assert_eq!(
+            row_keys(&arena, 0),
+            ["a", "a_2", "column3", "b", "a_3", "column6"]
+        );
+    }
+
+    #[test]
+    fn tab_delimiter_and_quoted_fields() {
+        let src = "a\tb\n\"x\ty\"\t\"multi\nline\"\n";
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            build_csv_tree_arena_from_bytes(src.as_bytes(), b'\t', &cfg)
+                .expect("parse tsv");
+        let values: Vec<_> = arena
+            .nodes
+            .iter()
+            .filter_map(|n| n.string_value.as_deref())
+            .collect();
+        assert_eq!(values, ["x\ty", "multi\nline"]);
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn sampled_rows_keep_original_indices() {
+        let mut src = String::from("n\n");
+        for i in 0..100 {
+            src.push_str(&format!("{i}\n"));
+        }
+        let mut cfg = PriorityConfig::new(usize::MAX, 10);
+        cfg.array_sampler = crate::ArraySamplerStrategy::Tail;
+        let arena =
+            build_csv_tree_arena_from_bytes(src.as_bytes(), b',', &cfg)
+                .expect("parse csv");
+        let root = &arena.nodes[arena.root_id];
+        assert_eq!(root.array_len, Some(100));
+        assert_eq!(root.children_len, 10);
+        let indices = &arena.arr_indices[root.arr_indices_start..]
+            [..root.arr_indices_len];
+        assert_eq!(indices.first(), Some(&90));
+        assert_eq!(indices.last(), Some(&99));
+    }
+}
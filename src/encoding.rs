@@ -0,0 +1,126 @@
+use encoding_rs::{UTF_8, UTF_16BE, UTF_16LE};
+
+pub use encoding_rs::Encoding;
+
+/// Number of leading bytes inspected when looking for UTF-16 without a BOM.
+const SAMPLE_LEN: usize = 1024;
+
+/// Detect UTF-16 input, either from its byte order mark or, without one,
+/// from the NUL bytes that ASCII characters leave in every other byte.
+pub fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    match Encoding::for_bom(bytes) {
+        Some((encoding, _)) if encoding != UTF_8 => Some(encoding),
+        Some(_) => None,
+        None => utf16_without_bom(bytes),
+    }
+}
+
+fn utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(SAMPLE_LEN)];
+    if looks_like_utf16(sample, u16::from_le_bytes) {
+        Some(UTF_16LE)
+    } else if looks_like_utf16(sample, u16::from_be_bytes) {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+// Read as UTF-16, mostly-ASCII text has at least every other code unit in
+// the printable ASCII range and no control characters besides whitespace.
+fn looks_like_utf16(sample: &[u8], unit: fn([u8; 2]) -> u16) -> bool {
+    let mut units = 0usize;
+    let mut ascii = 0usize;
+    for pair in sample.chunks_exact(2) {
+        let u = unit([pair[0], pair[1]]);
+        if u < 0x20 && !matches!(u, 0x09 | 0x0A | 0x0D) {
+            return false;
+        }
+        units += 1;
+        ascii += usize::from(u < 0x7F);
+    }
+    units >= 2 && ascii * 2 >= units
+}
+
+/// Transcode text input to UTF-8 before it reaches a text format's parser.
+/// A byte order mark decides the encoding and is dropped; otherwise
+/// `fallback` (from `--encoding`) is used, then BOM-less UTF-16 detection.
+/// Anything else is passed through untouched and read as UTF-8.
+pub(crate) fn decode_to_utf8(
+    mut bytes: Vec<u8>,
+    fallback: Option<&'static Encoding>,
+) -> Vec<u8> {
+    let Some((encoding, bom_len)) = source_encoding(&bytes, fallback) else {
+        return bytes;
+    };
+    if encoding == UTF_8 {
+        bytes.drain(..bom_len);
+        return bytes;
+    }
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+    text.into_owned().into_bytes()
+}
+
+/// For input that can be parsed where it is, without transcoding: the
+/// length of the UTF-8 byte order mark to skip. `None` when
+/// `decode_to_utf8` would have to transcode it.
+pub(crate) fn utf8_bom_len(
+    bytes: &[u8],
+    fallback: Option<&'static Encoding>,
+) -> Option<usize> {
+    match source_encoding(bytes, fallback) {
+        None => Some(0),
+        Some((encoding, bom_len)) => (encoding == UTF_8).then_some(bom_len),
+    }
+}
+
+// The encoding `bytes` are written in and the length of its byte order
+// mark, or `None` when they are read as UTF-8 as they are.
+fn source_encoding(
+    bytes: &[u8],
+    fallback: Option<&'static Encoding>,
+) -> Option<(&'static Encoding, usize)> {
+    Encoding::for_bom(bytes).or_else(|| {
+        fallback
+            .or_else(|| utf16_without_bom(bytes))
+            .map(|encoding| (encoding, 0))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn byte_order_marks_pick_the_encoding() {
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(utf16le("{\"a\": \"é\"}"));
+        assert_eq!(decode_to_utf8(le, None), "{\"a\": \"é\"}".as_bytes());
+        let be: Vec<u8> = [0xFE, 0xFF, 0x00, b'1'].to_vec();
+        assert_eq!(decode_to_utf8(be, None), b"1");
+        let utf8 = b"\xEF\xBB\xBFa: 1".to_vec();
+        assert_eq!(decode_to_utf8(utf8, None), b"a: 1");
+    }
+
+    #[test]
+    fn utf16_without_bom_is_detected() {
+        let bytes = utf16le("[1, 2, 3]");
+        assert_eq!(sniff_utf16(&bytes), Some(UTF_16LE));
+        assert_eq!(decode_to_utf8(bytes, None), b"[1, 2, 3]");
+        assert_eq!(sniff_utf16(b"[1, 2, 3]"), None);
+    }
+
+    #[test]
+    fn fallback_decodes_legacy_single_byte_input() {
+        let latin1 = Encoding::for_label(b"latin1");
+        assert_eq!(
+            decode_to_utf8(b"caf\xE9".to_vec(), latin1),
+            "café".as_bytes()
+        );
+        assert_eq!(decode_to_utf8(b"caf\xE9".to_vec(), None), b"caf\xE9");
+    }
+}
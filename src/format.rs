@@ -1,10 +1,27 @@
-/// Logical data formats detected from filenames or paths.
-/// This is independent from input ingestion; it controls how an item
-/// should be rendered within a fileset based on its filename extension.
+/// Logical data formats detected from filenames, paths or content.
+/// Filesets in auto mode use it to pick both the ingest path and the
+/// output template of every entry.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Format {
     Json,
+    Json5,
+    Jsonl,
     Yaml,
+    Toml,
+    Csv,
+    Tsv,
+    Xml,
+    Html,
+    Msgpack,
+    Cbor,
+    Bson,
+    Markdown,
+    Ini,
+    Dotenv,
+    Properties,
+    Hcl,
+    // `key=value` log lines.
+    Logfmt,
     Unknown,
 }
 
@@ -13,14 +30,70 @@ impl Format {
     /// Uses `Path::extension` and ASCII case-insensitive comparison to avoid
     /// allocations. Known mappings:
     /// - .json -> Json
+    /// - .json5, .jsonc -> Json5
+    /// - .jsonl, .ndjson -> Jsonl
     /// - .yaml, .yml -> Yaml
+    /// - .toml -> Toml
+    /// - .csv -> Csv
+    /// - .tsv, .tab -> Tsv
+    /// - .xml, .xhtml, .svg, .xsd, .xsl, .xslt, .wsdl, .rss, .atom -> Xml
+    /// - .html, .htm -> Html
+    /// - .msgpack, .mpk -> Msgpack
+    /// - .cbor -> Cbor
+    /// - .bson -> Bson
+    /// - .md, .markdown -> Markdown
+    /// - .ini, .cfg -> Ini
+    /// - .env, and files named `.env` or `.env.*` -> Dotenv
+    /// - .properties -> Properties
+    /// - .tf, .tfvars, .hcl -> Hcl
+    /// - .logfmt -> Logfmt
+    ///
+    /// A compression suffix (.gz, .zst, .xz, .bz2) is skipped, so
+    /// `data.json.gz` maps like `data.json`.
     pub fn from_filename(name: &str) -> Self {
         use std::path::Path;
         const EXT_FORMATS: &[(&str, Format)] = &[
             ("json", Format::Json),
+            ("json5", Format::Json5),
+            ("jsonc", Format::Json5),
+            ("jsonl", Format::Jsonl),
+            ("ndjson", Format::Jsonl),
             ("yaml", Format::Yaml),
             ("yml", Format::Yaml),
+            ("toml", Format::Toml),
+            ("csv", Format::Csv),
+            ("tsv", Format::Tsv),
+            ("tab", Format::Tsv),
+            ("xml", Format::Xml),
+            ("xhtml", Format::Xml),
+            ("svg", Format::Xml),
+            ("xsd", Format::Xml),
+            ("xsl", Format::Xml),
+            ("xslt", Format::Xml),
+            ("wsdl", Format::Xml),
+            ("rss", Format::Xml),
+            ("atom", Format::Xml),
+            ("html", Format::Html),
+            ("htm", Format::Html),
+            ("msgpack", Format::Msgpack),
+            ("mpk", Format::Msgpack),
+            ("cbor", Format::Cbor),
+            ("bson", Format::Bson),
+            ("md", Format::Markdown),
+            ("markdown", Format::Markdown),
+            ("ini", Format::Ini),
+            ("cfg", Format::Ini),
+            ("env", Format::Dotenv),
+            ("properties", Format::Properties),
+            ("tf", Format::Hcl),
+            ("tfvars", Format::Hcl),
+            ("hcl", Format::Hcl),
+            ("logfmt", Format::Logfmt),
         ];
+        let name = crate::compression::strip_compression_suffix(name);
+        if is_dotenv_name(name) {
+            return Format::Dotenv;
+        }
         if let Some(ext) = Path::new(name).extension().and_then(|e| e.to_str())
         {
             for (pat, fmt) in EXT_FORMATS {
@@ -32,6 +105,54 @@ impl Format {
         Format::Unknown
     }
 
+    /// Classify raw input bytes. Binary documents (BSON, CBOR and
+    /// MessagePack) are recognized from the whole input; text formats from
+    /// the first `SNIFF_LEN` bytes: JSON, JSON Lines, XML, HTML, YAML and
+    /// TOML. Anything else is `Unknown` and should be read as text.
+    /// UTF-16 input is classified by its decoded text.
+    pub fn sniff(bytes: &[u8]) -> Self {
+        if let Some(encoding) = crate::encoding::sniff_utf16(bytes) {
+            let prefix = &bytes[..bytes.len().min(SNIFF_LEN)];
+            let (text, _) = encoding.decode_with_bom_removal(prefix);
+            let complete = bytes.len() <= SNIFF_LEN;
+            return sniff_text_format(&text, text.as_bytes(), complete);
+        }
+        let Some(text) = sniff_text(bytes) else {
+            return sniff_binary(bytes).unwrap_or(Format::Unknown);
+        };
+        if crate::bson_ingest::is_bson_stream(bytes) {
+            return Format::Bson;
+        }
+        sniff_text_format(text, bytes, bytes.len() <= SNIFF_LEN)
+    }
+
+    /// Whether `prefix`, the first bytes of a possibly longer input, could
+    /// start a BSON, CBOR or MessagePack document. Lets callers that skip
+    /// binary files decide whether the rest is worth reading.
+    pub fn may_start_binary_document(prefix: &[u8]) -> bool {
+        crate::bson_ingest::is_bson_prefix(prefix)
+            || prefix.starts_with(CBOR_SELF_DESCRIBE)
+            || crate::msgpack_ingest::is_msgpack_prefix(prefix)
+            || crate::cbor_ingest::is_cbor_prefix(prefix)
+    }
+
+    /// Binary formats are ingested from raw bytes rather than text.
+    pub fn is_binary(self) -> bool {
+        matches!(self, Format::Msgpack | Format::Cbor | Format::Bson)
+    }
+
+    /// Pick a format from the filename extension, falling back to content
+    /// sniffing when the extension is missing or unknown.
+    /// A `.json` file with comments (`tsconfig.json`, editor settings) is
+    /// read as JSON5, since the strict parser rejects them.
+    pub fn detect(name: &str, bytes: &[u8]) -> Self {
+        match Format::from_filename(name) {
+            Format::Unknown => Format::sniff(bytes),
+            Format::Json => json_or_json5(bytes),
+            known => known,
+        }
+    }
+
     /// Preferred output template for this format. Falls back to caller’s
     /// template for `Unknown`.
     #[allow(dead_code, reason = "kept for compatibility and potential reuse")]
@@ -40,13 +161,299 @@ impl Format {
         fallback: crate::serialization::types::OutputTemplate,
     ) -> crate::serialization::types::OutputTemplate {
         match self {
-            Format::Json => crate::serialization::types::OutputTemplate::Json,
+            Format::Json
+            | Format::Json5
+            | Format::Jsonl
+            | Format::Xml
+            | Format::Html
+            | Format::Markdown
+            | Format::Hcl
+            | Format::Logfmt
+            | Format::Msgpack
+            | Format::Cbor
+            | Format::Bson => {
+                crate::serialization::types::OutputTemplate::Json
+            }
             Format::Yaml => crate::serialization::types::OutputTemplate::Yaml,
+            Format::Toml
+            | Format::Ini
+            | Format::Dotenv
+            | Format::Properties => {
+                crate::serialization::types::OutputTemplate::Toml
+            }
+            Format::Csv => crate::serialization::types::OutputTemplate::Csv,
+            Format::Tsv => crate::serialization::types::OutputTemplate::Tsv,
             Format::Unknown => fallback,
         }
     }
 }
 
+/// Number of leading bytes inspected by `Format::sniff`.
+pub const SNIFF_LEN: usize = 64 * 1024;
+
+// Decode the sniffed prefix as UTF-8 (minus a BOM). A multi-byte character
+// cut off by the prefix boundary is tolerated; other invalid bytes are not.
+fn sniff_text(bytes: &[u8]) -> Option<&str> {
+    let chunk = &bytes[..bytes.len().min(SNIFF_LEN)];
+    let chunk = chunk.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(chunk);
+    match std::str::from_utf8(chunk) {
+        Ok(s) => Some(s),
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&chunk[..e.valid_up_to()]).ok()
+        }
+        Err(_) => None,
+    }
+}
+
+// Text formats, from the decoded start of the input.
+fn sniff_text_format(text: &str, bytes: &[u8], complete: bool) -> Format {
+    let trimmed = text.trim_start();
+    match sniff_json5(trimmed)
+        .or_else(|| sniff_json(trimmed, complete))
+        .or_else(|| sniff_markup(trimmed))
+        .or_else(|| sniff_logfmt(trimmed, complete))
+        .or_else(|| sniff_config(trimmed))
+    {
+        Some(Format::Json) => json_or_json5(bytes),
+        Some(format) => format,
+        None => Format::Unknown,
+    }
+}
+
+// CBOR's optional self-describe tag (55799), the closest it has to magic
+// bytes.
+const CBOR_SELF_DESCRIBE: &[u8] = b"\xd9\xd9\xf7";
+
+// Binary documents. BSON framing is checked first since it is the most
+// specific; MessagePack and CBOR accept almost any leading byte, so they
+// only count when the whole input decodes as exactly one value.
+fn sniff_binary(bytes: &[u8]) -> Option<Format> {
+    if crate::bson_ingest::is_bson_stream(bytes) {
+        Some(Format::Bson)
+    } else if bytes.starts_with(CBOR_SELF_DESCRIBE) {
+        Some(Format::Cbor)
+    } else if crate::msgpack_ingest::is_single_msgpack_value(bytes) {
+        Some(Format::Msgpack)
+    } else if crate::cbor_ingest::is_single_cbor_item(bytes) {
+        Some(Format::Cbor)
+    } else {
+        None
+    }
+}
+
+// JSON family. A leading `[` is also how TOML tables start, so it only
+// means JSON when the text does not read as TOML.
+fn sniff_json(text: &str, complete: bool) -> Option<Format> {
+    if looks_like_json_lines(text) {
+        return Some(Format::Jsonl);
+    }
+    match text.as_bytes().first()? {
+        b'{' => Some(Format::Json),
+        _ if complete && parses_as_json(text) => Some(Format::Json),
+        b'[' if !looks_like_toml(text) => Some(Format::Json),
+        _ => None,
+    }
+}
+
+// JSONC/JSON5: leading `//` or `/* */` comments before an object or array.
+fn sniff_json5(text: &str) -> Option<Format> {
+    let commented = text.starts_with("//") || text.starts_with("/*");
+    (commented && after_leading_comments(text).starts_with(['{', '[']))
+        .then_some(Format::Json5)
+}
+
+// JSON that contains comments only parses as JSON5.
+fn json_or_json5(bytes: &[u8]) -> Format {
+    if has_json_comment(bytes) {
+        Format::Json5
+    } else {
+        Format::Json
+    }
+}
+
+// Whether a `//` or `/*` appears outside of double-quoted strings.
+pub(crate) fn has_json_comment(bytes: &[u8]) -> bool {
+    if !bytes.contains(&b'/') {
+        return false;
+    }
+    let mut in_string = false;
+    let mut escaped = false;
+    bytes.windows(2).any(|pair| {
+        let [c, next] = [pair[0], pair[1]];
+        if in_string {
+            in_string = escaped || c != b'"';
+            escaped = !escaped && c == b'\\';
+            return false;
+        }
+        in_string = c == b'"';
+        c == b'/' && matches!(next, b'/' | b'*')
+    })
+}
+
+// Text following any leading `//` and `/* */` comments.
+fn after_leading_comments(mut text: &str) -> &str {
+    loop {
+        if let Some(line) = text.strip_prefix("//") {
+            text = line.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(block) = text.strip_prefix("/*") {
+            text = block.split_once("*/").map_or("", |(_, rest)| rest);
+        } else {
+            return text;
+        }
+        text = text.trim_start();
+    }
+}
+
+// `.env`, `.env.local`, `.env.production`: hidden files without an
+// extension of their own.
+fn is_dotenv_name(name: &str) -> bool {
+    std::path::Path::new(name)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n == ".env" || n.starts_with(".env."))
+}
+
+// Markup: an HTML doctype or `<html>` root is HTML; any other document
+// opening with an XML declaration, comment, doctype or element is XML.
+fn sniff_markup(text: &str) -> Option<Format> {
+    let rest = text.strip_prefix('<')?;
+    let head = rest.get(..14).unwrap_or(rest).to_ascii_lowercase();
+    if head.starts_with("!doctype html") || head.starts_with("html") {
+        return Some(Format::Html);
+    }
+    let opens_markup = rest.starts_with(['?', '!'])
+        || rest.starts_with(|c: char| c.is_alphabetic() || c == '_');
+    opens_markup.then_some(Format::Xml)
+}
+
+// logfmt lines would also pass as TOML `key=value` lines, so they are
+// checked first.
+fn sniff_logfmt(text: &str, complete: bool) -> Option<Format> {
+    crate::logfmt_ingest::looks_like_logfmt(text, complete)
+        .then_some(Format::Logfmt)
+}
+
+fn sniff_config(text: &str) -> Option<Format> {
+    if looks_like_yaml(text) {
+        Some(Format::Yaml)
+    } else if looks_like_toml(text) {
+        Some(Format::Toml)
+    } else {
+        None
+    }
+}
+
+fn parses_as_json(text: &str) -> bool {
+    serde_json::from_str::<serde::de::IgnoredAny>(text).is_ok()
+}
+
+// JSON Lines: at least two records and the first line is a complete value
+// on its own. Pretty-printed JSON fails the latter (its first line is `{`).
+fn looks_like_json_lines(text: &str) -> bool {
+    if !text.starts_with(['{', '[']) {
+        return false;
+    }
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let Some(first) = lines.next() else {
+        return false;
+    };
+    lines.next().is_some_and(|second| {
+        matches!(second.trim_start().as_bytes().first(), Some(b'{' | b'['))
+    }) && parses_as_json(first)
+}
+
+// Number of leading meaningful lines checked by the YAML heuristic.
+const YAML_PROBE_LINES: usize = 20;
+
+// YAML: a document marker, or top-level lines that are all `key: value`
+// pairs or `- item` entries (indented lines continue the previous entry).
+fn looks_like_yaml(text: &str) -> bool {
+    let mut meaningful = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.is_empty() && !l.trim_start().starts_with('#'))
+        .take(YAML_PROBE_LINES)
+        .peekable();
+    let Some(first) = meaningful.peek() else {
+        return false;
+    };
+    if first.starts_with("---") || first.starts_with("%YAML") {
+        return true;
+    }
+    meaningful.all(|line| {
+        line.starts_with(char::is_whitespace) || is_yaml_entry_line(line)
+    })
+}
+
+fn is_yaml_entry_line(line: &str) -> bool {
+    line == "-"
+        || line.starts_with("- ")
+        || line == "..."
+        || yaml_key_of(line).is_some()
+}
+
+// The key of a `key: value` / `key:` line, when it looks like a mapping key:
+// a quoted string or a bare token without whitespace.
+fn yaml_key_of(line: &str) -> Option<&str> {
+    let (key, _) = line
+        .split_once(": ")
+        .or_else(|| line.strip_suffix(':').map(|k| (k, "")))?;
+    let quoted = key.len() >= 2
+        && ((key.starts_with('"') && key.ends_with('"'))
+            || (key.starts_with('\'') && key.ends_with('\'')));
+    let bare = !key.is_empty() && !key.contains(char::is_whitespace);
+    (quoted || bare).then_some(key)
+}
+
+// TOML: top-level lines that are all `[table]` / `[[array]]` headers or
+// `key = value` pairs (indented lines and closing brackets continue
+// multi-line arrays and inline tables).
+fn looks_like_toml(text: &str) -> bool {
+    let mut meaningful = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.is_empty() && !l.trim_start().starts_with('#'))
+        .take(YAML_PROBE_LINES)
+        .peekable();
+    meaningful.peek().is_some()
+        && meaningful.all(|line| {
+            line.starts_with(char::is_whitespace)
+                || line.starts_with([']', '}'])
+                || is_toml_header(line)
+                || is_toml_key_value(line)
+        })
+}
+
+fn is_toml_header(line: &str) -> bool {
+    let line = line.split_once(" #").map_or(line, |(l, _)| l).trim_end();
+    let inner = line
+        .strip_prefix("[[")
+        .and_then(|l| l.strip_suffix("]]"))
+        .or_else(|| line.strip_prefix('[').and_then(|l| l.strip_suffix(']')));
+    inner.is_some_and(is_toml_key)
+}
+
+fn is_toml_key_value(line: &str) -> bool {
+    line.split_once('=').is_some_and(|(key, value)| {
+        is_toml_key(key) && !value.trim().is_empty()
+    })
+}
+
+// A bare, quoted or dotted TOML key.
+fn is_toml_key(key: &str) -> bool {
+    key.split('.').all(|part| {
+        let part = part.trim();
+        let quoted = part.len() >= 2
+            && ((part.starts_with('"') && part.ends_with('"'))
+                || (part.starts_with('\'') && part.ends_with('\'')));
+        let bare = !part.is_empty()
+            && part
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+        quoted || bare
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::Format;
@@ -62,7 +469,119 @@ mod tests {
         assert_eq!(Format::from_filename("c.yml"), Format::Yaml);
         assert_eq!(Format::from_filename("d.JSON"), Format::Json);
         assert_eq!(Format::from_filename("e.YmL"), Format::Yaml);
+        assert_eq!(Format::from_filename("f.jsonl"), Format::Jsonl);
+        assert_eq!(Format::from_filename("settings.jsonc"), Format::Json5);
+        assert_eq!(Format::from_filename("g.NDJSON"), Format::Jsonl);
+        assert_eq!(Format::from_filename("Cargo.toml"), Format::Toml);
+        assert_eq!(Format::from_filename("rows.CSV"), Format::Csv);
+        assert_eq!(Format::from_filename("rows.tsv"), Format::Tsv);
+        assert_eq!(Format::from_filename("pom.xml"), Format::Xml);
+        assert_eq!(Format::from_filename("index.HTM"), Format::Html);
+        assert_eq!(Format::from_filename("dump.bson"), Format::Bson);
+        assert_eq!(Format::from_filename("x.mpk"), Format::Msgpack);
         assert_eq!(Format::from_filename("noext"), Format::Unknown);
         assert_eq!(Format::from_filename("weird.tar.gz"), Format::Unknown);
+        assert_eq!(Format::from_filename("dump.json.gz"), Format::Json);
+        assert_eq!(Format::from_filename("events.ndjson.zst"), Format::Jsonl);
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "Single test covers multiple assertions compactly."
+    )]
+    fn sniffs_json_and_json_lines() {
+        assert_eq!(Format::sniff(b"  {\n  \"a\": 1\n}\n"), Format::Json);
+        assert_eq!(Format::sniff(b"[1, 2, 3]"), Format::Json);
+        assert_eq!(Format::sniff(b"\"just a string\""), Format::Json);
+        assert_eq!(Format::sniff(b"{\"a\":1}\n{\"a\":2}\n"), Format::Jsonl);
+        assert_eq!(Format::sniff(b"\xEF\xBB\xBF{\"a\":1}"), Format::Json);
+        assert_eq!(Format::sniff(b"// c\n/* d */ {a: 1}"), Format::Json5);
+    }
+
+    #[test]
+    fn sniffs_yaml_documents() {
+        let k8s = b"apiVersion: v1\nkind: List\nitems:\n  - name: a\n";
+        assert_eq!(Format::sniff(k8s), Format::Yaml);
+        assert_eq!(Format::sniff(b"---\nfoo\n"), Format::Yaml);
+        assert_eq!(Format::sniff(b"# comment\n- a\n- b\n"), Format::Yaml);
+    }
+
+    #[test]
+    fn sniffs_toml_documents() {
+        let cargo = b"[package]\nname = \"demo\"\nversion = \"0.1.0\"\n";
+        assert_eq!(Format::sniff(cargo), Format::Toml);
+        let lock = b"# generated\nversion = 4\n\n[[package]]\nname = \"a\"\n\
+                     deps = [\n  \"b\",\n]\n";
+        assert_eq!(Format::sniff(lock), Format::Toml);
+        assert_eq!(Format::sniff(b"[\"a\"]"), Format::Json);
+    }
+
+    #[test]
+    fn sniffs_logfmt_lines() {
+        let log =
+            b"time=1 level=info msg=\"up\"\ntime=2 level=warn msg=slow\n";
+        assert_eq!(Format::sniff(log), Format::Logfmt);
+        assert_eq!(Format::sniff(b"name=\"a\"\nport=80\n"), Format::Toml);
+    }
+
+    #[test]
+    fn sniffs_xml_and_html_documents() {
+        let pom = b"<?xml version=\"1.0\"?>\n<project><a>1</a></project>\n";
+        assert_eq!(Format::sniff(pom), Format::Xml);
+        assert_eq!(Format::sniff(b"<svg width=\"1\"/>"), Format::Xml);
+        let page = b"<!DOCTYPE html>\n<html><body>hi</body></html>";
+        assert_eq!(Format::sniff(page), Format::Html);
+        assert_eq!(Format::sniff(b"<HTML lang=en>"), Format::Html);
+    }
+
+    #[test]
+    fn sniffs_binary_documents() {
+        let bson = b"\x0c\0\0\0\x10a\0\x01\0\0\0\0";
+        assert_eq!(Format::sniff(bson), Format::Bson);
+        assert_eq!(Format::sniff(b"\xd9\xd9\xf7\x01"), Format::Cbor);
+        assert_eq!(Format::sniff(b"\x92\x01\xa1x"), Format::Msgpack);
+        assert!(Format::may_start_binary_document(b"\xdc\x01\x00\x01"));
+    }
+
+    #[test]
+    fn sniffs_utf16_text_by_its_decoded_content() {
+        let utf16 = |text: &str| -> Vec<u8> {
+            text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+        };
+        assert_eq!(Format::sniff(&utf16("{\"a\": 1}")), Format::Json);
+        let mut with_bom = vec![0xFE, 0xFF];
+        with_bom.extend(utf16("key: value\nother: 2\n"));
+        assert_eq!(Format::sniff(&with_bom), Format::Yaml);
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "Single test covers multiple assertions compactly."
+    )]
+    fn sniffs_everything_else_as_unknown() {
+        assert_eq!(Format::sniff(b""), Format::Unknown);
+        assert_eq!(Format::sniff(b"alpha\nbeta\n"), Format::Unknown);
+        assert_eq!(
+            Format::sniff(b"Note: this is prose\nand more prose\n"),
+            Format::Unknown
+        );
+        assert_eq!(Format::sniff(&[0, 159, 146, 150]), Format::Unknown);
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn detect_prefers_extension_over_content() {
+        assert_eq!(Format::detect("a.yaml", b"{\"a\":1}"), Format::Yaml);
+        assert_eq!(Format::detect("config", b"a: 1\n"), Format::Yaml);
+        assert_eq!(Format::detect("notes.txt", b"a\n"), Format::Unknown);
+        let tsconfig = b"{\n  // strict mode\n  \"strict\": true,\n}";
+        assert_eq!(Format::detect("tsconfig.json", tsconfig), Format::Json5);
+        let url = b"{\"u\": \"http://x/*\"}";
+        assert_eq!(Format::detect("a.json", url), Format::Json);
     }
 }
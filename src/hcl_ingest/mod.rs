@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serde::de::value::{
+    Error as ValueError, MapDeserializer, SeqDeserializer,
+};
+use serde::de::{IntoDeserializer, Visitor};
+
+use crate::PriorityConfig;
+use crate::json_ingest::{
+    NUMBER_TOKEN, build_tree_arena_from_deserializer,
+    build_tree_arena_from_many_deserializers,
+};
+use crate::utils::tree_arena::JsonTreeArena;
+
+mod parser;
+
+/// Build an arena from HCL (Terraform `.tf`, `.tfvars`, `.hcl`). Blocks
+/// nest under their type and labels, attributes keep literal values, and
+/// unevaluated expressions stay atomic tokens of their source text.
+pub fn build_hcl_tree_arena_from_bytes(
+    bytes: &[u8],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let doc = parse_bytes(bytes)?;
+    let mut arena = build_tree_arena_from_deserializer(doc, config)?;
+    arena.preserve_source_key_order();
+    Ok(arena)
+}
+
+pub fn build_hcl_tree_arena_from_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut docs: Vec<(String, HclValue)> = Vec::with_capacity(inputs.len());
+    for (key, bytes) in inputs {
+        let doc = parse_bytes(&bytes)
+            .with_context(|| format!("failed to parse {key}"))?;
+        docs.push((key, doc));
+    }
+    let mut arena = build_tree_arena_from_many_deserializers(docs, config)?;
+    arena.preserve_source_key_order();
+    Ok(arena)
+}
+
+fn parse_bytes(bytes: &[u8]) -> Result<HclValue> {
+    let text = String::from_utf8_lossy(bytes).replace("\r\n", "\n");
+    let body = parser::parse_document(&text).context("invalid HCL")?;
+    Ok(HclValue::Object(body))
+}
+
+// Object entries in source order. A repeated attribute keeps its first
+// position and takes the last value.
+#[derive(Default)]
+struct Entries {
+    items: Vec<(String, HclValue)>,
+    index: HashMap<String, usize>,
+}
+
+impl Entries {
+    fn insert(&mut self, key: String, value: HclValue) {
+        if let Some(&i) = self.index.get(&key) {
+            self.items[i].1 = value;
+        } else {
+            self.index.insert(key.clone(), self.items.len());
+            self.items.push((key, value));
+        }
+    }
+
+    // `resource "aws_instance" "web" {…}` goes to
+    // `{resource: {aws_instance: {web: {…}}}}`, merging with the other
+    // blocks that share a prefix of the path. Blocks with the same type and
+    // labels (`ingress`, `provisioner`) collect into an array.
+    fn insert_block(&mut self, kind: String, labels: Vec<String>, body: Self) {
+        let mut target = self;
+        let mut key = kind;
+        for label in labels {
+            target = target.object_mut(key);
+            key = label;
+        }
+        match target.index.get(&key) {
+            Some(&i) => match &mut target.items[i].1 {
+                HclValue::Blocks(bodies) => bodies.push(body),
+                slot => *slot = HclValue::Blocks(vec![body]),
+            },
+            None => target.insert(key, HclValue::Blocks(vec![body])),
+        }
+    }
+
+    // The object under `key`, created empty if missing. Under repeated
+    // blocks it is the body of the last one; a plain value is replaced.
+    fn object_mut(&mut self, key: String) -> &mut Self {
+        let i = if let Some(&i) = self.index.get(&key) {
+            i
+        } else {
+            self.insert(key, HclValue::Object(Self::default()));
+            self.items.len() - 1
+        };
+        let slot = &mut self.items[i].1;
+        if !matches!(slot, HclValue::Object(_) | HclValue::Blocks(_)) {
+            *slot = HclValue::Object(Self::default());
+        }
+        match slot {
+            HclValue::Object(entries) => entries,
+            HclValue::Blocks(bodies) => {
+                bodies.last_mut().unwrap_or_else(|| {
+                    unreachable!("block lists are never empty")
+                })
+            }
+            _ => unreachable!("slot was just made an object"),
+        }
+    }
+}
+
+// Document model handed to the shared serde builder. Numbers and
+// unevaluated expressions travel as atomic tokens so they print as
+// written.
+enum HclValue {
+    String(String),
+    Bool(bool),
+    Null,
+    Token(String),
+    Tuple(Vec<HclValue>),
+    Object(Entries),
+    // Bodies of the blocks with one type and label path: an object for a
+    // single block, an array when it repeats.
+    Blocks(Vec<Entries>),
+}
+
+impl<'de> serde::Deserializer<'de> for HclValue {
+    type Error = ValueError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            HclValue::String(s) => visitor.visit_string(s),
+            HclValue::Bool(b) => visitor.visit_bool(b),
+            HclValue::Null => visitor.visit_unit(),
+            HclValue::Token(token) => visitor.visit_map(MapDeserializer::new(
+                std::iter::once((NUMBER_TOKEN, token)),
+            )),
+            HclValue::Tuple(items) => {
+                visitor.visit_seq(SeqDeserializer::new(items.into_iter()))
+            }
+            HclValue::Object(entries) => visitor
+                .visit_map(MapDeserializer::new(entries.items.into_iter())),
+            HclValue::Blocks(mut bodies) => match bodies.pop() {
+                Some(body) if bodies.is_empty() => visitor
+                    .visit_map(MapDeserializer::new(body.items.into_iter())),
+                last => {
+                    bodies.extend(last);
+                    visitor.visit_seq(SeqDeserializer::new(
+                        bodies.into_iter().map(HclValue::Object),
+                    ))
+                }
+            },
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, ValueError> for HclValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        RenderConfig,
+        serialization::types::{ColorMode, OutputTemplate, Style},
+    };
+
+    fn render(text: &str) -> String {
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            build_hcl_tree_arena_from_bytes(text.as_bytes(), &cfg).unwrap();
+        let render = RenderConfig {
+            template: OutputTemplate::Json,
+            indent_unit: String::new(),
+            space: String::new(),
+            newline: String::new(),
+            prefer_tail_arrays: false,
+            color_mode: ColorMode::Off,
+            color_enabled: false,
+            style: Style::Strict,
+        };
+        let order = crate::order::build_order(&arena, &cfg).unwrap();
+        crate::find_largest_render_under_budget(&order, &render, usize::MAX)
+    }
+
+    #[test]
+    fn blocks_nest_under_type_and_labels() {
+        let text = "resource \"aws_instance\" \"web\" {\n  ami = \"ami-1\"\n  \
+                    ingress { port = 80 }\n  ingress {\n    port = 443\n  }\n}\n\
+                    resource \"aws_instance\" \"db\" {}\n\
+                    variable region { default = \"eu\" }\n";
+        assert_eq!(
+            render(text),
+            r#"{"resource":{"aws_instance":{"web":{"ami":"ami-1","ingress":[{"port":80},{"port":443}]},"db":{}}},"variable":{"region":{"default":"eu"}}}"#
+        );
+    }
+
+    #[test]
+    fn literals_become_values_and_expressions_stay_tokens() {
+        let text = "a = [1, \"x\", true, null]\n\
+                    b = { k = var.x, \"q\": 0755 } # note\n\
+                    c = \"${var.env}-\\\"app\\\"\"\n\
+                    d = var.count > 1 ? \"a\" : \"b\"\n\
+                    e = [for s in var.list : upper(s)]\n\
+                    f = length(local.x) /* why */\n";
+        assert_eq!(
+            render(text),
+            r#"{"a":[1,"x",true,null],"b":{"k":var.x,"q":0755},"c":"${var.env}-\"app\"","d":var.count > 1 ? "a" : "b","e":[for s in var.list : upper(s)],"f":length(local.x)}"#
+        );
+    }
+
+    #[test]
+    fn heredocs_become_strings() {
+        let text = "policy = <<-EOT\n    {\n      \"a\": 1\n    }\n    EOT\n\
+                    raw = <<EOF\n  x\nEOF\n";
+        assert_eq!(
+            render(text),
+            r#"{"policy":"{\n  \"a\": 1\n}\n","raw":"  x\n"}"#
+        );
+    }
+
+    #[test]
+    fn multi_line_expressions_are_kept_whole() {
+        let text = "tags = merge(\n  local.tags,\n  { Name = \"x\" }, # c\n)\nnext = 1\n";
+        assert_eq!(
+            render(text),
+            "{\"tags\":merge(\n  local.tags,\n  { Name = \"x\" }, # c\n),\"next\":1}"
+        );
+    }
+
+    #[test]
+    fn unbalanced_input_is_an_error() {
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let err = build_hcl_tree_arena_from_bytes(b"a = [1, 2\nb = 3\n", &cfg)
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("expected `]`"), "{err:#}");
+    }
+}
@@ -0,0 +1,531 @@
+use anyhow::{Result, anyhow};
+
+use super::{Entries, HclValue};
+
+// Blocks and collection literals nested deeper than this are rejected or
+// kept as a single token, so hostile input cannot exhaust the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Parse a whole HCL document into the object of its top-level body.
+pub(super) fn parse_document(src: &str) -> Result<Entries> {
+    let mut parser = Parser::new(src);
+    parser.parse_body(false, 0)
+}
+
+// What the expression scanner is inside of.
+enum Frame {
+    // A bracket group waiting for this closing character.
+    Group(char),
+    // The body of a quoted string.
+    Template,
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos == self.src.len()
+    }
+
+    fn error(&self, msg: &str) -> anyhow::Error {
+        let before = &self.src[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rfind('\n')
+            .map_or(before, |nl| &before[nl + 1..])
+            .chars()
+            .count()
+            + 1;
+        anyhow!("{msg} at line {line} column {column}")
+    }
+
+    // Spaces and comments; newlines too when `newlines` is set. A line
+    // comment stops before its newline.
+    fn skip_trivia(&mut self, newlines: bool) -> Result<()> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start_matches(|c: char| {
+                c == ' '
+                    || c == '\t'
+                    || c == '\r'
+                    || c == '\u{feff}'
+                    || (newlines && c == '\n')
+            });
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with('#') || trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if let Some(body) = trimmed.strip_prefix("/*") {
+                let end = body
+                    .find("*/")
+                    .ok_or_else(|| self.error("unterminated comment"))?;
+                self.pos += 2 + end + 2;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_identifier(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !is_identifier_char(c))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    // Attributes and blocks up to the closing `}` of a block, or to the
+    // end of input for the top-level body.
+    fn parse_body(&mut self, in_block: bool, depth: usize) -> Result<Entries> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("blocks nested too deeply"));
+        }
+        let mut entries = Entries::default();
+        loop {
+            self.skip_trivia(true)?;
+            match self.peek() {
+                None if in_block => return Err(self.error("unclosed block")),
+                None => return Ok(entries),
+                Some('}') if in_block => {
+                    self.bump();
+                    return Ok(entries);
+                }
+                Some(_) => self.parse_item(&mut entries, depth)?,
+            }
+        }
+    }
+
+    fn parse_item(
+        &mut self,
+        entries: &mut Entries,
+        depth: usize,
+    ) -> Result<()> {
+        let name = self.parse_identifier();
+        if name.is_empty() {
+            return Err(self.error("expected an attribute or block"));
+        }
+        self.skip_trivia(false)?;
+        if self.rest().starts_with('=') && !self.rest().starts_with("==") {
+            self.bump();
+            let value = self.parse_expression()?;
+            entries.insert(name.to_string(), value);
+            return Ok(());
+        }
+        let mut labels = Vec::new();
+        loop {
+            self.skip_trivia(false)?;
+            match self.peek() {
+                Some('{') => break,
+                Some('"') => labels.push(self.parse_string_label()?),
+                Some(c) if is_identifier_char(c) => {
+                    labels.push(self.parse_identifier().to_string());
+                }
+                _ => return Err(self.error("expected `=` or `{`")),
+            }
+        }
+        self.bump();
+        let body = self.parse_body(true, depth + 1)?;
+        entries.insert_block(name.to_string(), labels, body);
+        Ok(())
+    }
+
+    fn parse_string_label(&mut self) -> Result<String> {
+        let start = self.pos;
+        self.bump();
+        self.scan(vec![Frame::Template], true)?;
+        Ok(decode_template(&self.src[start + 1..self.pos - 1]))
+    }
+
+    // One expression, ending at a newline, comma, comment or unmatched
+    // closing bracket outside of any group.
+    fn parse_expression(&mut self) -> Result<HclValue> {
+        self.skip_trivia(false)?;
+        let start = self.pos;
+        self.scan(Vec::new(), false)?;
+        let text = self.src[start..self.pos].trim_end();
+        if text.is_empty() {
+            return Err(self.error("expected an expression"));
+        }
+        Ok(classify(text, 0))
+    }
+
+    // Advance over expression text. With `until_closed`, stop as soon as
+    // the frames of `stack` are closed; otherwise stop at a terminator
+    // outside of any frame.
+    fn scan(
+        &mut self,
+        mut stack: Vec<Frame>,
+        until_closed: bool,
+    ) -> Result<()> {
+        loop {
+            if until_closed && stack.is_empty() {
+                return Ok(());
+            }
+            let Some(c) = self.peek() else {
+                return self.scan_end(stack.last());
+            };
+            let stop = if let Some(Frame::Template) = stack.last() {
+                self.scan_template_step(c, &mut stack)?;
+                false
+            } else {
+                self.scan_step(c, &mut stack)?
+            };
+            if stop {
+                return Ok(());
+            }
+        }
+    }
+
+    fn scan_end(&self, open: Option<&Frame>) -> Result<()> {
+        match open {
+            None => Ok(()),
+            Some(Frame::Template) => Err(self.error("unterminated string")),
+            Some(Frame::Group(closer)) => {
+                Err(self.error(&format!("expected `{closer}`")))
+            }
+        }
+    }
+
+    // One step inside a quoted string: an escape, an interpolation or
+    // directive opening, the closing quote or a plain character.
+    fn scan_template_step(
+        &mut self,
+        c: char,
+        stack: &mut Vec<Frame>,
+    ) -> Result<()> {
+        let rest = self.rest();
+        if rest.starts_with("$${") || rest.starts_with("%%{") {
+            self.pos += 3;
+            return Ok(());
+        }
+        if rest.starts_with("${") || rest.starts_with("%{") {
+            self.pos += 2;
+            stack.push(Frame::Group('}'));
+            return Ok(());
+        }
+        match c {
+            '"' => {
+                stack.pop();
+            }
+            '\n' => return Err(self.error("unterminated string")),
+            '\\' => {
+                self.bump();
+            }
+            _ => {}
+        }
+        self.bump();
+        Ok(())
+    }
+
+    // One step outside of strings. Returns whether the expression ends
+    // here: at a comment, newline, comma or unmatched closing bracket
+    // outside of any group.
+    fn scan_step(&mut self, c: char, stack: &mut Vec<Frame>) -> Result<bool> {
+        let rest = self.rest();
+        if rest.starts_with('#')
+            || rest.starts_with("//")
+            || rest.starts_with("/*")
+        {
+            if stack.is_empty() {
+                return Ok(true);
+            }
+            self.skip_trivia(false)?;
+            return Ok(false);
+        }
+        if let Some(end) = heredoc_end(rest) {
+            self.pos +=
+                end.ok_or_else(|| self.error("unterminated heredoc"))?;
+            return Ok(false);
+        }
+        match c {
+            '\n' | ',' if stack.is_empty() => return Ok(true),
+            '"' => stack.push(Frame::Template),
+            '(' => stack.push(Frame::Group(')')),
+            '[' => stack.push(Frame::Group(']')),
+            '{' => stack.push(Frame::Group('}')),
+            ')' | ']' | '}' => match stack.last() {
+                None => return Ok(true),
+                Some(Frame::Group(closer)) if *closer == c => {
+                    stack.pop();
+                }
+                _ => return Err(self.error(&format!("unexpected `{c}`"))),
+            },
+            _ => {}
+        }
+        self.bump();
+        Ok(false)
+    }
+
+    // `${…}` or `%{…}` as written, up to its closing brace or the end of
+    // input.
+    fn interpolation(&mut self) -> &'a str {
+        let start = self.pos;
+        self.pos += 2;
+        if self.scan(vec![Frame::Group('}')], true).is_err() {
+            self.pos = self.src.len();
+        }
+        &self.src[start..self.pos]
+    }
+
+    // Elements of a tuple literal, the parser standing after its `[`.
+    fn parse_tuple(&mut self, depth: usize) -> Result<Vec<HclValue>> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia(true)?;
+            if self.peek() == Some(']') {
+                self.bump();
+                return Ok(items);
+            }
+            let start = self.pos;
+            self.scan(Vec::new(), false)?;
+            let text = self.src[start..self.pos].trim_end();
+            if text.is_empty() {
+                return Err(self.error("expected an element"));
+            }
+            items.push(classify(text, depth + 1));
+            self.skip_trivia(true)?;
+            if self.peek() == Some(',') {
+                self.bump();
+            }
+        }
+    }
+
+    // Items of an object literal, the parser standing after its `{`.
+    // Keys are identifiers, quoted strings or parenthesized expressions.
+    fn parse_object(&mut self, depth: usize) -> Result<Entries> {
+        let mut entries = Entries::default();
+        loop {
+            self.skip_trivia(true)?;
+            let key = match self.peek() {
+                Some('}') => {
+                    self.bump();
+                    return Ok(entries);
+                }
+                Some('"') => self.parse_string_label()?,
+                Some('(') => {
+                    let start = self.pos;
+                    self.bump();
+                    self.scan(vec![Frame::Group(')')], true)?;
+                    self.src[start..self.pos].to_string()
+                }
+                _ => self.parse_identifier().to_string(),
+            };
+            if key.is_empty() {
+                return Err(self.error("expected an object key"));
+            }
+            self.skip_trivia(false)?;
+            match self.bump() {
+                Some('=' | ':') => {}
+                _ => return Err(self.error("expected `=` or `:`")),
+            }
+            self.skip_trivia(false)?;
+            let start = self.pos;
+            self.scan(Vec::new(), false)?;
+            let text = self.src[start..self.pos].trim_end();
+            if text.is_empty() {
+                return Err(self.error("expected a value"));
+            }
+            entries.insert(key, classify(text, depth + 1));
+            self.skip_trivia(true)?;
+            if self.peek() == Some(',') {
+                self.bump();
+            }
+        }
+    }
+}
+
+// Literals become values; tuple and object literals become arrays and
+// objects; anything else (references, function calls, operators, `for`
+// expressions) stays an atomic token of its source text.
+fn classify(text: &str, depth: usize) -> HclValue {
+    match text {
+        "true" => return HclValue::Bool(true),
+        "false" => return HclValue::Bool(false),
+        "null" => return HclValue::Null,
+        _ => {}
+    }
+    let nested = depth < MAX_DEPTH && !starts_for(&text[1..]);
+    let literal = match text.chars().next() {
+        Some('"') if closes_at_end(text, Frame::Template) => {
+            Some(HclValue::String(decode_template(&text[1..text.len() - 1])))
+        }
+        Some('[') if nested && closes_at_end(text, Frame::Group(']')) => {
+            Parser::new(&text[1..])
+                .parse_tuple(depth)
+                .ok()
+                .map(HclValue::Tuple)
+        }
+        Some('{') if nested && closes_at_end(text, Frame::Group('}')) => {
+            Parser::new(&text[1..])
+                .parse_object(depth)
+                .ok()
+                .map(HclValue::Object)
+        }
+        Some('<') => heredoc_value(text).map(HclValue::String),
+        _ => None,
+    };
+    literal.unwrap_or_else(|| HclValue::Token(text.to_string()))
+}
+
+// Whether the bracket or quote opening `text` is closed by its last
+// character, e.g. `[1, 2]` but not `[1][0]`.
+fn closes_at_end(text: &str, frame: Frame) -> bool {
+    let mut parser = Parser::new(text);
+    parser.bump();
+    parser.scan(vec![frame], true).is_ok() && parser.at_end()
+}
+
+// `[for x in xs : x]` and `{for k, v in m : k => v}`.
+fn starts_for(inner: &str) -> bool {
+    inner
+        .trim_start()
+        .strip_prefix("for")
+        .is_some_and(|after| after.starts_with(char::is_whitespace))
+}
+
+// The body of a quoted string with its escapes resolved. Interpolations
+// and directives are kept as written.
+fn decode_template(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut parser = Parser::new(body);
+    while let Some(c) = parser.peek() {
+        let rest = parser.rest();
+        if rest.starts_with("$${") || rest.starts_with("%%{") {
+            out.push_str(&rest[1..3]);
+            parser.pos += 3;
+        } else if rest.starts_with("${") || rest.starts_with("%{") {
+            out.push_str(parser.interpolation());
+        } else if c == '\\' {
+            parser.bump();
+            push_escape(&mut parser, &mut out);
+        } else {
+            out.push(c);
+            parser.bump();
+        }
+    }
+    out
+}
+
+fn push_escape(parser: &mut Parser<'_>, out: &mut String) {
+    let Some(c) = parser.bump() else {
+        out.push('\\');
+        return;
+    };
+    let digits = match c {
+        'n' => return out.push('\n'),
+        'r' => return out.push('\r'),
+        't' => return out.push('\t'),
+        'u' => 4,
+        'U' => 8,
+        _ => return out.push(c),
+    };
+    let hex = parser.rest().get(..digits).unwrap_or_default();
+    let Some(decoded) =
+        u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+    else {
+        out.push('\\');
+        out.push(c);
+        return;
+    };
+    out.push(decoded);
+    parser.pos += digits;
+}
+
+// `<<EOF` or `<<-EOF` followed by a newline: the header of a heredoc.
+// Returns the marker and whether the content is indented.
+fn heredoc_header(text: &str) -> Option<(&str, bool, usize)> {
+    let after = text.strip_prefix("<<")?;
+    let (indented, after) = match after.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, after),
+    };
+    let len = after
+        .find(|c: char| !is_identifier_char(c))
+        .unwrap_or(after.len());
+    let marker = &after[..len];
+    let line_end = after[len..].find('\n')?;
+    if marker.is_empty() || !after[len..len + line_end].trim().is_empty() {
+        return None;
+    }
+    let body_start = text.len() - after.len() + len + line_end + 1;
+    Some((marker, indented, body_start))
+}
+
+// Lines of a heredoc body up to (not including) the closing marker, and
+// the byte offset just past that marker.
+fn heredoc_lines<'t>(
+    text: &'t str,
+    marker: &str,
+    body_start: usize,
+) -> Option<(Vec<&'t str>, usize)> {
+    let mut lines = Vec::new();
+    let mut offset = body_start;
+    for line in text[body_start..].split_inclusive('\n') {
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        if content.trim() == marker {
+            return Some((lines, offset + content.len()));
+        }
+        lines.push(content);
+        offset += line.len();
+    }
+    None
+}
+
+// Length of the heredoc starting `text`, `Some(None)` when its closing
+// marker is missing, and `None` when `text` does not start a heredoc.
+fn heredoc_end(text: &str) -> Option<Option<usize>> {
+    let (marker, _, body_start) = heredoc_header(text)?;
+    Some(heredoc_lines(text, marker, body_start).map(|(_, end)| end))
+}
+
+// The string a heredoc spanning all of `text` stands for. `<<-` strips
+// the indentation common to its lines.
+fn heredoc_value(text: &str) -> Option<String> {
+    let (marker, indented, body_start) = heredoc_header(text)?;
+    let (lines, end) = heredoc_lines(text, marker, body_start)?;
+    // Past the end when `text` was trimmed after the marker.
+    if text.get(end..).is_some_and(|tail| !tail.trim().is_empty()) {
+        return None;
+    }
+    let strip = if indented {
+        lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(line.get(strip..).unwrap_or_else(|| line.trim_start()));
+        out.push('\n');
+    }
+    Some(out)
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
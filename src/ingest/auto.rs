@@ -0,0 +1,278 @@
+use anyhow::Result;
+
+use super::IngestOptions;
+use crate::format::Format;
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// Build a fileset where each entry is ingested by the adapter matching
+/// its own filename, or its content when the extension is unknown (see
+/// `Format::detect`). The per-file arenas are grafted under one synthetic
+/// fileset object, so `a.json b.yaml c.txt` keeps the structure of every
+/// file. An entry that fails to parse in its detected format is shown as
+/// plain text and listed in `text_fallbacks`, so one broken file does not
+/// abort the whole fileset.
+pub fn parse_many_auto(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    let mut arena = TreeArena::default();
+    let mut keys: Vec<String> = Vec::with_capacity(inputs.len());
+    let mut children: Vec<usize> = Vec::with_capacity(inputs.len());
+    let mut formats: Vec<Format> = Vec::with_capacity(inputs.len());
+    for (key, bytes) in inputs {
+        let detected = Format::detect(&key, &bytes);
+        let (child, format) = parse_or_text(detected, bytes, ingest, cfg)?;
+        if format != detected {
+            arena.text_fallbacks.push(key.clone());
+        }
+        children.push(arena.graft(child));
+        keys.push(key);
+        formats.push(format);
+    }
+    arena.root_id = arena.push_object(keys, children);
+    arena.is_fileset = true;
+    arena.fileset_formats = formats;
+    Ok(arena)
+}
+
+// Parse with `format`, or as plain text when that fails. Also returns the
+// format the entry was ingested with.
+fn parse_or_text(
+    format: Format,
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<(TreeArena, Format)> {
+    match try_parse(format, bytes, ingest, cfg) {
+        Ok(child) => Ok((child, format)),
+        Err(text) => Ok((
+            crate::text_ingest::build_text_tree_arena_from_bytes(&text, cfg)?,
+            Format::Unknown,
+        )),
+    }
+}
+
+// Parsers read the entry without keeping it, so a failed parse hands it
+// back for the fallback instead of each entry being copied up front.
+// Text formats hand it back decoded, binary ones decode it then.
+fn try_parse(
+    format: Format,
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> std::result::Result<TreeArena, Vec<u8>> {
+    if format.is_binary() {
+        return parse_binary(format, &bytes, cfg)
+            .map_err(|_| super::decode_one(bytes, ingest));
+    }
+    let text = super::decode_one(bytes, ingest);
+    match format {
+        Format::Json => {
+            crate::json_ingest::try_build_json_tree_arena(text, ingest, cfg)
+        }
+        Format::Jsonl => {
+            crate::json_ingest::try_build_jsonl_tree_arena(text, cfg)
+        }
+        _ => parse_text(format, &text, ingest, cfg).map_err(|_| text),
+    }
+}
+
+fn parse_binary(
+    format: Format,
+    bytes: &[u8],
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    match format {
+        Format::Msgpack => {
+            crate::msgpack_ingest::build_msgpack_tree_arena_from_bytes(
+                bytes, cfg,
+            )
+        }
+        Format::Cbor => {
+            crate::cbor_ingest::build_cbor_tree_arena_from_bytes(bytes, cfg)
+        }
+        _ => crate::bson_ingest::build_bson_tree_arena_from_bytes(bytes, cfg),
+    }
+}
+
+// The text formats that parse decoded input without rewriting it.
+fn parse_text(
+    format: Format,
+    text: &[u8],
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    use crate::keyvalue_ingest::KeyValueFormat;
+    use crate::xml_ingest::Markup;
+    match format {
+        Format::Json5 => {
+            crate::json5_ingest::build_json5_tree_arena_from_bytes(text, cfg)
+        }
+        Format::Yaml => {
+            crate::yaml_ingest::build_yaml_tree_arena_from_bytes(text, cfg)
+        }
+        Format::Toml => {
+            crate::toml_ingest::build_toml_tree_arena_from_bytes(text, cfg)
+        }
+        Format::Csv => {
+            crate::csv_ingest::build_csv_tree_arena_from_bytes(text, b',', cfg)
+        }
+        Format::Tsv => crate::csv_ingest::build_csv_tree_arena_from_bytes(
+            text, b'\t', cfg,
+        ),
+        Format::Xml => crate::xml_ingest::build_markup_tree_arena_from_bytes(
+            text,
+            Markup::Xml,
+            cfg,
+        ),
+        Format::Html => crate::xml_ingest::build_markup_tree_arena_from_bytes(
+            text,
+            Markup::Html,
+            cfg,
+        ),
+        Format::Markdown => {
+            crate::markdown_ingest::build_markdown_tree_arena_from_bytes(
+                text, cfg,
+            )
+        }
+        Format::Ini => parse_keyvalue(text, KeyValueFormat::Ini, ingest, cfg),
+        Format::Dotenv => {
+            parse_keyvalue(text, KeyValueFormat::Dotenv, ingest, cfg)
+        }
+        Format::Properties => {
+            parse_keyvalue(text, KeyValueFormat::Properties, ingest, cfg)
+        }
+        Format::Hcl => {
+            crate::hcl_ingest::build_hcl_tree_arena_from_bytes(text, cfg)
+        }
+        Format::Logfmt => {
+            crate::logfmt_ingest::build_logfmt_tree_arena_from_bytes(
+                text, ingest, cfg,
+            )
+        }
+        _ => crate::text_ingest::build_text_tree_arena_from_bytes(text, cfg),
+    }
+}
+
+fn parse_keyvalue(
+    text: &[u8],
+    format: crate::keyvalue_ingest::KeyValueFormat,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    crate::keyvalue_ingest::build_keyvalue_tree_arena_from_bytes(
+        text, format, ingest, cfg,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::NodeKind;
+
+    fn child_kinds(arena: &TreeArena) -> Vec<NodeKind> {
+        let root = &arena.nodes[arena.root_id];
+        arena.children
+            [root.children_start..root.children_start + root.children_len]
+            .iter()
+            .map(|&c| arena.nodes[c].kind)
+            .collect()
+    }
+
+    #[test]
+    fn mixed_fileset_uses_per_file_ingest() {
+        let inputs = vec![
+            ("a.json".to_string(), b"{\"x\":[1,2]}".to_vec()),
+            ("b.yaml".to_string(), b"k: v\n".to_vec()),
+            ("notes.txt".to_string(), b"one\ntwo\n".to_vec()),
+        ];
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            parse_many_auto(inputs, &IngestOptions::default(), &cfg).unwrap();
+        assert!(arena.is_fileset);
+        assert_eq!(
+            child_kinds(&arena),
+            vec![NodeKind::Object, NodeKind::Object, NodeKind::Array]
+        );
+        let root = &arena.nodes[arena.root_id];
+        let keys = &arena.obj_keys
+            [root.obj_keys_start..root.obj_keys_start + root.obj_keys_len];
+        assert_eq!(keys, ["a.json", "b.yaml", "notes.txt"]);
+    }
+
+    #[test]
+    fn grafted_children_keep_their_own_ranges() {
+        let inputs = vec![
+            ("a.json".to_string(), b"{\"x\":[1,2,3]}".to_vec()),
+            ("b.json".to_string(), b"{\"y\":[4]}".to_vec()),
+        ];
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            parse_many_auto(inputs, &IngestOptions::default(), &cfg).unwrap();
+        let root = &arena.nodes[arena.root_id];
+        let b_id = arena.children[root.children_start + 1];
+        let b = &arena.nodes[b_id];
+        assert_eq!(arena.obj_keys[b.obj_keys_start], "y");
+        let y = &arena.nodes[arena.children[b.children_start]];
+        assert_eq!(y.array_len, Some(1));
+        let four = &arena.nodes[arena.children[y.children_start]];
+        assert_eq!(four.atomic_token.as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn unknown_extensions_fall_back_to_sniffing() {
+        let inputs = vec![
+            ("config".to_string(), b"a: 1\nb: [x]\n".to_vec()),
+            ("notes.txt".to_string(), b"plain words\n".to_vec()),
+        ];
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            parse_many_auto(inputs, &IngestOptions::default(), &cfg).unwrap();
+        assert_eq!(arena.fileset_formats, vec![Format::Yaml, Format::Unknown]);
+        assert_eq!(
+            child_kinds(&arena),
+            vec![NodeKind::Object, NodeKind::Array]
+        );
+    }
+
+    #[test]
+    fn unparsable_entries_fall_back_to_text() {
+        let inputs = vec![
+            ("bad.json".to_string(), b"[1 true]\n".to_vec()),
+            ("good.json".to_string(), b"{\"x\":1}".to_vec()),
+        ];
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            parse_many_auto(inputs, &IngestOptions::default(), &cfg).unwrap();
+        assert_eq!(arena.text_fallbacks, ["bad.json"]);
+        assert_eq!(arena.fileset_formats, vec![Format::Unknown, Format::Json]);
+        assert_eq!(
+            child_kinds(&arena),
+            vec![NodeKind::Array, NodeKind::Object]
+        );
+    }
+
+    #[test]
+    fn fallback_text_is_the_entry_as_written() {
+        let bad = b"{\"a\": \"x\\ty\\u00e9\", \"b\": [1 true]}\n";
+        let inputs = vec![
+            ("bad.json".to_string(), bad.to_vec()),
+            ("bad.jsonl".to_string(), bad.to_vec()),
+        ];
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            parse_many_auto(inputs, &IngestOptions::default(), &cfg).unwrap();
+        assert_eq!(arena.text_fallbacks, ["bad.json", "bad.jsonl"]);
+        let root = &arena.nodes[arena.root_id];
+        for &file in &arena.children[root.children_start..][..2] {
+            let lines = &arena.nodes[file];
+            let line = &arena.nodes[arena.children[lines.children_start]];
+            assert_eq!(
+                line.string_value.as_deref().map(str::as_bytes),
+                Some(&bad[..bad.len() - 1])
+            );
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// BSON adapter for the ingest boundary. Concatenated documents (MongoDB
+/// dumps) become a sampled array; ObjectIds, dates and other BSON types
+/// use MongoDB Extended JSON shapes.
+pub struct BsonIngest;
+
+impl Ingest for BsonIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::bson_ingest::build_bson_tree_arena_from_bytes(&bytes, cfg)
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::bson_ingest::build_bson_tree_arena_from_many(&inputs, cfg)
+    }
+}
+
+/// Convenience functions for the BSON ingest path.
+pub fn parse_bson_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    BsonIngest::parse_one(bytes, ingest, cfg)
+}
+
+pub fn parse_bson_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    BsonIngest::parse_many(inputs, ingest, cfg)
+}
@@ -0,0 +1,44 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// CBOR adapter for the ingest boundary. Tags are unwrapped, byte strings
+/// become hex and non-text map keys are rendered as JSON.
+pub struct CborIngest;
+
+impl Ingest for CborIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::cbor_ingest::build_cbor_tree_arena_from_bytes(&bytes, cfg)
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::cbor_ingest::build_cbor_tree_arena_from_many(&inputs, cfg)
+    }
+}
+
+/// Convenience functions for the CBOR ingest path.
+pub fn parse_cbor_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    CborIngest::parse_one(bytes, ingest, cfg)
+}
+
+pub fn parse_cbor_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    CborIngest::parse_many(inputs, ingest, cfg)
+}
@@ -0,0 +1,82 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// CSV adapter for the ingest boundary. The header row names the columns
+/// and every record becomes an object in a root array, so rows go through
+/// the regular array samplers and keep their original row indices.
+pub struct CsvIngest;
+
+impl Ingest for CsvIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::csv_ingest::build_csv_tree_arena_from_bytes(&bytes, b',', cfg)
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::csv_ingest::build_csv_tree_arena_from_many(&inputs, b',', cfg)
+    }
+}
+
+/// Tab-separated variant of `CsvIngest`.
+pub struct TsvIngest;
+
+impl Ingest for TsvIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::csv_ingest::build_csv_tree_arena_from_bytes(&bytes, b'\t', cfg)
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::csv_ingest::build_csv_tree_arena_from_many(&inputs, b'\t', cfg)
+    }
+}
+
+/// Convenience functions for the CSV/TSV ingest paths.
+pub fn parse_csv_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    CsvIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_csv_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    CsvIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
+
+pub fn parse_tsv_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    TsvIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_tsv_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    TsvIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
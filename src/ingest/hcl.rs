@@ -0,0 +1,44 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// HCL adapter for the ingest boundary. Blocks become objects keyed by
+/// type and labels; expressions that need evaluation stay atomic tokens.
+pub struct HclIngest;
+
+impl Ingest for HclIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::hcl_ingest::build_hcl_tree_arena_from_bytes(&bytes, cfg)
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::hcl_ingest::build_hcl_tree_arena_from_many(inputs, cfg)
+    }
+}
+
+/// Convenience functions for the HCL ingest path.
+pub fn parse_hcl_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    HclIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_hcl_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    HclIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use super::Ingest;
+use super::{Ingest, IngestOptions};
 use crate::order::PriorityConfig;
 use crate::utils::tree_arena::JsonTreeArena as TreeArena;
 
@@ -9,29 +9,79 @@ use crate::utils::tree_arena::JsonTreeArena as TreeArena;
 pub struct JsonIngest;
 
 impl Ingest for JsonIngest {
-    fn parse_one(bytes: Vec<u8>, cfg: &PriorityConfig) -> Result<TreeArena> {
-        crate::json_ingest::build_json_tree_arena_from_bytes(bytes, cfg)
+    fn parse_one(
+        bytes: Vec<u8>,
+        ingest: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::json_ingest::build_json_tree_arena_from_bytes(
+            bytes, ingest, cfg,
+        )
     }
 
     fn parse_many(
         inputs: Vec<(String, Vec<u8>)>,
+        ingest: &IngestOptions,
         cfg: &PriorityConfig,
     ) -> Result<TreeArena> {
-        crate::json_ingest::build_json_tree_arena_from_many(inputs, cfg)
+        crate::json_ingest::build_json_tree_arena_from_many(
+            inputs, ingest, cfg,
+        )
     }
 }
 
 /// Convenience functions for the JSON ingest path.
 pub fn parse_json_one(
     bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    JsonIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+/// Parse JSON, keeping what precedes a syntax error or an early end of
+/// input instead of failing.
+pub fn parse_json_one_recovering(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<(TreeArena, Option<crate::json_ingest::JsonRecovery>)> {
+    let bytes = super::decode_one(bytes, ingest);
+    crate::json_ingest::build_json_tree_arena_recovering(bytes, ingest, cfg)
+}
+
+/// Parse a memory-mapped JSON file in place. Input that needs transcoding
+/// is copied into memory and takes the usual path. With `json5_on_comment`,
+/// JSON with `//` or `/*` comments is read as JSON5, as auto mode does for
+/// a `.json` file.
+pub fn parse_json_mapped(
+    input: &mut crate::mapped::MappedInput,
+    ingest: &IngestOptions,
     cfg: &PriorityConfig,
+    json5_on_comment: bool,
 ) -> Result<TreeArena> {
-    JsonIngest::parse_one(bytes, cfg)
+    let Some(start) = crate::encoding::utf8_bom_len(input, ingest.encoding)
+    else {
+        let bytes = super::decode_one(input.to_vec(), ingest);
+        return if json5_on_comment && crate::format::has_json_comment(&bytes) {
+            super::json5::Json5Ingest::parse_one(bytes, ingest, cfg)
+        } else {
+            JsonIngest::parse_one(bytes, ingest, cfg)
+        };
+    };
+    crate::json_ingest::build_json_tree_arena_from_mapped(
+        input,
+        start,
+        ingest,
+        cfg,
+        json5_on_comment,
+    )
 }
 
 pub fn parse_json_many(
     inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
     cfg: &PriorityConfig,
 ) -> Result<TreeArena> {
-    JsonIngest::parse_many(inputs, cfg)
+    JsonIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
 }
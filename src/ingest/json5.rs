@@ -0,0 +1,44 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// JSON5/JSONC adapter for the ingest boundary: comments, trailing commas,
+/// unquoted keys and the other JSON5 relaxations on top of strict JSON.
+pub struct Json5Ingest;
+
+impl Ingest for Json5Ingest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::json5_ingest::build_json5_tree_arena_from_bytes(&bytes, cfg)
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::json5_ingest::build_json5_tree_arena_from_many(&inputs, cfg)
+    }
+}
+
+/// Convenience functions for the JSON5 ingest path.
+pub fn parse_json5_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    Json5Ingest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_json5_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    Json5Ingest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
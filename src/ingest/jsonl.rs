@@ -0,0 +1,46 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// NDJSON / JSON Lines adapter for the ingest boundary. Each non-blank
+/// line is parsed as its own JSON value and the records form a root array,
+/// so array sampling and bias apply across records. Multi-file inputs
+/// produce a fileset object whose values are the per-file record arrays.
+pub struct JsonlIngest;
+
+impl Ingest for JsonlIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::json_ingest::build_jsonl_tree_arena_from_bytes(bytes, cfg)
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::json_ingest::build_jsonl_tree_arena_from_many(inputs, cfg)
+    }
+}
+
+/// Convenience functions for the JSON Lines ingest path.
+pub fn parse_jsonl_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    JsonlIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_jsonl_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    JsonlIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::keyvalue_ingest::KeyValueFormat;
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// INI adapter for the ingest boundary. `[section]` headers become
+/// objects; values are strings unless `IngestOptions::typed_values`
+/// is set.
+pub struct IniIngest;
+
+impl Ingest for IniIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        ingest: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::keyvalue_ingest::build_keyvalue_tree_arena_from_bytes(
+            &bytes,
+            KeyValueFormat::Ini,
+            ingest,
+            cfg,
+        )
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        ingest: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::keyvalue_ingest::build_keyvalue_tree_arena_from_many(
+            inputs,
+            KeyValueFormat::Ini,
+            ingest,
+            cfg,
+        )
+    }
+}
+
+/// dotenv adapter: a flat object of the assigned variables.
+pub struct DotenvIngest;
+
+impl Ingest for DotenvIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        ingest: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::keyvalue_ingest::build_keyvalue_tree_arena_from_bytes(
+            &bytes,
+            KeyValueFormat::Dotenv,
+            ingest,
+            cfg,
+        )
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        ingest: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::keyvalue_ingest::build_keyvalue_tree_arena_from_many(
+            inputs,
+            KeyValueFormat::Dotenv,
+            ingest,
+            cfg,
+        )
+    }
+}
+
+/// Java `.properties` adapter; dotted keys nest into objects when
+/// `IngestOptions::nest_dotted_keys` is set.
+pub struct PropertiesIngest;
+
+impl Ingest for PropertiesIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        ingest: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::keyvalue_ingest::build_keyvalue_tree_arena_from_bytes(
+            &bytes,
+            KeyValueFormat::Properties,
+            ingest,
+            cfg,
+        )
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        ingest: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::keyvalue_ingest::build_keyvalue_tree_arena_from_many(
+            inputs,
+            KeyValueFormat::Properties,
+            ingest,
+            cfg,
+        )
+    }
+}
+
+/// Convenience functions for the INI, dotenv and `.properties` ingest
+/// paths.
+pub fn parse_ini_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    IniIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_ini_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    IniIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
+
+pub fn parse_dotenv_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    DotenvIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_dotenv_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    DotenvIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
+
+pub fn parse_properties_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    PropertiesIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_properties_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    PropertiesIngest::parse_many(
+        super::decode_many(inputs, ingest),
+        ingest,
+        cfg,
+    )
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// logfmt adapter for the ingest boundary. Each non-blank line becomes an
+/// object of its `key=value` pairs in a root array; lines that are not
+/// made of pairs stay raw strings.
+pub struct LogfmtIngest;
+
+impl Ingest for LogfmtIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        ingest: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::logfmt_ingest::build_logfmt_tree_arena_from_bytes(
+            &bytes, ingest, cfg,
+        )
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        ingest: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::logfmt_ingest::build_logfmt_tree_arena_from_many(
+            inputs, ingest, cfg,
+        )
+    }
+}
+
+/// Convenience functions for the logfmt ingest path.
+pub fn parse_logfmt_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    LogfmtIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_logfmt_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    LogfmtIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// Markdown adapter for the ingest boundary. Sections become objects keyed
+/// by heading, paragraphs and lists become arrays and fenced code blocks
+/// become strings; YAML front matter goes through the YAML ingest.
+pub struct MarkdownIngest;
+
+impl Ingest for MarkdownIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::markdown_ingest::build_markdown_tree_arena_from_bytes(
+            &bytes, cfg,
+        )
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::markdown_ingest::build_markdown_tree_arena_from_many(
+            inputs, cfg,
+        )
+    }
+}
+
+/// Convenience functions for the Markdown ingest path.
+pub fn parse_markdown_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    MarkdownIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_markdown_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    MarkdownIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
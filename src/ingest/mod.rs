@@ -1,26 +1,106 @@
 use anyhow::Result;
 
+use crate::encoding::Encoding;
 use crate::order::PriorityConfig;
+use crate::order::types::DuplicateKeys;
 use crate::utils::tree_arena::JsonTreeArena as TreeArena;
 
+/// How the input is read, as opposed to `PriorityConfig`, which decides
+/// what of it is shown. The default reads every format as written.
+#[derive(Clone, Debug, Default)]
+pub struct IngestOptions {
+    // Resolve keys repeated within one JSON object; `None` keeps every
+    // occurrence.
+    pub duplicate_keys: Option<DuplicateKeys>,
+    // Encoding of text input without a byte order mark; `None` reads it as
+    // UTF-8 unless it looks like UTF-16.
+    pub encoding: Option<&'static Encoding>,
+    // INI, dotenv, `.properties` and logfmt values: read unquoted numbers and
+    // booleans as such instead of strings.
+    pub typed_values: bool,
+    // `.properties` keys: nest `a.b.c` into objects.
+    pub nest_dotted_keys: bool,
+    // Fileset inputs left unread because the budget could not show them;
+    // counted in the `N more files` summary.
+    pub unread_files: usize,
+}
+
 /// Format-agnostic ingest boundary. Other formats can implement this trait
 /// to produce the neutral TreeArena without going through JSON first.
 pub trait Ingest {
-    fn parse_one(bytes: Vec<u8>, cfg: &PriorityConfig) -> Result<TreeArena>;
+    fn parse_one(
+        bytes: Vec<u8>,
+        ingest: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena>;
     fn parse_many(
         inputs: Vec<(String, Vec<u8>)>,
+        ingest: &IngestOptions,
         cfg: &PriorityConfig,
     ) -> Result<TreeArena>;
 }
 
+/// Shared decoding step of the text formats: input with a byte order mark,
+/// UTF-16 input and input in the configured encoding is transcoded to
+/// UTF-8. Binary formats read their bytes as they are.
+pub(crate) fn decode_one(bytes: Vec<u8>, ingest: &IngestOptions) -> Vec<u8> {
+    crate::encoding::decode_to_utf8(bytes, ingest.encoding)
+}
+
+pub(crate) fn decode_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+) -> Vec<(String, Vec<u8>)> {
+    inputs
+        .into_iter()
+        .map(|(name, bytes)| (name, decode_one(bytes, ingest)))
+        .collect()
+}
+
 // Submodules for per-format adapters
+pub mod auto;
+pub mod bson;
+pub mod cbor;
+pub mod csv;
+pub mod hcl;
 pub mod json;
+pub mod json5;
+pub mod jsonl;
+pub mod keyvalue;
+pub mod logfmt;
+pub mod markdown;
+pub mod msgpack;
 pub mod text;
+pub mod toml;
+pub mod xml;
 pub mod yaml;
 
 // Re-export commonly used helpers for convenience (keep adapter types private)
-pub use json::{parse_json_many, parse_json_one};
-pub use text::{parse_text_many, parse_text_one};
+pub use auto::parse_many_auto;
+pub use bson::{parse_bson_many, parse_bson_one};
+pub use cbor::{parse_cbor_many, parse_cbor_one};
+pub use csv::{parse_csv_many, parse_csv_one, parse_tsv_many, parse_tsv_one};
+pub use hcl::{parse_hcl_many, parse_hcl_one};
+pub use json::{
+    parse_json_many, parse_json_mapped, parse_json_one,
+    parse_json_one_recovering,
+};
+pub use json5::{parse_json5_many, parse_json5_one};
+pub use jsonl::{parse_jsonl_many, parse_jsonl_one};
+pub use keyvalue::{
+    parse_dotenv_many, parse_dotenv_one, parse_ini_many, parse_ini_one,
+    parse_properties_many, parse_properties_one,
+};
+pub use logfmt::{parse_logfmt_many, parse_logfmt_one};
+pub use markdown::{parse_markdown_many, parse_markdown_one};
+pub use msgpack::{parse_msgpack_many, parse_msgpack_one};
+pub use text::{
+    parse_outline_many, parse_outline_one, parse_text_many, parse_text_one,
+};
+pub use toml::{parse_toml_many, parse_toml_one};
+pub use xml::{
+    parse_html_many, parse_html_one, parse_xml_many, parse_xml_one,
+};
 pub use yaml::{parse_yaml_many, parse_yaml_one};
 
 #[cfg(test)]
@@ -32,6 +112,7 @@ mod tests {
     fn parse_one_basic_shape() {
         let arena = parse_json_one(
             b"{\"a\":1}".to_vec(),
+            &IngestOptions::default(),
             &PriorityConfig::new(usize::MAX, usize::MAX),
         )
         .unwrap();
@@ -52,6 +133,7 @@ mod tests {
         ];
         let arena = parse_json_many(
             inputs,
+            &IngestOptions::default(),
             &PriorityConfig::new(usize::MAX, usize::MAX),
         )
         .unwrap();
@@ -0,0 +1,44 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// MessagePack adapter for the ingest boundary. Map keys that are not
+/// strings are stringified, binary blobs become hex strings.
+pub struct MsgpackIngest;
+
+impl Ingest for MsgpackIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::msgpack_ingest::build_msgpack_tree_arena_from_bytes(&bytes, cfg)
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::msgpack_ingest::build_msgpack_tree_arena_from_many(&inputs, cfg)
+    }
+}
+
+/// Convenience functions for the MessagePack ingest path.
+pub fn parse_msgpack_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    MsgpackIngest::parse_one(bytes, ingest, cfg)
+}
+
+pub fn parse_msgpack_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    MsgpackIngest::parse_many(inputs, ingest, cfg)
+}
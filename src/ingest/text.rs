@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use super::Ingest;
+use super::{Ingest, IngestOptions};
 use crate::order::PriorityConfig;
 use crate::utils::tree_arena::JsonTreeArena as TreeArena;
 
@@ -17,12 +17,17 @@ use crate::utils::tree_arena::JsonTreeArena as TreeArena;
 pub struct TextIngest;
 
 impl Ingest for TextIngest {
-    fn parse_one(bytes: Vec<u8>, cfg: &PriorityConfig) -> Result<TreeArena> {
-        crate::text_ingest::build_text_tree_arena_from_bytes(bytes, cfg)
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::text_ingest::build_text_tree_arena_from_bytes(&bytes, cfg)
     }
 
     fn parse_many(
         inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
         cfg: &PriorityConfig,
     ) -> Result<TreeArena> {
         crate::text_ingest::build_text_tree_arena_from_many(inputs, cfg)
@@ -32,14 +37,54 @@ impl Ingest for TextIngest {
 /// Convenience functions for the Text ingest path.
 pub fn parse_text_one(
     bytes: Vec<u8>,
+    ingest: &IngestOptions,
     cfg: &PriorityConfig,
 ) -> Result<TreeArena> {
-    TextIngest::parse_one(bytes, cfg)
+    TextIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
 }
 
 pub fn parse_text_many(
     inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    TextIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
+
+/// Outline adapter: text nested by indentation and brackets, so source
+/// code and stack traces keep their top-level lines when bodies are cut.
+pub struct OutlineIngest;
+
+impl Ingest for OutlineIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::text_ingest::build_outline_tree_arena_from_bytes(&bytes, cfg)
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::text_ingest::build_outline_tree_arena_from_many(inputs, cfg)
+    }
+}
+
+pub fn parse_outline_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    OutlineIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_outline_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
     cfg: &PriorityConfig,
 ) -> Result<TreeArena> {
-    TextIngest::parse_many(inputs, cfg)
+    OutlineIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
 }
@@ -0,0 +1,46 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+
+/// TOML adapter for the ingest boundary. Parses a TOML document with the
+/// `toml` crate and builds the neutral `TreeArena` through the shared
+/// serde builder, so tables become objects and arrays (including arrays
+/// of tables) go through the regular array samplers.
+pub struct TomlIngest;
+
+impl Ingest for TomlIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::toml_ingest::build_toml_tree_arena_from_bytes(&bytes, cfg)
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::toml_ingest::build_toml_tree_arena_from_many(inputs, cfg)
+    }
+}
+
+/// Convenience functions for the TOML ingest path.
+pub fn parse_toml_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    TomlIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_toml_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    TomlIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
@@ -0,0 +1,100 @@
+use anyhow::Result;
+
+use super::{Ingest, IngestOptions};
+use crate::order::PriorityConfig;
+use crate::utils::tree_arena::JsonTreeArena as TreeArena;
+use crate::xml_ingest::Markup;
+
+/// XML adapter for the ingest boundary. Elements become objects with
+/// `@attribute` keys, repeated child elements become arrays (sampled like
+/// any other array) and text-only elements collapse to strings.
+pub struct XmlIngest;
+
+impl Ingest for XmlIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::xml_ingest::build_markup_tree_arena_from_bytes(
+            &bytes,
+            Markup::Xml,
+            cfg,
+        )
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::xml_ingest::build_markup_tree_arena_from_many(
+            inputs,
+            Markup::Xml,
+            cfg,
+        )
+    }
+}
+
+/// HTML variant of `XmlIngest` that tolerates void elements, unquoted
+/// attributes and unclosed tags.
+pub struct HtmlIngest;
+
+impl Ingest for HtmlIngest {
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::xml_ingest::build_markup_tree_arena_from_bytes(
+            &bytes,
+            Markup::Html,
+            cfg,
+        )
+    }
+
+    fn parse_many(
+        inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::xml_ingest::build_markup_tree_arena_from_many(
+            inputs,
+            Markup::Html,
+            cfg,
+        )
+    }
+}
+
+/// Convenience functions for the XML/HTML ingest paths.
+pub fn parse_xml_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    XmlIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_xml_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    XmlIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
+
+pub fn parse_html_one(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    HtmlIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
+}
+
+pub fn parse_html_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    cfg: &PriorityConfig,
+) -> Result<TreeArena> {
+    HtmlIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
+}
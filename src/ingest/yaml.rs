@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use super::Ingest;
+use super::{Ingest, IngestOptions};
 use crate::order::PriorityConfig;
 use crate::utils::tree_arena::JsonTreeArena as TreeArena;
 
@@ -11,12 +11,17 @@ use crate::utils::tree_arena::JsonTreeArena as TreeArena;
 pub struct YamlIngest;
 
 impl Ingest for YamlIngest {
-    fn parse_one(bytes: Vec<u8>, cfg: &PriorityConfig) -> Result<TreeArena> {
-        crate::yaml_ingest::build_yaml_tree_arena_from_bytes(bytes, cfg)
+    fn parse_one(
+        bytes: Vec<u8>,
+        _: &IngestOptions,
+        cfg: &PriorityConfig,
+    ) -> Result<TreeArena> {
+        crate::yaml_ingest::build_yaml_tree_arena_from_bytes(&bytes, cfg)
     }
 
     fn parse_many(
         inputs: Vec<(String, Vec<u8>)>,
+        _: &IngestOptions,
         cfg: &PriorityConfig,
     ) -> Result<TreeArena> {
         crate::yaml_ingest::build_yaml_tree_arena_from_many(inputs, cfg)
@@ -26,14 +31,16 @@ impl Ingest for YamlIngest {
 /// Convenience functions for the YAML ingest path.
 pub fn parse_yaml_one(
     bytes: Vec<u8>,
+    ingest: &IngestOptions,
     cfg: &PriorityConfig,
 ) -> Result<TreeArena> {
-    YamlIngest::parse_one(bytes, cfg)
+    YamlIngest::parse_one(super::decode_one(bytes, ingest), ingest, cfg)
 }
 
 pub fn parse_yaml_many(
     inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
     cfg: &PriorityConfig,
 ) -> Result<TreeArena> {
-    YamlIngest::parse_many(inputs, cfg)
+    YamlIngest::parse_many(super::decode_many(inputs, ingest), ingest, cfg)
 }
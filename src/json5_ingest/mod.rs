@@ -0,0 +1,513 @@
+use anyhow::{Context, Result};
+use serde::de::value::{Error as ValueError, MapDeserializer};
+use serde::de::{
+    DeserializeSeed, Error as _, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+
+use crate::PriorityConfig;
+use crate::json_ingest::{
+    NUMBER_TOKEN, build_tree_arena_from_deserializer,
+    build_tree_arena_from_many_deserializers, is_json_number,
+};
+use crate::utils::tree_arena::JsonTreeArena;
+
+/// Build an arena from JSON5 (a superset of JSONC): comments, trailing
+/// commas, unquoted keys, single-quoted strings, hex numbers, `Infinity`
+/// and `NaN`. Values stream into the JSON builder, so arrays are sampled
+/// exactly as for strict JSON.
+pub fn build_json5_tree_arena_from_bytes(
+    bytes: &[u8],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let src =
+        std::str::from_utf8(bytes).context("JSON5 input is not UTF-8")?;
+    let mut parser = Json5Parser::new(src);
+    let arena = build_tree_arena_from_deserializer(&mut parser, config)?;
+    parser.end()?;
+    Ok(arena)
+}
+
+pub fn build_json5_tree_arena_from_many(
+    inputs: &[(String, Vec<u8>)],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut parsers: Vec<(&str, Json5Parser<'_>)> =
+        Vec::with_capacity(inputs.len());
+    for (key, bytes) in inputs {
+        let src = std::str::from_utf8(bytes)
+            .with_context(|| format!("{key}: JSON5 input is not UTF-8"))?;
+        parsers.push((key, Json5Parser::new(src)));
+    }
+    let arena = build_tree_arena_from_many_deserializers(
+        parsers
+            .iter_mut()
+            .map(|(key, parser)| ((*key).to_owned(), parser))
+            .collect(),
+        config,
+    )?;
+    for (key, parser) in &parsers {
+        parser
+            .end()
+            .with_context(|| format!("failed to parse {key}"))?;
+    }
+    Ok(arena)
+}
+
+/// Streaming JSON5 reader exposed as a serde `Deserializer`.
+struct Json5Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Json5Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, msg: &str) -> ValueError {
+        let before = &self.src[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rfind('\n')
+            .map_or(before, |nl| &before[nl + 1..])
+            .chars()
+            .count()
+            + 1;
+        ValueError::custom(format!("{msg} at line {line} column {column}"))
+    }
+
+    fn expect(&mut self, wanted: char) -> Result<(), ValueError> {
+        if self.bump() == Some(wanted) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{wanted}`")))
+        }
+    }
+
+    // Only whitespace and comments may follow the root value.
+    fn end(&self) -> Result<(), ValueError> {
+        let mut tail = Json5Parser {
+            src: self.src,
+            pos: self.pos,
+        };
+        tail.skip_trivia()?;
+        match tail.peek() {
+            None => Ok(()),
+            Some(_) => Err(tail.error("trailing characters")),
+        }
+    }
+
+    // Whitespace (any Unicode space, BOM included) and comments.
+    fn skip_trivia(&mut self) -> Result<(), ValueError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start_matches(|c: char| {
+                c.is_whitespace() || c == '\u{feff}'
+            });
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if let Some(body) = trimmed.strip_prefix("/*") {
+                let end = body
+                    .find("*/")
+                    .ok_or_else(|| self.error("unterminated block comment"))?;
+                self.pos += 2 + end + 2;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    // Consumes `word` when it is not followed by more identifier characters.
+    fn eat_keyword(&mut self, word: &str) -> bool {
+        let matches = self
+            .rest()
+            .strip_prefix(word)
+            .is_some_and(|after| !after.starts_with(is_identifier_char));
+        if matches {
+            self.pos += word.len();
+        }
+        matches
+    }
+
+    fn parse_identifier(&mut self) -> Result<String, ValueError> {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                self.bump();
+                self.expect('u')?;
+                name.push(self.parse_unicode_escape()?);
+            } else if is_identifier_char(c) {
+                self.bump();
+                name.push(c);
+            } else {
+                break;
+            }
+        }
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.error("expected an object key"));
+        }
+        Ok(name)
+    }
+
+    fn parse_key(&mut self) -> Result<String, ValueError> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.bump();
+                self.parse_string(quote)
+            }
+            _ => self.parse_identifier(),
+        }
+    }
+
+    // Body of a string whose opening quote was consumed.
+    fn parse_string(&mut self, quote: char) -> Result<String, ValueError> {
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => return Ok(out),
+                Some('\\') => self.parse_escape(&mut out)?,
+                Some('\n' | '\r') | None => {
+                    return Err(self.error("unterminated string"));
+                }
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self, out: &mut String) -> Result<(), ValueError> {
+        let c = self
+            .bump()
+            .ok_or_else(|| self.error("unterminated string"))?;
+        let unescaped = match c {
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\u{b}',
+            '0' => '\0',
+            'x' => self.parse_hex_escape(2)?,
+            'u' => self.parse_unicode_escape()?,
+            // Line continuation: the escaped line break is dropped.
+            '\r' => {
+                if self.peek() == Some('\n') {
+                    self.bump();
+                }
+                return Ok(());
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => return Ok(()),
+            other => other,
+        };
+        out.push(unescaped);
+        Ok(())
+    }
+
+    fn parse_hex_escape(&mut self, digits: usize) -> Result<char, ValueError> {
+        let code = self.parse_hex_code(digits)?;
+        char::from_u32(code).ok_or_else(|| self.error("invalid escape"))
+    }
+
+    fn parse_hex_code(&mut self, digits: usize) -> Result<u32, ValueError> {
+        let hex = self
+            .rest()
+            .get(..digits)
+            .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("invalid escape"))?;
+        self.pos += digits;
+        u32::from_str_radix(hex, 16).map_err(|_| self.error("invalid escape"))
+    }
+
+    // `\uXXXX`, combining UTF-16 surrogate pairs; lone surrogates become
+    // U+FFFD.
+    fn parse_unicode_escape(&mut self) -> Result<char, ValueError> {
+        let high = self.parse_hex_code(4)?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return Ok(char::from_u32(high).unwrap_or('\u{fffd}'));
+        }
+        if !self.rest().starts_with("\\u") {
+            return Ok('\u{fffd}');
+        }
+        self.pos += 2;
+        let low = self.parse_hex_code(4)?;
+        let code =
+            0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00));
+        Ok(char::from_u32(code).unwrap_or('\u{fffd}'))
+    }
+
+    fn number_token(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| {
+                !(c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-'))
+            })
+            .unwrap_or(rest.len());
+        // Signs only belong at the start or right after an exponent.
+        let len = rest[..len]
+            .char_indices()
+            .skip(1)
+            .find(|&(i, c)| {
+                matches!(c, '+' | '-') && !rest[..i].ends_with(['e', 'E'])
+            })
+            .map_or(len, |(i, _)| i);
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn visit_number<V>(&mut self, visitor: V) -> Result<V::Value, ValueError>
+    where
+        V: Visitor<'a>,
+    {
+        let token = self.number_token();
+        // Numbers as JSON spells them keep their source text; only the
+        // JSON5 forms (hex, `Infinity`, `.5`, `+1`) are read as values.
+        if is_json_number(token.as_bytes()) {
+            return visitor.visit_map(MapDeserializer::new(std::iter::once(
+                (NUMBER_TOKEN, token),
+            )));
+        }
+        let (negative, unsigned) = match token.as_bytes().first() {
+            Some(b'-') => (true, &token[1..]),
+            Some(b'+') => (false, &token[1..]),
+            _ => (false, token),
+        };
+        let sign = if negative { -1.0 } else { 1.0 };
+        match unsigned {
+            "Infinity" => visitor.visit_f64(sign * f64::INFINITY),
+            "NaN" => visitor.visit_f64(f64::NAN),
+            _ => {
+                if let Some(n) = parse_integer(unsigned, negative) {
+                    return visitor.visit_i128(n);
+                }
+                let v = parse_decimal(unsigned)
+                    .ok_or_else(|| self.error("invalid number"))?;
+                visitor.visit_f64(sign * v)
+            }
+        }
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '\u{200c}' | '\u{200d}')
+}
+
+// Decimal or `0x` hexadecimal integer literal, when it fits 128 bits.
+fn parse_integer(digits: &str, negative: bool) -> Option<i128> {
+    let magnitude = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None if digits.bytes().all(|b| b.is_ascii_digit()) => {
+            digits.parse::<i128>().ok()?
+        }
+        None => return None,
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+// JSON5 allows a leading or trailing decimal point (`.5`, `5.`).
+fn parse_decimal(digits: &str) -> Option<f64> {
+    let valid = digits.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && digits.bytes().any(|b| b.is_ascii_digit());
+    valid.then(|| digits.parse::<f64>().ok()).flatten()
+}
+
+impl<'de> serde::Deserializer<'de> for &mut Json5Parser<'de> {
+    type Error = ValueError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.skip_trivia()?;
+        match self.peek() {
+            Some('{') => {
+                self.bump();
+                visitor.visit_map(Entries::new(self, '}'))
+            }
+            Some('[') => {
+                self.bump();
+                visitor.visit_seq(Entries::new(self, ']'))
+            }
+            Some(quote @ ('"' | '\'')) => {
+                self.bump();
+                visitor.visit_string(self.parse_string(quote)?)
+            }
+            Some(c) if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => {
+                self.visit_number(visitor)
+            }
+            _ => self.visit_keyword(visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'a> Json5Parser<'a> {
+    fn visit_keyword<V>(&mut self, visitor: V) -> Result<V::Value, ValueError>
+    where
+        V: Visitor<'a>,
+    {
+        if self.eat_keyword("null") {
+            visitor.visit_unit()
+        } else if self.eat_keyword("true") {
+            visitor.visit_bool(true)
+        } else if self.eat_keyword("false") {
+            visitor.visit_bool(false)
+        } else if self.rest().starts_with(['I', 'N']) {
+            self.visit_number(visitor)
+        } else {
+            Err(self.error("expected a value"))
+        }
+    }
+}
+
+// Members of an object or array whose opening bracket was consumed.
+// Commas separate members and one may trail the last.
+struct Entries<'p, 'a> {
+    parser: &'p mut Json5Parser<'a>,
+    close: char,
+    first: bool,
+}
+
+impl<'p, 'a> Entries<'p, 'a> {
+    fn new(parser: &'p mut Json5Parser<'a>, close: char) -> Self {
+        Self {
+            parser,
+            close,
+            first: true,
+        }
+    }
+
+    // Moves to the next member; false once the closing bracket is eaten.
+    fn advance(&mut self) -> Result<bool, ValueError> {
+        self.parser.skip_trivia()?;
+        if !self.first && self.parser.peek() != Some(self.close) {
+            self.parser.expect(',')?;
+            self.parser.skip_trivia()?;
+        }
+        self.first = false;
+        if self.parser.peek() == Some(self.close) {
+            self.parser.bump();
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+impl<'de> MapAccess<'de> for Entries<'_, 'de> {
+    type Error = ValueError;
+
+    fn next_key_seed<K>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if !self.advance()? {
+            return Ok(None);
+        }
+        let key = self.parser.parse_key()?;
+        self.parser.skip_trivia()?;
+        self.parser.expect(':')?;
+        seed.deserialize(key.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.parser)
+    }
+}
+
+impl<'de> SeqAccess<'de> for Entries<'_, 'de> {
+    type Error = ValueError;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if !self.advance()? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.parser).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize as _;
+
+    fn to_json(src: &str) -> serde_json::Value {
+        let mut parser = Json5Parser::new(src);
+        let value = serde_json::Value::deserialize(&mut parser)
+            .unwrap_or_else(|e| panic!("{e}"));
+        parser.end().unwrap_or_else(|e| panic!("{e}"));
+        value
+    }
+
+    #[test]
+    fn accepts_jsonc_and_json5_syntax() {
+        let src = r#"
+            // tsconfig-style comment
+            {
+              compilerOptions: { strict: true, /* inline */ },
+              'paths': ['a', "b",],
+              hex: 0x1F, half: .5, big: +1e3, str: 'it\'s \x41é',
+            }
+        "#;
+        assert_eq!(
+            to_json(src),
+            serde_json::json!({
+                "compilerOptions": {"strict": true},
+                "paths": ["a", "b"],
+                "hex": 31,
+                "half": 0.5,
+                "big": 1000.0,
+                "str": "it's Aé",
+            })
+        );
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn errors_name_the_position() {
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let err =
+            build_json5_tree_arena_from_bytes(b"{\n  a: [1,, 2]\n}", &cfg)
+                .unwrap_err();
+        assert!(format!("{err:#}").contains("line 2"), "{err:#}");
+        assert!(build_json5_tree_arena_from_bytes(b"{} x", &cfg).is_err());
+        assert!(build_json5_tree_arena_from_bytes(b"/* open", &cfg).is_err());
+        assert!(build_json5_tree_arena_from_bytes(b"[1, 2,]", &cfg).is_ok());
+    }
+}
@@ -3,15 +3,26 @@ use serde::de::{DeserializeSeed, MapAccess, SeqAccess, Visitor};
 use std::cell::RefCell;
 
 use crate::order::NodeKind;
-use crate::utils::tree_arena::{JsonTreeArena, JsonTreeNode};
+use crate::order::types::DuplicateKeys;
+use crate::utils::text::to_hex;
+use crate::utils::tree_arena::{JsonTreeArena, JsonTreeNode, RepeatedKey};
 
+use super::duplicates;
+use super::numbers::{NUMBER_AT, NUMBER_TOKEN};
 use super::samplers::ArraySamplerKind;
+use crate::toml_ingest::DATETIME_TOKEN;
 
 #[derive(Default)]
 pub(crate) struct JsonTreeBuilder {
     arena: RefCell<JsonTreeArena>,
     pub(crate) array_cap: usize,
     sampler: ArraySamplerKind,
+    // When set, keys repeated within one object are collapsed to a single
+    // entry and recorded on the arena; otherwise every occurrence is kept.
+    pub(crate) duplicate_keys: Option<DuplicateKeys>,
+    // Numbers read from a simd-json tape whose tokens are still to be
+    // sliced from the input, as (tape position, node id).
+    number_sources: RefCell<Vec<(usize, usize)>>,
 }
 
 impl JsonTreeBuilder {
@@ -23,6 +34,8 @@ impl JsonTreeBuilder {
             arena: RefCell::new(JsonTreeArena::default()),
             array_cap,
             sampler,
+            duplicate_keys: None,
+            number_sources: RefCell::new(Vec::new()),
         }
     }
 
@@ -31,7 +44,11 @@ impl JsonTreeBuilder {
     }
 
     pub(crate) fn finish(self) -> JsonTreeArena {
-        self.arena.into_inner()
+        let mut arena = self.arena.into_inner();
+        // Objects are recorded once their values are built, so nested
+        // objects come first.
+        arena.repeated_keys.sort_by_key(|r| r.object);
+        arena
     }
 
     // Create an object node from provided keys and child ids and return its id.
@@ -46,6 +63,50 @@ impl JsonTreeBuilder {
         id
     }
 
+    // Create an array node by running the configured sampler over `seq`
+    // and return its id. Shared by JSON arrays and line-delimited roots.
+    pub(crate) fn push_sampled_array<'de, A>(
+        &self,
+        seq: &mut A,
+    ) -> Result<usize, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let id = self.push_default();
+        let sampled = self.sampler.sample_stream(seq, self, self.array_cap)?;
+        let kept = sampled.children.len();
+        self.finish_array(
+            id,
+            kept,
+            sampled.total_len,
+            sampled.children,
+            sampled.indices,
+        );
+        Ok(id)
+    }
+
+    // The current size of the arena, to roll back to.
+    pub(crate) fn mark(&self) -> Mark {
+        let a = self.arena.borrow();
+        Mark {
+            nodes: a.nodes.len(),
+            children: a.children.len(),
+            obj_keys: a.obj_keys.len(),
+            arr_indices: a.arr_indices.len(),
+        }
+    }
+
+    // Discard everything built since `mark` was taken.
+    pub(crate) fn rollback(&self, mark: Mark) {
+        let mut a = self.arena.borrow_mut();
+        a.nodes.truncate(mark.nodes);
+        a.children.truncate(mark.children);
+        a.obj_keys.truncate(mark.obj_keys);
+        a.arr_indices.truncate(mark.arr_indices);
+        a.repeated_keys.retain(|r| r.object < mark.nodes);
+        self.number_sources.borrow_mut().clear();
+    }
+
     fn push_default(&self) -> usize {
         let mut a = self.arena.borrow_mut();
         let id = a.nodes.len();
@@ -85,6 +146,32 @@ impl JsonTreeBuilder {
             n.string_value = Some(s);
         })
     }
+    fn push_datetime(&self, text: String) -> usize {
+        let id = self.push_string_owned(text);
+        self.arena.borrow_mut().datetimes.insert(id);
+        id
+    }
+    fn push_number_token(&self, token: String) -> usize {
+        self.push_with(|n| {
+            n.kind = NodeKind::Number;
+            n.atomic_token = Some(token);
+        })
+    }
+    fn push_number_at(&self, at: usize) -> usize {
+        let id = self.push_with(|n| n.kind = NodeKind::Number);
+        self.number_sources.borrow_mut().push((at, id));
+        id
+    }
+
+    // Numbers pushed by tape position since the last call, in tape order.
+    pub(crate) fn take_number_sources(&self) -> Vec<(usize, usize)> {
+        std::mem::take(&mut *self.number_sources.borrow_mut())
+    }
+
+    pub(crate) fn set_token(&self, id: usize, token: String) {
+        self.arena.borrow_mut().nodes[id].atomic_token = Some(token);
+    }
+
     fn push_null(&self) -> usize {
         self.push_with(|n| {
             n.kind = NodeKind::Null;
@@ -128,6 +215,29 @@ impl JsonTreeBuilder {
         n.arr_indices_len = pushed_len.min(kept);
     }
 
+    // Resolve repeated keys of object `id` according to the configured
+    // policy before its entries are stored.
+    fn resolve_duplicates(
+        &self,
+        id: usize,
+        keys: Vec<String>,
+        children: Vec<usize>,
+    ) -> (Vec<String>, Vec<usize>) {
+        let Some(policy) = self.duplicate_keys else {
+            return (keys, children);
+        };
+        let deduped = duplicates::dedupe(policy, keys, children);
+        let mut a = self.arena.borrow_mut();
+        a.repeated_keys.extend(deduped.repeated.into_iter().map(
+            |(key, occurrences)| RepeatedKey {
+                object: id,
+                key,
+                occurrences,
+            },
+        ));
+        (deduped.keys, deduped.children)
+    }
+
     fn finish_object(
         &self,
         id: usize,
@@ -150,6 +260,14 @@ impl JsonTreeBuilder {
     }
 }
 
+#[derive(Clone, Copy)]
+pub(crate) struct Mark {
+    nodes: usize,
+    children: usize,
+    obj_keys: usize,
+    arr_indices: usize,
+}
+
 pub(crate) struct NodeSeed<'a> {
     pub(crate) b: &'a JsonTreeBuilder,
 }
@@ -192,12 +310,27 @@ impl<'de> Visitor<'de> for NodeVisitor<'_> {
     {
         Ok(self.b.push_number(v))
     }
+    fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(self.b.push_number_token(v.to_string()))
+    }
+    fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(self.b.push_number_token(v.to_string()))
+    }
     fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
     {
-        let num = serde_json::Number::from_f64(v)
-            .ok_or_else(|| E::custom("invalid f64"))?;
+        // Binary formats can carry NaN and infinities, which have no JSON
+        // number form; keep them visible as strings.
+        let Some(num) = serde_json::Number::from_f64(v) else {
+            return Ok(self.b.push_string_owned(v.to_string()));
+        };
         let id = self.b.push_with(|n| {
             n.kind = NodeKind::Number;
             n.atomic_token = Some(num.to_string());
@@ -216,6 +349,14 @@ impl<'de> Visitor<'de> for NodeVisitor<'_> {
     {
         Ok(self.b.push_string_owned(v))
     }
+    // Binary payloads (MessagePack bin, CBOR byte strings) have no tree
+    // kind of their own and are kept as lowercase hex strings.
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(self.b.push_string_owned(to_hex(v)))
+    }
     fn visit_unit<E>(self) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
@@ -228,51 +369,131 @@ impl<'de> Visitor<'de> for NodeVisitor<'_> {
     {
         self.visit_unit()
     }
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.b.seed().deserialize(deserializer)
+    }
+    fn visit_newtype_struct<D>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.b.seed().deserialize(deserializer)
+    }
 
     fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
     where
         A: SeqAccess<'de>,
     {
-        let id = self.b.push_default();
-        let sampled = self.b.sampler.sample_stream(
-            &mut seq,
-            self.b,
-            self.b.array_cap,
-        )?;
-        let kept = sampled.children.len();
-        self.b.finish_array(
-            id,
-            kept,
-            sampled.total_len,
-            sampled.children,
-            sampled.indices,
-        );
-        Ok(id)
+        self.b.push_sampled_array(&mut seq)
     }
 
     fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
     where
         A: MapAccess<'de>,
     {
+        let first = map.next_key_seed(KeySeed)?;
+        // Numbers kept as text arrive as a one-entry map.
+        match first.as_deref() {
+            Some(NUMBER_TOKEN) => {
+                return Ok(self.b.push_number_token(map.next_value()?));
+            }
+            Some(NUMBER_AT) => {
+                return Ok(self.b.push_number_at(map.next_value()?));
+            }
+            Some(DATETIME_TOKEN) => {
+                return Ok(self.b.push_datetime(map.next_value()?));
+            }
+            _ => {}
+        }
         let id = self.b.push_default();
         let mut local_children: Vec<usize> = Vec::new();
         let mut local_keys: Vec<String> = Vec::new();
         let low = map.size_hint().unwrap_or(0);
         local_children.reserve(low);
         local_keys.reserve(low);
-        let mut count = 0usize;
-        while let Some(key) = map.next_key::<String>()? {
+        let mut next = first;
+        while let Some(key) = next {
             let cid: usize = {
                 let seed = self.b.seed();
                 map.next_value_seed(seed)?
             };
             local_children.push(cid);
             local_keys.push(key);
-            count += 1;
+            next = map.next_key_seed(KeySeed)?;
         }
+        let (local_keys, local_children) =
+            self.b.resolve_duplicates(id, local_keys, local_children);
+        let count = local_keys.len();
         self.b.finish_object(id, count, local_children, local_keys);
         Ok(id)
     }
 }
 
-impl JsonTreeBuilder {}
+// Object keys are strings in JSON, but binary formats also allow numbers,
+// booleans and byte strings as keys; those are kept as their text form.
+struct KeySeed;
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = String;
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl Visitor<'_> for KeySeed {
+    type Value = String;
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a string, number or boolean object key")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_owned())
+    }
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v)
+    }
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_string())
+    }
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_string())
+    }
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_string())
+    }
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_string())
+    }
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(to_hex(v))
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use crate::order::NodeKind;
+use crate::order::types::DuplicateKeys;
+use crate::utils::json::json_string;
+use crate::utils::tree_arena::JsonTreeArena;
+
+/// A key that occurred more than once in one JSON object and was resolved
+/// to a single entry during ingest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateKey {
+    /// Input the object came from, for filesets.
+    pub file: Option<String>,
+    /// jq-style path of the key, e.g. `.users[3].name`.
+    pub path: String,
+    /// How many times the key occurred in the object.
+    pub occurrences: usize,
+}
+
+// The entries of one object after resolving repeated keys, plus every key
+// that was repeated with its number of occurrences.
+pub(crate) struct Deduped {
+    pub(crate) keys: Vec<String>,
+    pub(crate) children: Vec<usize>,
+    pub(crate) repeated: Vec<(String, usize)>,
+}
+
+/// Keep one entry per key. Keys stay at the position of their first
+/// occurrence; `KeepLast` swaps in the value of the last one.
+pub(crate) fn dedupe(
+    policy: DuplicateKeys,
+    keys: Vec<String>,
+    children: Vec<usize>,
+) -> Deduped {
+    let mut slots: HashMap<String, usize> = HashMap::new();
+    let mut kept = Deduped {
+        keys: Vec::with_capacity(keys.len()),
+        children: Vec::with_capacity(children.len()),
+        repeated: Vec::new(),
+    };
+    let mut occurrences: Vec<usize> = Vec::with_capacity(keys.len());
+    for (key, child) in keys.into_iter().zip(children) {
+        match slots.entry(key) {
+            Entry::Occupied(slot) => {
+                let i = *slot.get();
+                occurrences[i] += 1;
+                if policy == DuplicateKeys::KeepLast {
+                    kept.children[i] = child;
+                }
+            }
+            Entry::Vacant(slot) => {
+                kept.keys.push(slot.key().clone());
+                kept.children.push(child);
+                occurrences.push(1);
+                slot.insert(kept.keys.len() - 1);
+            }
+        }
+    }
+    kept.repeated = kept
+        .keys
+        .iter()
+        .zip(occurrences)
+        .filter(|&(_, n)| n > 1)
+        .map(|(key, n)| (key.clone(), n))
+        .collect();
+    kept
+}
+
+/// List the repeated keys recorded in `arena` with their paths, in source
+/// order.
+pub(crate) fn report(arena: &JsonTreeArena) -> Vec<DuplicateKey> {
+    let mut found = Vec::new();
+    if arena.repeated_keys.is_empty() {
+        return found;
+    }
+    if arena.is_fileset {
+        for (file, child) in object_entries(arena, arena.root_id) {
+            let mut walk = Walk {
+                arena,
+                file: Some(file),
+                found: &mut found,
+            };
+            walk.visit(child, &mut String::new());
+        }
+    } else {
+        let mut walk = Walk {
+            arena,
+            file: None,
+            found: &mut found,
+        };
+        walk.visit(arena.root_id, &mut String::new());
+    }
+    found
+}
+
+fn object_entries(
+    arena: &JsonTreeArena,
+    id: usize,
+) -> impl Iterator<Item = (&str, usize)> {
+    let node = &arena.nodes[id];
+    let keys = &arena.obj_keys
+        [node.obj_keys_start..node.obj_keys_start + node.obj_keys_len];
+    let children = &arena.children
+        [node.children_start..node.children_start + node.children_len];
+    keys.iter()
+        .map(String::as_str)
+        .zip(children.iter().copied())
+}
+
+struct Walk<'a> {
+    arena: &'a JsonTreeArena,
+    file: Option<&'a str>,
+    found: &'a mut Vec<DuplicateKey>,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, id: usize, path: &mut String) {
+        match self.arena.nodes[id].kind {
+            NodeKind::Object => self.visit_object(id, path),
+            NodeKind::Array => self.visit_array(id, path),
+            _ => {}
+        }
+    }
+
+    fn visit_object(&mut self, id: usize, path: &mut String) {
+        for repeated in self.arena.repeated_keys_of(id) {
+            let mut key_path = path.clone();
+            push_key(&mut key_path, &repeated.key);
+            self.found.push(DuplicateKey {
+                file: self.file.map(str::to_string),
+                path: key_path,
+                occurrences: repeated.occurrences,
+            });
+        }
+        for (key, child) in object_entries(self.arena, id) {
+            let len = path.len();
+            push_key(path, key);
+            self.visit(child, path);
+            path.truncate(len);
+        }
+    }
+
+    fn visit_array(&mut self, id: usize, path: &mut String) {
+        let node = &self.arena.nodes[id];
+        for i in 0..node.children_len {
+            let index = if node.arr_indices_len == 0 {
+                i
+            } else {
+                self.arena.arr_indices[node.arr_indices_start + i]
+            };
+            let len = path.len();
+            path.push_str(&format!("[{index}]"));
+            self.visit(self.arena.children[node.children_start + i], path);
+            path.truncate(len);
+        }
+    }
+}
+
+// `.name` for identifier-like keys, `["odd key"]` for everything else.
+fn push_key(path: &mut String, key: &str) {
+    let mut chars = key.chars();
+    let is_ident = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_ident {
+        path.push('.');
+        path.push_str(key);
+    } else {
+        path.push_str(&format!("[{}]", json_string(key)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(names: &[&str]) -> Vec<String> {
+        names.iter().map(|k| (*k).to_string()).collect()
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn keep_first_and_keep_last_pick_different_values() {
+        let names = keys(&["a", "b", "a", "a"]);
+        let first =
+            dedupe(DuplicateKeys::KeepFirst, names.clone(), vec![0, 1, 2, 3]);
+        assert_eq!(first.keys, keys(&["a", "b"]));
+        assert_eq!(first.children, vec![0, 1]);
+        assert_eq!(first.repeated, vec![("a".to_string(), 3)]);
+        let last = dedupe(DuplicateKeys::KeepLast, names, vec![0, 1, 2, 3]);
+        assert_eq!(last.keys, keys(&["a", "b"]));
+        assert_eq!(last.children, vec![3, 1]);
+    }
+
+    #[test]
+    fn paths_quote_keys_that_are_not_identifiers() {
+        let mut path = String::new();
+        push_key(&mut path, "users");
+        path.push_str("[3]");
+        push_key(&mut path, "first name");
+        assert_eq!(path, ".users[3][\"first name\"]");
+    }
+}
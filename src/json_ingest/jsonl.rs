@@ -0,0 +1,120 @@
+use serde::de::{DeserializeSeed, SeqAccess};
+
+use super::builder::JsonTreeBuilder;
+use super::tape::TapeParser;
+
+// Sequence access over newline-delimited JSON records. Each non-blank line
+// is parsed as an independent JSON value, so the array samplers see the
+// records exactly like the elements of a regular JSON array. Lines go
+// through the same tape parser as JSON documents, so numbers keep their
+// source text.
+pub(crate) struct JsonLines<'a> {
+    rest: &'a mut [u8],
+    line_no: usize,
+    parser: &'a mut TapeParser,
+    builder: &'a JsonTreeBuilder,
+}
+
+impl<'a> JsonLines<'a> {
+    pub(crate) fn new(
+        bytes: &'a mut [u8],
+        parser: &'a mut TapeParser,
+        builder: &'a JsonTreeBuilder,
+    ) -> Self {
+        Self {
+            rest: bytes,
+            line_no: 0,
+            parser,
+            builder,
+        }
+    }
+
+    // Split off the next line that contains anything besides whitespace.
+    fn next_record(&mut self) -> Option<&'a mut [u8]> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            let rest = std::mem::take(&mut self.rest);
+            let end = rest.iter().position(|&b| b == b'\n');
+            let (line, tail) = match end {
+                Some(i) => {
+                    let (line, tail) = rest.split_at_mut(i);
+                    (line, &mut tail[1..])
+                }
+                None => (rest, &mut [][..]),
+            };
+            self.rest = tail;
+            self.line_no += 1;
+            if !line.iter().all(u8::is_ascii_whitespace) {
+                return Some(line);
+            }
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for JsonLines<'_> {
+    type Error = simd_json::Error;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let Some(line) = self.next_record() else {
+            return Ok(None);
+        };
+        let line_no = self.line_no;
+        let with_line = |e: simd_json::Error| {
+            <simd_json::Error as serde::de::Error>::custom(format!(
+                "line {line_no}: {e}"
+            ))
+        };
+        self.parser
+            .push_value(self.builder, line, seed)
+            .map(Some)
+            .map_err(with_line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_blank_lines_and_counts_records() {
+        let mut bytes = b"{\"a\":1}\n\n  \r\n[2]\r\n3".to_vec();
+        let (mut parser, builder) =
+            (TapeParser::new(), JsonTreeBuilder::default());
+        let mut lines = JsonLines::new(&mut bytes, &mut parser, &builder);
+        let mut count = 0usize;
+        while lines
+            .next_element::<serde::de::IgnoredAny>()
+            .expect("valid record")
+            .is_some()
+        {
+            count += 1;
+        }
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn reports_line_number_of_bad_record() {
+        let mut bytes = b"{\"a\":1}\n{oops\n".to_vec();
+        let (mut parser, builder) =
+            (TapeParser::new(), JsonTreeBuilder::default());
+        let mut lines = JsonLines::new(&mut bytes, &mut parser, &builder);
+        assert!(
+            lines
+                .next_element::<serde::de::IgnoredAny>()
+                .unwrap()
+                .is_some()
+        );
+        let err = lines
+            .next_element::<serde::de::IgnoredAny>()
+            .expect_err("second line is invalid");
+        assert!(err.to_string().contains("line 2"), "got: {err}");
+    }
+}
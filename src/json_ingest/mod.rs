@@ -1,39 +1,366 @@
 mod builder;
+mod duplicates;
+mod jsonl;
+mod numbers;
+mod recover;
 mod samplers;
+mod stream;
+mod tape;
 use serde::de::DeserializeSeed;
 
 use crate::PriorityConfig;
+use crate::ingest::IngestOptions;
+use crate::mapped::MappedInput;
 use crate::utils::tree_arena::JsonTreeArena;
 use anyhow::Result;
-use builder::JsonTreeBuilder;
+use builder::{JsonTreeBuilder, Mark};
+pub use duplicates::DuplicateKey;
+pub(crate) use duplicates::report as duplicate_key_report;
+use jsonl::JsonLines;
+pub(crate) use numbers::{NUMBER_TOKEN, is_json_number};
+pub use recover::JsonRecovery;
+use stream::JsonStream;
+use tape::TapeParser;
 
 #[cfg(test)]
 pub fn build_json_tree_arena(
     input: &str,
     config: &PriorityConfig,
 ) -> Result<JsonTreeArena> {
-    build_json_tree_arena_from_bytes(input.as_bytes().to_vec(), config)
+    build_json_tree_arena_from_bytes(
+        input.as_bytes().to_vec(),
+        &IngestOptions::default(),
+        config,
+    )
 }
 
 pub fn build_json_tree_arena_from_bytes(
     mut bytes: Vec<u8>,
+    ingest: &IngestOptions,
     config: &PriorityConfig,
 ) -> Result<JsonTreeArena> {
-    let mut de = simd_json::Deserializer::from_slice(&mut bytes)?;
-    let builder = JsonTreeBuilder::new(
+    build_json_tree_arena_in_place(&mut bytes, ingest, config)
+}
+
+/// Like `build_json_tree_arena_from_bytes`, but hands `bytes` back as
+/// written when the parse fails.
+pub(crate) fn try_build_json_tree_arena(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &PriorityConfig,
+) -> std::result::Result<JsonTreeArena, Vec<u8>> {
+    parse_or_give_back(bytes, |text| {
+        build_json_tree_arena_in_place(text, ingest, config)
+    })
+}
+
+fn build_json_tree_arena_in_place(
+    text: &mut [u8],
+    ingest: &IngestOptions,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let builder = json_builder(ingest, config);
+    let root_id = push_json_document(&builder, &mut TapeParser::new(), text)?;
+    let mut arena = builder.finish();
+    arena.root_id = root_id;
+    Ok(arena)
+}
+
+// Parsing rewrites escaped strings in place, so only those are set aside
+// to undo a failed parse, rather than a copy of the whole input.
+fn parse_or_give_back(
+    mut bytes: Vec<u8>,
+    parse: impl FnOnce(&mut [u8]) -> Result<JsonTreeArena>,
+) -> std::result::Result<JsonTreeArena, Vec<u8>> {
+    let tails = tape::escaped_tails(&bytes);
+    parse(&mut bytes).map_err(|_| {
+        numbers::put_back(&mut bytes, &tails);
+        bytes
+    })
+}
+
+// JSON objects may repeat a key; the configured policy decides which
+// occurrence is kept.
+fn json_builder(
+    ingest: &IngestOptions,
+    config: &PriorityConfig,
+) -> JsonTreeBuilder {
+    let mut builder = JsonTreeBuilder::new(
         config.array_max_items,
         config.array_sampler.into(),
     );
-    let root_id: usize = {
-        let seed = builder.seed();
-        seed.deserialize(&mut de)?
+    builder.duplicate_keys = ingest.duplicate_keys;
+    builder
+}
+
+// A JSON document is one value, or several values back to back, which
+// become the elements of a root array (like multi-document YAML). The
+// stream is only tried once the strict single-value parse has failed.
+fn push_json_document(
+    builder: &JsonTreeBuilder,
+    parser: &mut TapeParser,
+    text: &mut [u8],
+) -> Result<usize> {
+    let mark = builder.mark();
+    let err = match parser.push_value(builder, text, builder.seed()) {
+        Ok(root_id) => return Ok(root_id),
+        Err(err) => err,
+    };
+    let mut copy = parser.take_copy();
+    let text = copy.as_deref_mut().unwrap_or(text);
+    push_json_stream(builder, parser, text, mark, err)
+}
+
+// simd_json parses `text` in place, so rather than keeping a copy of the
+// input for the stream, it rebuilds the first value, the only part the
+// failed parse can have rewritten.
+fn push_json_stream(
+    builder: &JsonTreeBuilder,
+    parser: &mut TapeParser,
+    text: &mut [u8],
+    mark: Mark,
+    err: simd_json::Error,
+) -> Result<usize> {
+    // Nodes of the failed attempt are no part of the stream.
+    builder.rollback(mark);
+    let Some(mut stream) = JsonStream::new(text, parser, builder) else {
+        return Err(err.into());
+    };
+    builder.push_sampled_array(&mut stream).map_err(|_| {
+        builder.rollback(mark);
+        err.into()
+    })
+}
+
+/// Like `build_json_tree_arena_from_bytes`, but parses a memory-mapped file
+/// in place from `start` (past a byte order mark). With `json5_on_comment`,
+/// input whose parse stops at a `//` or `/*` comment is read as JSON5
+/// instead, so it needs no scan for comments up front.
+pub fn build_json_tree_arena_from_mapped(
+    input: &mut MappedInput,
+    start: usize,
+    ingest: &IngestOptions,
+    config: &PriorityConfig,
+    json5_on_comment: bool,
+) -> Result<JsonTreeArena> {
+    let text = &mut input[start..];
+    let builder = json_builder(ingest, config);
+    let mut parser = TapeParser::new();
+    let mark = builder.mark();
+    let root_id = match parser.push_value(&builder, text, builder.seed()) {
+        Ok(root_id) => root_id,
+        Err(err) => {
+            let mut copy = parser.take_copy();
+            let text = copy.as_deref_mut().unwrap_or(text);
+            let commented = json5_on_comment
+                .then(|| commented_source(&parser, text, err.index()))
+                .flatten();
+            if let Some(source) = commented {
+                return crate::json5_ingest::build_json5_tree_arena_from_bytes(
+                    &source, config,
+                );
+            }
+            push_json_stream(&builder, &mut parser, text, mark, err)?
+        }
     };
     let mut arena = builder.finish();
     arena.root_id = root_id;
     Ok(arena)
 }
 
+// The input as written when the failed parse of `text` stopped at a
+// comment, at byte `at` of an atom outside strings.
+fn commented_source(
+    parser: &TapeParser,
+    text: &[u8],
+    at: usize,
+) -> Option<Vec<u8>> {
+    let rest = text.get(at..).filter(|rest| !rest.starts_with(b"\""))?;
+    let atom_len = rest
+        .iter()
+        .position(u8::is_ascii_whitespace)
+        .unwrap_or(rest.len());
+    if !rest[..atom_len]
+        .windows(2)
+        .any(|pair| matches!(pair, b"//" | b"/*"))
+    {
+        return None;
+    }
+    let mut source = parser.restore(&text[..at])?;
+    source.extend_from_slice(rest);
+    Some(source)
+}
+
+/// Like `build_json_tree_arena_from_bytes`, but input that is cut off or
+/// breaks halfway through still yields the values parsed before the
+/// failure. Containers left open are closed and flagged as truncated, and
+/// the failure point is returned alongside the arena.
+pub fn build_json_tree_arena_recovering(
+    bytes: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &PriorityConfig,
+) -> Result<(JsonTreeArena, Option<JsonRecovery>)> {
+    let Some(repaired) = recover::repair(&bytes) else {
+        let arena = build_json_tree_arena_from_bytes(bytes, ingest, config)?;
+        return Ok((arena, None));
+    };
+    let mut arena =
+        build_json_tree_arena_from_bytes(repaired.bytes, ingest, config)?;
+    recover::mark_truncated(&mut arena, repaired.closed);
+    Ok((arena, Some(repaired.recovery)))
+}
+
 pub fn build_json_tree_arena_from_many(
+    mut inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let builder = json_builder(ingest, config);
+    // One parser for all documents, so simd-json's buffers are reused.
+    let mut parser = TapeParser::new();
+    let mut child_ids: Vec<usize> = Vec::with_capacity(inputs.len());
+    let mut keys: Vec<String> = Vec::with_capacity(inputs.len());
+    for (key, mut bytes) in inputs.drain(..) {
+        let root_id = push_json_document(&builder, &mut parser, &mut bytes)?;
+        child_ids.push(root_id);
+        keys.push(key);
+    }
+    let root_id = builder.push_object_root(keys, child_ids);
+    let mut arena = builder.finish();
+    arena.root_id = root_id;
+    arena.is_fileset = true;
+    Ok(arena)
+}
+
+/// Build an arena from any serde `Deserializer`. Lets other structured
+/// formats reuse the JSON builder (and its array samplers) once they have
+/// been parsed into a self-describing value.
+pub(crate) fn build_tree_arena_from_deserializer<'de, D>(
+    de: D,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena>
+where
+    D: serde::Deserializer<'de>,
+    D::Error: std::error::Error + Send + Sync + 'static,
+{
+    let builder = JsonTreeBuilder::new(
+        config.array_max_items,
+        config.array_sampler.into(),
+    );
+    let root_id = builder.seed().deserialize(de)?;
+    let mut arena = builder.finish();
+    arena.root_id = root_id;
+    Ok(arena)
+}
+
+pub(crate) fn build_tree_arena_from_many_deserializers<'de, D>(
+    inputs: Vec<(String, D)>,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena>
+where
+    D: serde::Deserializer<'de>,
+    D::Error: std::error::Error + Send + Sync + 'static,
+{
+    let builder = JsonTreeBuilder::new(
+        config.array_max_items,
+        config.array_sampler.into(),
+    );
+    let mut child_ids: Vec<usize> = Vec::with_capacity(inputs.len());
+    let mut keys: Vec<String> = Vec::with_capacity(inputs.len());
+    for (key, de) in inputs {
+        child_ids.push(builder.seed().deserialize(de)?);
+        keys.push(key);
+    }
+    let root_id = builder.push_object_root(keys, child_ids);
+    let mut arena = builder.finish();
+    arena.root_id = root_id;
+    arena.is_fileset = true;
+    Ok(arena)
+}
+
+/// Build an arena whose root is an array streamed from `seq`, sampled
+/// like any JSON array. Used by record-oriented formats.
+pub(crate) fn build_tree_arena_from_seq<'de, A>(
+    seq: &mut A,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena>
+where
+    A: serde::de::SeqAccess<'de>,
+    A::Error: std::error::Error + Send + Sync + 'static,
+{
+    let builder = JsonTreeBuilder::new(
+        config.array_max_items,
+        config.array_sampler.into(),
+    );
+    let root_id = builder.push_sampled_array(seq)?;
+    let mut arena = builder.finish();
+    arena.root_id = root_id;
+    Ok(arena)
+}
+
+pub(crate) fn build_tree_arena_from_many_seqs<'de, A>(
+    inputs: Vec<(String, A)>,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena>
+where
+    A: serde::de::SeqAccess<'de>,
+    A::Error: std::error::Error + Send + Sync + 'static,
+{
+    let builder = JsonTreeBuilder::new(
+        config.array_max_items,
+        config.array_sampler.into(),
+    );
+    let mut child_ids: Vec<usize> = Vec::with_capacity(inputs.len());
+    let mut keys: Vec<String> = Vec::with_capacity(inputs.len());
+    for (key, mut seq) in inputs {
+        child_ids.push(builder.push_sampled_array(&mut seq)?);
+        keys.push(key);
+    }
+    let root_id = builder.push_object_root(keys, child_ids);
+    let mut arena = builder.finish();
+    arena.root_id = root_id;
+    arena.is_fileset = true;
+    Ok(arena)
+}
+
+/// Build an arena from newline-delimited JSON (NDJSON / JSON Lines).
+/// Every non-blank line becomes an element of a root array, which goes
+/// through the configured array sampler like any other JSON array.
+pub fn build_jsonl_tree_arena_from_bytes(
+    mut bytes: Vec<u8>,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    build_jsonl_tree_arena_in_place(&mut bytes, config)
+}
+
+/// Like `build_jsonl_tree_arena_from_bytes`, but hands `bytes` back as
+/// written when the parse fails.
+pub(crate) fn try_build_jsonl_tree_arena(
+    bytes: Vec<u8>,
+    config: &PriorityConfig,
+) -> std::result::Result<JsonTreeArena, Vec<u8>> {
+    parse_or_give_back(bytes, |text| {
+        build_jsonl_tree_arena_in_place(text, config)
+    })
+}
+
+fn build_jsonl_tree_arena_in_place(
+    text: &mut [u8],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let builder = JsonTreeBuilder::new(
+        config.array_max_items,
+        config.array_sampler.into(),
+    );
+    let mut parser = TapeParser::new();
+    let mut lines = JsonLines::new(text, &mut parser, &builder);
+    let root_id = builder.push_sampled_array(&mut lines)?;
+    let mut arena = builder.finish();
+    arena.root_id = root_id;
+    Ok(arena)
+}
+
+pub fn build_jsonl_tree_arena_from_many(
     mut inputs: Vec<(String, Vec<u8>)>,
     config: &PriorityConfig,
 ) -> Result<JsonTreeArena> {
@@ -41,12 +368,12 @@ pub fn build_json_tree_arena_from_many(
         config.array_max_items,
         config.array_sampler.into(),
     );
+    let mut parser = TapeParser::new();
     let mut child_ids: Vec<usize> = Vec::with_capacity(inputs.len());
     let mut keys: Vec<String> = Vec::with_capacity(inputs.len());
     for (key, mut bytes) in inputs.drain(..) {
-        let mut de = simd_json::Deserializer::from_slice(&mut bytes)?;
-        let seed = builder.seed();
-        let root_id: usize = seed.deserialize(&mut de)?;
+        let mut lines = JsonLines::new(&mut bytes, &mut parser, &builder);
+        let root_id = builder.push_sampled_array(&mut lines)?;
         child_ids.push(root_id);
         keys.push(key);
     }
@@ -68,15 +395,114 @@ mod tests {
             ("b.json".to_string(), b"[]".to_vec()),
         ];
         let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
-        let arena = build_json_tree_arena_from_many(inputs, &cfg).unwrap();
+        let arena = build_json_tree_arena_from_many(
+            inputs,
+            &IngestOptions::default(),
+            &cfg,
+        )
+        .unwrap();
         assert!(arena.is_fileset, "expected fileset marker true");
     }
 
     #[test]
     fn fileset_marker_false_for_single_input() {
         let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
-        let arena =
-            build_json_tree_arena_from_bytes(b"{}".to_vec(), &cfg).unwrap();
+        let arena = build_json_tree_arena_from_bytes(
+            b"{}".to_vec(),
+            &IngestOptions::default(),
+            &cfg,
+        )
+        .unwrap();
         assert!(!arena.is_fileset, "expected fileset marker false");
     }
+
+    #[test]
+    fn mapped_streams_restore_strings_unescaped_in_place() {
+        use std::io::Write;
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(b"\xEF\xBB\xBF{\"s\":\"a\\\"b\"}\n{\"s\":\"c\\nd\"}\n")
+            .unwrap();
+        let mut mapped = MappedInput::new(&file).unwrap();
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena = build_json_tree_arena_from_mapped(
+            &mut mapped,
+            3,
+            &IngestOptions::default(),
+            &cfg,
+            false,
+        )
+        .unwrap();
+        let strings: Vec<_> = arena
+            .nodes
+            .iter()
+            .filter_map(|n| n.string_value.as_deref())
+            .collect();
+        assert_eq!(strings, ["a\"b", "c\nd"]);
+    }
+
+    #[test]
+    fn mapped_json_with_comments_is_read_as_json5() {
+        use std::io::Write;
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(b"{\"s\": \"a\\\"b\", // note\n\"n\": [1, /* x */ 2]}")
+            .unwrap();
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let mut strict = MappedInput::new(&file).unwrap();
+        assert!(
+            build_json_tree_arena_from_mapped(
+                &mut strict,
+                0,
+                &IngestOptions::default(),
+                &cfg,
+                false,
+            )
+            .is_err()
+        );
+        let mut lenient = MappedInput::new(&file).unwrap();
+        let arena = build_json_tree_arena_from_mapped(
+            &mut lenient,
+            0,
+            &IngestOptions::default(),
+            &cfg,
+            true,
+        )
+        .unwrap();
+        let strings: Vec<_> = arena
+            .nodes
+            .iter()
+            .filter_map(|n| n.string_value.as_deref())
+            .collect();
+        assert_eq!(strings, ["a\"b"]);
+        assert_eq!(arena.nodes.len(), 5);
+    }
+
+    #[test]
+    fn jsonl_records_become_root_array() {
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena = build_jsonl_tree_arena_from_bytes(
+            b"{\"a\":1}\n{\"a\":2}\n\n[3]\n".to_vec(),
+            &cfg,
+        )
+        .unwrap();
+        let root = &arena.nodes[arena.root_id];
+        assert_eq!(root.kind, crate::order::NodeKind::Array);
+        assert_eq!(root.array_len, Some(3));
+        assert_eq!(root.children_len, 3);
+    }
+
+    #[test]
+    fn jsonl_root_respects_array_cap() {
+        let input = (0..100)
+            .map(|i| format!("{{\"i\":{i}}}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut cfg = PriorityConfig::new(usize::MAX, 5);
+        cfg.array_sampler = crate::ArraySamplerStrategy::Head;
+        let arena =
+            build_jsonl_tree_arena_from_bytes(input.into_bytes(), &cfg)
+                .unwrap();
+        let root = &arena.nodes[arena.root_id];
+        assert_eq!(root.array_len, Some(100));
+        assert_eq!(root.children_len, 5);
+    }
 }
@@ -0,0 +1,167 @@
+// Numbers arrive from simd_json as `f64`/`i64`/`u64`, which cannot hold
+// every JSON number: big integers, long decimals and exponents come back
+// rounded or reformatted, and `-0` as `0`. The tape reader therefore hands
+// floats and zeros over by their position in the tape, and their tokens
+// are sliced from the input once the tape is released. Numbers beyond the
+// range of `f64` (`1e400`), which simd_json rejects, are zeroed out for a
+// second parse and get their text back the same way.
+
+use super::builder::JsonTreeBuilder;
+
+/// Map key under which a number is handed to `visit_map` with its source
+/// text as the value.
+pub(crate) const NUMBER_TOKEN: &str = "$headson::private::Number";
+
+/// Map key under which the tape reader hands a number to `visit_map` with
+/// its tape position as the value, for `restore_tokens` to fill in.
+pub(crate) const NUMBER_AT: &str = "$headson::private::NumberAt";
+
+/// Give the numbers `builder` recorded from the last tape their source text.
+/// `text` is the input that tape was built from and `indexes` its
+/// structural indexes: every value starts at one of them, in tape order.
+pub(crate) fn restore_tokens(
+    builder: &JsonTreeBuilder,
+    text: &[u8],
+    indexes: &[u32],
+) {
+    let mut starts = indexes
+        .iter()
+        .map(|&i| i as usize)
+        .filter(|&i| text.get(i).is_some_and(|&c| is_value_start(c)));
+    let mut next = 0;
+    for (at, id) in builder.take_number_sources() {
+        let Some(start) = starts.nth(at - next) else {
+            return;
+        };
+        next = at + 1;
+        let lexeme = &text[start..number_end(text, start)];
+        builder.set_token(id, String::from_utf8_lossy(lexeme).into_owned());
+    }
+}
+
+// Structural characters that close or separate values rather than start
+// one.
+fn is_value_start(c: u8) -> bool {
+    !matches!(c, b'}' | b']' | b',' | b':')
+}
+
+fn number_end(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&c| !is_number_byte(c))
+        .map_or(bytes.len(), |n| start + n)
+}
+
+fn is_number_byte(c: u8) -> bool {
+    c.is_ascii_digit() || matches!(c, b'-' | b'+' | b'.' | b'e' | b'E')
+}
+
+/// Whether `token` is a number as JSON writes it:
+/// `-?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?`.
+pub(crate) fn is_json_number(token: &[u8]) -> bool {
+    let token = token.strip_prefix(b"-").unwrap_or(token);
+    let int = digits(token);
+    if int == 0 || (int > 1 && token[0] == b'0') {
+        return false;
+    }
+    let rest = &token[int..];
+    let rest = match rest.strip_prefix(b".") {
+        Some(fraction) if digits(fraction) > 0 => {
+            &fraction[digits(fraction)..]
+        }
+        Some(_) => return false,
+        None => rest,
+    };
+    match rest {
+        [] => true,
+        [b'e' | b'E', exponent @ ..] => {
+            let exponent = exponent
+                .strip_prefix(b"+")
+                .or_else(|| exponent.strip_prefix(b"-"))
+                .unwrap_or(exponent);
+            !exponent.is_empty() && digits(exponent) == exponent.len()
+        }
+        _ => false,
+    }
+}
+
+fn digits(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|c| c.is_ascii_digit()).count()
+}
+
+/// Whether the number starting at byte `at` of `text` is valid JSON that
+/// does not fit an `f64`.
+pub(crate) fn out_of_range_at(text: &[u8], at: usize) -> bool {
+    text.get(at..)
+        .is_some_and(|rest| out_of_range(&rest[..number_end(rest, 0)]))
+}
+
+fn out_of_range(lexeme: &[u8]) -> bool {
+    is_json_number(lexeme)
+        && std::str::from_utf8(lexeme)
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .is_some_and(f64::is_infinite)
+}
+
+/// Overwrite every number of `text` that is out of range with `0` and
+/// spaces, for simd_json to accept. Returns where each one was and its
+/// text, for `put_back`. `text` must start outside any string.
+pub(crate) fn zero_out_of_range(text: &mut [u8]) -> Vec<(usize, Vec<u8>)> {
+    let mut zeroed = Vec::new();
+    let mut strings = StringScan::default();
+    let mut i = 0;
+    while let Some(&c) = text.get(i) {
+        if strings.inside(c) || !(c == b'-' || c.is_ascii_digit()) {
+            i += 1;
+            continue;
+        }
+        let end = number_end(text, i);
+        zeroed.extend(zero_if_out_of_range(text, i, end));
+        i = end;
+    }
+    zeroed
+}
+
+fn zero_if_out_of_range(
+    text: &mut [u8],
+    start: usize,
+    end: usize,
+) -> Option<(usize, Vec<u8>)> {
+    let lexeme = &mut text[start..end];
+    if !out_of_range(lexeme) {
+        return None;
+    }
+    let original = lexeme.to_vec();
+    lexeme.fill(b' ');
+    lexeme[0] = b'0';
+    Some((start, original))
+}
+
+/// Write the numbers `zero_out_of_range` replaced, or the strings that
+/// `tape::escaped_tails` kept, back into `text`.
+pub(crate) fn put_back(text: &mut [u8], zeroed: &[(usize, Vec<u8>)]) {
+    for (at, lexeme) in zeroed {
+        text[*at..*at + lexeme.len()].copy_from_slice(lexeme);
+    }
+}
+
+// Tells the bytes of double-quoted strings, quotes included, from the rest
+// when fed one byte at a time.
+#[derive(Default)]
+struct StringScan {
+    open: bool,
+    escaped: bool,
+}
+
+impl StringScan {
+    fn inside(&mut self, c: u8) -> bool {
+        if self.open {
+            self.open = self.escaped || c != b'"';
+            self.escaped = !self.escaped && c == b'\\';
+            return true;
+        }
+        self.open = c == b'"';
+        self.open
+    }
+}
@@ -0,0 +1,218 @@
+use serde::de::IgnoredAny;
+
+use crate::order::NodeKind;
+use crate::utils::tree_arena::JsonTreeArena;
+
+/// Where and why a JSON input stopped parsing when it was previewed in
+/// recovery mode. Everything before `offset` that formed complete values
+/// was kept.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonRecovery {
+    /// Byte offset at which the parser gave up.
+    pub offset: usize,
+    /// The parser's description of the failure.
+    pub error: String,
+}
+
+// The input cut back to its last complete value, with the containers that
+// were still open at that point closed again.
+pub(crate) struct Repaired {
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) closed: usize,
+    pub(crate) recovery: JsonRecovery,
+}
+
+/// Repair `bytes` when they do not parse as JSON. Returns `None` for valid
+/// input and for input without any complete value to keep.
+pub(crate) fn repair(bytes: &[u8]) -> Option<Repaired> {
+    // Values back to back are a valid stream, not an error.
+    let err = serde_json::Deserializer::from_slice(bytes)
+        .into_iter::<IgnoredAny>()
+        .find_map(Result::err)?;
+    let offset = error_offset(bytes, &err);
+    let mut scanner = Scanner::default();
+    for (i, &c) in bytes[..offset].iter().enumerate() {
+        scanner.step(i, c);
+    }
+    let cut = scanner.cut?;
+    let mut repaired = bytes[..cut].to_vec();
+    repaired.extend(
+        scanner
+            .cut_open
+            .iter()
+            .rev()
+            .map(|&open| if open == b'{' { b'}' } else { b']' }),
+    );
+    // A stream that broke off is read into a root array of its values,
+    // which is itself cut short.
+    let values = scanner.roots + usize::from(!scanner.cut_open.is_empty());
+    Some(Repaired {
+        bytes: repaired,
+        closed: scanner.cut_open.len() + usize::from(values > 1),
+        recovery: JsonRecovery {
+            offset,
+            error: err.to_string(),
+        },
+    })
+}
+
+// serde_json reports one-based line/column positions; turn them back into
+// a byte offset. Errors at end of input point past the last byte.
+fn error_offset(bytes: &[u8], err: &serde_json::Error) -> usize {
+    if err.is_eof() {
+        return bytes.len();
+    }
+    let line_start = bytes
+        .split_inclusive(|&b| b == b'\n')
+        .take(err.line().saturating_sub(1))
+        .map(<[u8]>::len)
+        .sum::<usize>();
+    (line_start + err.column().saturating_sub(1)).min(bytes.len())
+}
+
+// Walks a prefix of valid JSON and remembers the last position after
+// which the text can be closed into a complete document: right after an
+// opening bracket or after a complete array element / object member.
+#[derive(Default)]
+struct Scanner {
+    open: Vec<u8>,
+    expect_key: bool,
+    // Inside a string; `Some(true)` for object keys.
+    string: Option<bool>,
+    escaped: bool,
+    in_scalar: bool,
+    // Top-level values completed so far.
+    roots: usize,
+    cut: Option<usize>,
+    cut_open: Vec<u8>,
+}
+
+impl Scanner {
+    fn step(&mut self, i: usize, c: u8) {
+        match self.string {
+            Some(is_key) => self.step_string(i, c, is_key),
+            None => self.step_structure(i, c),
+        }
+    }
+
+    fn step_string(&mut self, i: usize, c: u8, is_key: bool) {
+        if self.escaped {
+            self.escaped = false;
+        } else if c == b'\\' {
+            self.escaped = true;
+        } else if c == b'"' {
+            self.string = None;
+            if !is_key {
+                self.mark(i + 1);
+            }
+        }
+    }
+
+    fn step_structure(&mut self, i: usize, c: u8) {
+        match c {
+            b'"' => {
+                self.end_scalar(i);
+                let in_object = self.open.last() == Some(&b'{');
+                self.string = Some(in_object && self.expect_key);
+                self.expect_key = false;
+            }
+            b'{' | b'[' => {
+                self.open.push(c);
+                self.expect_key = c == b'{';
+                self.mark(i + 1);
+            }
+            b'}' | b']' => {
+                self.end_scalar(i);
+                self.open.pop();
+                self.expect_key = false;
+                self.mark(i + 1);
+            }
+            b',' => {
+                self.end_scalar(i);
+                self.expect_key = self.open.last() == Some(&b'{');
+            }
+            b':' | b' ' | b'\t' | b'\n' | b'\r' => self.end_scalar(i),
+            _ => self.in_scalar = true,
+        }
+    }
+
+    // Numbers and literals are only known to be complete once a delimiter
+    // follows; one that runs into the failure point is dropped.
+    fn end_scalar(&mut self, i: usize) {
+        if std::mem::take(&mut self.in_scalar) {
+            self.mark(i);
+        }
+    }
+
+    fn mark(&mut self, pos: usize) {
+        if self.open.is_empty() {
+            self.roots += 1;
+        }
+        self.cut = Some(pos);
+        self.cut_open.clone_from(&self.open);
+    }
+}
+
+/// Flag the `closed` containers that recovery had to close: the root and,
+/// below it, each last child in source order. Stops early when the array
+/// sampler dropped the element the input broke off in.
+pub(crate) fn mark_truncated(arena: &mut JsonTreeArena, closed: usize) {
+    let mut id = arena.root_id;
+    for _ in 0..closed {
+        arena.nodes[id].truncated = true;
+        match last_source_child(arena, id) {
+            Some(child) => id = child,
+            None => break,
+        }
+    }
+}
+
+fn last_source_child(arena: &JsonTreeArena, id: usize) -> Option<usize> {
+    let node = &arena.nodes[id];
+    let last = node.children_len.checked_sub(1)?;
+    if node.kind == NodeKind::Array {
+        let index = if node.arr_indices_len == 0 {
+            last
+        } else {
+            arena.arr_indices[node.arr_indices_start + last]
+        };
+        if node.array_len != Some(index + 1) {
+            return None;
+        }
+    }
+    Some(arena.children[node.children_start + last])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repaired(input: &str) -> Option<(String, usize)> {
+        repair(input.as_bytes())
+            .map(|r| (String::from_utf8(r.bytes).unwrap(), r.recovery.offset))
+    }
+
+    #[test]
+    fn closes_open_containers_after_last_complete_value() {
+        let input = r#"{"a": [1, 2, {"b": "x"}, 3"#;
+        assert_eq!(
+            repaired(input),
+            Some((r#"{"a": [1, 2, {"b": "x"}]}"#.to_string(), input.len()))
+        );
+        assert_eq!(
+            repaired(r#"[{"k": "cut off"#),
+            Some(("[{}]".to_string(), 15))
+        );
+    }
+
+    #[test]
+    fn stops_at_syntax_errors_and_keeps_valid_input() {
+        assert_eq!(
+            repaired("[1, 2, oops, 4]"),
+            Some(("[1, 2]".to_string(), 7))
+        );
+        assert_eq!(repaired(r#"{"a": 1}"#), None);
+        assert_eq!(repaired(r#"{"a": 1} {"a": 2}"#), None);
+        assert_eq!(repaired("tru"), None);
+    }
+}
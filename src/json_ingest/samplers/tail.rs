@@ -88,9 +88,12 @@ mod tests {
         let input = b"[0,1,2,3,4,5,6,7,8,9]".to_vec();
         let mut cfg = PriorityConfig::new(usize::MAX, 5);
         cfg.array_sampler = crate::ArraySamplerStrategy::Tail;
-        let arena =
-            crate::json_ingest::build_json_tree_arena_from_bytes(input, &cfg)
-                .expect("arena");
+        let arena = crate::json_ingest::build_json_tree_arena_from_bytes(
+            input,
+            &crate::IngestOptions::default(),
+            &cfg,
+        )
+        .expect("arena");
         let root = &arena.nodes[arena.root_id];
         assert_eq!(root.children_len, 5, "kept 5");
         let mut orig_indices = Vec::new();
@@ -0,0 +1,130 @@
+use serde::de::{DeserializeSeed, SeqAccess};
+
+use super::builder::JsonTreeBuilder;
+use super::tape::TapeParser;
+
+// Sequence access over JSON values written back to back (`{..}{..}`) or
+// separated by whitespace, as produced by `jq -c`, Docker and streaming
+// HTTP APIs. Each value becomes one element of the root array. The values
+// are told apart by the structural indexes of the failed parse of the
+// whole input and then parsed one at a time.
+pub(crate) struct JsonStream<'a> {
+    text: &'a mut [u8],
+    // The first value as written; the failed parse may have unescaped its
+    // strings in place.
+    first: Vec<u8>,
+    // Where each value starts, then the end of the input.
+    bounds: Vec<usize>,
+    next: usize,
+    parser: &'a mut TapeParser,
+    builder: &'a JsonTreeBuilder,
+}
+
+impl<'a> JsonStream<'a> {
+    /// `parser` must have failed to parse `text` as one value last. None
+    /// unless `text` holds at least two values and the first one parsed.
+    pub(crate) fn new(
+        text: &'a mut [u8],
+        parser: &'a mut TapeParser,
+        builder: &'a JsonTreeBuilder,
+    ) -> Option<Self> {
+        let indexes = parser.structural_indexes();
+        let mut bounds = value_starts(text, indexes);
+        let first_end = *bounds.get(1)?;
+        let first = parser.restore(&text[..first_end])?;
+        bounds.push(text.len());
+        Some(Self {
+            text,
+            first,
+            bounds,
+            next: 0,
+            parser,
+            builder,
+        })
+    }
+}
+
+// A value starts at every structural character outside all brackets.
+// Anything there that cannot start a value fails once its piece is parsed.
+fn value_starts(text: &[u8], indexes: &[u32]) -> Vec<usize> {
+    let mut depth = 0usize;
+    let mut starts = Vec::new();
+    for i in indexes.iter().map(|&i| i as usize) {
+        if depth == 0 {
+            starts.push(i);
+        }
+        match text.get(i) {
+            Some(b'{' | b'[') => depth += 1,
+            Some(b'}' | b']') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    starts
+}
+
+impl<'de> SeqAccess<'de> for JsonStream<'_> {
+    type Error = simd_json::Error;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let (Some(&start), Some(&end)) =
+            (self.bounds.get(self.next), self.bounds.get(self.next + 1))
+        else {
+            return Ok(None);
+        };
+        let value = if self.next == 0 {
+            &mut self.first[..]
+        } else {
+            &mut self.text[start..end]
+        };
+        self.next += 1;
+        self.parser.push_value(self.builder, value, seed).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(bytes: &[u8]) -> Option<Result<usize, simd_json::Error>> {
+        let builder = JsonTreeBuilder::default();
+        let mut parser = TapeParser::new();
+        let mut text = bytes.to_vec();
+        let seed = std::marker::PhantomData::<serde::de::IgnoredAny>;
+        parser.push_value(&builder, &mut text, seed).err()?;
+        let mut stream = JsonStream::new(&mut text, &mut parser, &builder)?;
+        let mut n = 0usize;
+        loop {
+            match stream.next_element::<serde::de::IgnoredAny>() {
+                Ok(Some(_)) => n += 1,
+                Ok(None) => return Some(Ok(n)),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+
+    #[test]
+    fn splits_adjacent_and_whitespace_separated_values() {
+        let n = count(b"{\"a\":1}{\"a\":2}\n[3] 4 \"x\"\n").unwrap();
+        assert_eq!(n.unwrap(), 5);
+        assert!(count(b"  ").is_none());
+        assert!(count(b"{} ]").unwrap().is_err());
+    }
+
+    #[test]
+    fn first_value_is_restored_after_failed_parse() {
+        let mut text = br#"{"a\"b": ["\u00e9\n", "x"]} "y\t""#.to_vec();
+        let mut parser = TapeParser::new();
+        let builder = JsonTreeBuilder::default();
+        let seed = std::marker::PhantomData::<serde::de::IgnoredAny>;
+        assert!(parser.push_value(&builder, &mut text, seed).is_err());
+        let stream = JsonStream::new(&mut text, &mut parser, &builder);
+        let first = stream.expect("two values").first;
+        assert_eq!(first, r#"{"a\"b": ["é\n", "x"]} "#.as_bytes());
+    }
+}
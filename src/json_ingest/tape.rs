@@ -0,0 +1,377 @@
+use serde::de::value::MapDeserializer;
+use serde::de::{
+    DeserializeSeed, Deserializer, Error as _, MapAccess, SeqAccess, Visitor,
+};
+use simd_json::{Buffers, Node, StaticNode, Tape};
+
+use super::builder::JsonTreeBuilder;
+use super::numbers::{self, NUMBER_AT};
+use crate::utils::json::json_string;
+
+/// simd-json's scratch buffers and tape, kept from one parse to the next.
+pub(crate) struct TapeParser {
+    buffers: Buffers,
+    tape: Tape<'static>,
+    // The strings, keys included, that the last failed parse had read
+    // before it stopped, in input order: what unescaping left of them.
+    unescaped: Vec<String>,
+    // The input as written, when the last failed parse was of a copy of
+    // it made for out-of-range numbers rather than of the given text.
+    copy: Option<Vec<u8>>,
+}
+
+// The outcome of one pass of simd-json and the tape reader. The seed comes
+// back when simd-json rejects the input before the reader starts.
+enum Parse<V, T> {
+    Read(Result<V, simd_json::Error>),
+    Rejected(simd_json::Error, T),
+}
+
+impl TapeParser {
+    pub(crate) fn new() -> Self {
+        Self {
+            buffers: Buffers::default(),
+            tape: Tape(Vec::new()),
+            unescaped: Vec::new(),
+            copy: None,
+        }
+    }
+
+    /// Parse `text` as one JSON value and build it with `seed`, numbers
+    /// with their source text. simd-json unescapes strings in place, so
+    /// `text` may be rewritten even when the parse fails.
+    pub(crate) fn push_value<'de, T>(
+        &mut self,
+        builder: &JsonTreeBuilder,
+        text: &mut [u8],
+        seed: T,
+    ) -> Result<T::Value, simd_json::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.copy = None;
+        let value = match self.parse(text, seed) {
+            Parse::Read(value) => value,
+            Parse::Rejected(err, seed)
+                if numbers::out_of_range_at(text, err.index()) =>
+            {
+                return self.push_out_of_range(builder, text, err, seed);
+            }
+            Parse::Rejected(err, _) => Err(err),
+        };
+        numbers::restore_tokens(builder, text, self.structural_indexes());
+        value
+    }
+
+    fn parse<'de, T>(&mut self, text: &mut [u8], seed: T) -> Parse<T::Value, T>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let mut tape =
+            std::mem::replace(&mut self.tape, Tape(Vec::new())).reset();
+        let parsed =
+            match simd_json::fill_tape(text, &mut self.buffers, &mut tape) {
+                Ok(()) => Parse::Read(
+                    seed.deserialize(&mut TapeReader::new(&tape.0)),
+                ),
+                Err(err) => Parse::Rejected(err, seed),
+            };
+        if !matches!(parsed, Parse::Read(Ok(_))) {
+            self.unescaped = strings(&tape);
+        }
+        self.tape = tape.reset();
+        parsed
+    }
+
+    // simd-json rejects numbers beyond the range of `f64` (`1e400`) even
+    // though they are valid JSON. Parse the input again as written, with
+    // each of those numbers zeroed out, then give them their text back
+    // for `restore_tokens`. When that parse fails as well, its copy is
+    // kept for `take_copy`.
+    fn push_out_of_range<'de, T>(
+        &mut self,
+        builder: &JsonTreeBuilder,
+        text: &[u8],
+        err: simd_json::Error,
+        seed: T,
+    ) -> Result<T::Value, simd_json::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let at = err.index();
+        let Some(mut source) = self.restore(&text[..at]) else {
+            return Err(err);
+        };
+        let start = source.len();
+        source.extend_from_slice(&text[at..]);
+        let mut zeroed = numbers::zero_out_of_range(&mut source[start..]);
+        zeroed.iter_mut().for_each(|(i, _)| *i += start);
+        let value = match self.parse(&mut source, seed) {
+            Parse::Read(value) => value,
+            Parse::Rejected(copy_err, _) => Err(copy_err),
+        };
+        numbers::put_back(&mut source, &zeroed);
+        numbers::restore_tokens(builder, &source, self.structural_indexes());
+        if value.is_err() {
+            self.copy = Some(source);
+        }
+        value
+    }
+
+    /// The copy of the input that the last failed `push_value` parsed in
+    /// the end, if not the text it was given. Its error, structural
+    /// indexes and strings all refer to the copy.
+    pub(crate) fn take_copy(&mut self) -> Option<Vec<u8>> {
+        self.copy.take()
+    }
+
+    /// Offsets of the structural characters found by the last parse.
+    pub(crate) fn structural_indexes(&self) -> &[u32] {
+        self.buffers.structural_indexes()
+    }
+
+    /// `text` as written before the last failed parse unescaped its strings
+    /// in place, copying everything between strings as it stands. `text`
+    /// must end before the string the parse stopped in, if any; None when
+    /// it holds more strings than the parse read.
+    pub(crate) fn restore(&self, text: &[u8]) -> Option<Vec<u8>> {
+        let mut out = Vec::with_capacity(text.len());
+        let mut strings = self.unescaped.iter();
+        let mut copied = 0;
+        let mut indexes = self
+            .structural_indexes()
+            .iter()
+            .map(|&i| i as usize)
+            .take_while(|&i| i < text.len())
+            .peekable();
+        while let Some(i) = indexes.next() {
+            if text[i] == b'"' {
+                out.extend_from_slice(&text[copied..i]);
+                out.extend_from_slice(json_string(strings.next()?).as_bytes());
+                // The rest of the string up to the next structural
+                // character is what unescaping left behind, and whitespace.
+                copied = indexes.peek().copied().unwrap_or(text.len());
+            }
+        }
+        out.extend_from_slice(&text[copied..]);
+        Some(out)
+    }
+}
+
+/// The parts of `text` that simd-json may rewrite when it unescapes strings
+/// in place: each string from its first backslash up to its closing quote.
+/// `numbers::put_back` writes them back after a failed parse.
+pub(crate) fn escaped_tails(text: &[u8]) -> Vec<(usize, Vec<u8>)> {
+    let mut tails = Vec::new();
+    let mut from = 0;
+    while let Some(i) = text[from..].iter().position(|&c| c == b'\\') {
+        let start = from + i;
+        let end = closing_quote(text, start).unwrap_or(text.len());
+        tails.push((start, text[start..end].to_vec()));
+        from = end;
+    }
+    tails
+}
+
+// The first quote from `at` on that no backslash escapes.
+fn closing_quote(text: &[u8], mut at: usize) -> Option<usize> {
+    while at < text.len() {
+        match text[at] {
+            b'\\' => at += 2,
+            b'"' => return Some(at),
+            _ => at += 1,
+        }
+    }
+    None
+}
+
+fn strings(tape: &Tape<'_>) -> Vec<String> {
+    tape.0
+        .iter()
+        .filter_map(|node| match node {
+            Node::String(s) => Some((*s).to_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+// Hands the nodes of a tape to serde visitors. Unlike simd-json's own
+// deserializer it passes floats and zeros over by their tape position (see
+// `numbers`), and skips ignored containers in one step.
+struct TapeReader<'t, 'i> {
+    nodes: &'t [Node<'i>],
+    at: usize,
+}
+
+impl<'t, 'i> TapeReader<'t, 'i> {
+    fn new(nodes: &'t [Node<'i>]) -> Self {
+        Self { nodes, at: 0 }
+    }
+}
+
+impl<'de> Deserializer<'de> for &mut TapeReader<'_, '_> {
+    type Error = simd_json::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let at = self.at;
+        let Some(&node) = self.nodes.get(at) else {
+            return Err(simd_json::Error::custom("unexpected end of tape"));
+        };
+        self.at += 1;
+        match node {
+            Node::String(s) => visitor.visit_str(s),
+            Node::Array { len, .. } => visitor.visit_seq(Elements {
+                reader: self,
+                left: len,
+            }),
+            Node::Object { len, .. } => visitor.visit_map(Entries {
+                reader: self,
+                left: len,
+            }),
+            Node::Static(
+                StaticNode::F64(_) | StaticNode::I64(0) | StaticNode::U64(0),
+            ) => visitor.visit_map(MapDeserializer::new(std::iter::once((
+                NUMBER_AT, at,
+            )))),
+            Node::Static(StaticNode::I64(v)) => visitor.visit_i64(v),
+            Node::Static(StaticNode::U64(v)) => visitor.visit_u64(v),
+            Node::Static(StaticNode::Bool(v)) => visitor.visit_bool(v),
+            Node::Static(StaticNode::Null) => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.at += match self.nodes.get(self.at) {
+            Some(Node::Array { count, .. } | Node::Object { count, .. }) => {
+                count + 1
+            }
+            _ => 1,
+        };
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier
+    }
+}
+
+struct Elements<'r, 't, 'i> {
+    reader: &'r mut TapeReader<'t, 'i>,
+    left: usize,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_, '_, '_> {
+    type Error = simd_json::Error;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+        seed.deserialize(&mut *self.reader).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.left)
+    }
+}
+
+struct Entries<'r, 't, 'i> {
+    reader: &'r mut TapeReader<'t, 'i>,
+    left: usize,
+}
+
+impl<'de> MapAccess<'de> for Entries<'_, '_, '_> {
+    type Error = simd_json::Error;
+
+    fn next_key_seed<K>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+        seed.deserialize(&mut *self.reader).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.reader)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.left)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::NodeKind;
+
+    fn tokens(json: &str, array_max_items: usize) -> Vec<String> {
+        let mut cfg = crate::PriorityConfig::new(usize::MAX, array_max_items);
+        cfg.array_sampler = crate::ArraySamplerStrategy::Head;
+        let builder =
+            super::super::json_builder(&crate::IngestOptions::default(), &cfg);
+        let mut text = json.as_bytes().to_vec();
+        TapeParser::new()
+            .push_value(&builder, &mut text, builder.seed())
+            .expect("valid JSON");
+        builder
+            .finish()
+            .nodes
+            .into_iter()
+            .filter(|n| n.kind == NodeKind::Number)
+            .filter_map(|n| n.atomic_token)
+            .collect()
+    }
+
+    #[test]
+    fn numbers_keep_their_source_text() {
+        assert_eq!(
+            tokens(r#"{"a\"": [1, -0, 10.50, 1E5], "b": {"c": [0, 7]}}"#, 10),
+            ["1", "-0", "10.50", "1E5", "0", "7"]
+        );
+        assert_eq!(tokens("-0", 10), ["-0"]);
+    }
+
+    #[test]
+    fn skipped_elements_keep_later_numbers_in_place() {
+        assert_eq!(
+            tokens(r#"{"a": [0.5, [1.5, {"x": 2.5}], 3.5], "b": 4.50}"#, 1),
+            ["0.5", "4.50"]
+        );
+    }
+
+    #[test]
+    fn out_of_range_numbers_keep_their_source_text() {
+        assert_eq!(
+            tokens(r#"{"s\"1e9": [1e400, 0.5, -2E+999], "t": 1e-400}"#, 10),
+            ["1e400", "0.5", "-2E+999", "1e-400"]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use super::{Item, Pair};
+
+// `KEY=value` lines, optionally prefixed with `export`. Double-quoted
+// values may span lines and understand `\n`, `\t`, `\"` and `\\`;
+// single-quoted values are literal. Unquoted values end at a ` #`
+// comment. Lines without `=` are skipped.
+pub(super) fn parse(text: &str) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let end = line_end(text, pos);
+        let trimmed = text[pos..end].trim();
+        pos = (end + 1).min(text.len());
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let assignment = trimmed
+            .strip_prefix("export ")
+            .map_or(trimmed, str::trim_start);
+        let Some((key, value)) = assignment.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        let value = value.trim_start();
+        let pair = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                // The value may run on past this line.
+                let start = offset_in(text, value) + 1;
+                let (unquoted, consumed) = quoted_value(&text[start..], quote);
+                pos = (line_end(text, start + consumed) + 1).min(text.len());
+                Pair {
+                    key,
+                    value: unquoted,
+                    quoted: true,
+                }
+            }
+            _ => Pair {
+                key,
+                value: strip_comment(value).to_string(),
+                quoted: false,
+            },
+        };
+        items.push(Item::Pair(pair));
+    }
+    items
+}
+
+fn line_end(text: &str, from: usize) -> usize {
+    text[from..].find('\n').map_or(text.len(), |i| from + i)
+}
+
+// Byte offset of `part`, a subslice of `text`.
+fn offset_in(text: &str, part: &str) -> usize {
+    part.as_ptr() as usize - text.as_ptr() as usize
+}
+
+// The text up to the closing quote, unescaped, and the number of bytes
+// consumed including the quote. An unclosed quote runs to the end.
+fn quoted_value(source: &str, quote: char) -> (String, usize) {
+    let mut value = String::new();
+    let mut chars = source.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c == quote {
+            return (value, i + 1);
+        }
+        if c == '\\' && quote == '"' {
+            value.push(chars.next().map_or('\\', |(_, e)| escaped(e)));
+        } else {
+            value.push(c);
+        }
+    }
+    (value, source.len())
+}
+
+fn escaped(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        other => other,
+    }
+}
+
+fn strip_comment(value: &str) -> &str {
+    value.find(" #").map_or(value, |i| &value[..i]).trim_end()
+}
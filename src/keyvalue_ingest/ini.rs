@@ -0,0 +1,76 @@
+use super::{Item, Pair};
+
+// `[section]` headers, `key = value` or `key: value` pairs and `;`/`#`
+// comment lines. An indented line continues a value that started empty or
+// is already continued (`install_requires =` followed by one requirement
+// per line), joined with newlines; other indented lines are pairs, as in
+// `.gitconfig`. A key without a separator gets an empty value.
+pub(super) fn parse(text: &str) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut multiline = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if is_blank_or_comment(trimmed) {
+            multiline = false;
+            continue;
+        }
+        if multiline && continue_value(&mut items, line) {
+            continue;
+        }
+        let item = parse_line(trimmed);
+        multiline = starts_multiline(&item);
+        items.push(item);
+    }
+    items
+}
+
+// `key =` with nothing after it, typically followed by indented lines.
+fn starts_multiline(item: &Item) -> bool {
+    matches!(item, Item::Pair(pair) if pair.value.is_empty())
+}
+
+fn is_blank_or_comment(trimmed: &str) -> bool {
+    trimmed.is_empty() || trimmed.starts_with([';', '#'])
+}
+
+// Append an indented line to the value of the last pair.
+fn continue_value(items: &mut [Item], line: &str) -> bool {
+    let Some(Item::Pair(pair)) = items.last_mut() else {
+        return false;
+    };
+    if !line.starts_with([' ', '\t']) {
+        return false;
+    }
+    if !pair.value.is_empty() {
+        pair.value.push('\n');
+    }
+    pair.value.push_str(line.trim());
+    true
+}
+
+fn parse_line(trimmed: &str) -> Item {
+    if let Some(name) =
+        trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']'))
+    {
+        return Item::Section(name.trim().to_string());
+    }
+    let Some(split) = trimmed.find(['=', ':']) else {
+        return Item::Pair(Pair {
+            key: trimmed.to_string(),
+            value: String::new(),
+            quoted: true,
+        });
+    };
+    let value = trimmed[split + 1..].trim();
+    let unquoted = ['"', '\''].iter().find_map(|&q| {
+        value
+            .strip_prefix(q)
+            .and_then(|v| v.strip_suffix(q))
+            .filter(|_| value.len() >= 2)
+    });
+    Item::Pair(Pair {
+        key: trimmed[..split].trim().to_string(),
+        value: unquoted.unwrap_or(value).to_string(),
+        quoted: unquoted.is_some(),
+    })
+}
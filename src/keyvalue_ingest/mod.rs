@@ -0,0 +1,361 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use serde::de::value::{Error as ValueError, MapDeserializer};
+use serde::de::{IntoDeserializer, Visitor};
+
+use crate::PriorityConfig;
+use crate::ingest::IngestOptions;
+use crate::json_ingest::{
+    NUMBER_TOKEN, build_tree_arena_from_deserializer,
+    build_tree_arena_from_many_deserializers,
+};
+use crate::utils::tree_arena::JsonTreeArena;
+
+mod dotenv;
+mod ini;
+mod properties;
+
+/// Line-oriented `key = value` config formats.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum KeyValueFormat {
+    /// `[section]` headers group the keys below them into objects.
+    Ini,
+    /// `KEY=value` lines with optional `export` and shell-style quoting.
+    Dotenv,
+    /// Java `.properties`: `key=value`, `key: value` or `key value` with
+    /// backslash escapes and line continuations.
+    Properties,
+}
+
+pub fn build_keyvalue_tree_arena_from_bytes(
+    bytes: &[u8],
+    format: KeyValueFormat,
+    ingest: &IngestOptions,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let doc = parse_document(bytes, format, ingest);
+    let mut arena = build_tree_arena_from_deserializer(doc, config)?;
+    arena.preserve_source_key_order();
+    Ok(arena)
+}
+
+pub fn build_keyvalue_tree_arena_from_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    format: KeyValueFormat,
+    ingest: &IngestOptions,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let docs: Vec<(String, KvValue)> = inputs
+        .into_iter()
+        .map(|(key, bytes)| (key, parse_document(&bytes, format, ingest)))
+        .collect();
+    let mut arena = build_tree_arena_from_many_deserializers(docs, config)?;
+    arena.preserve_source_key_order();
+    Ok(arena)
+}
+
+// One parsed line (or logical line) of a config file.
+enum Item {
+    Section(String),
+    Pair(Pair),
+}
+
+struct Pair {
+    key: String,
+    value: String,
+    // Quoted values stay strings even with typed coercion.
+    quoted: bool,
+}
+
+fn parse_document(
+    bytes: &[u8],
+    format: KeyValueFormat,
+    ingest: &IngestOptions,
+) -> KvValue {
+    let text = String::from_utf8_lossy(bytes).replace("\r\n", "\n");
+    let items = match format {
+        KeyValueFormat::Ini => ini::parse(&text),
+        KeyValueFormat::Dotenv => dotenv::parse(&text),
+        KeyValueFormat::Properties => properties::parse(&text),
+    };
+    let nest = format == KeyValueFormat::Properties && ingest.nest_dotted_keys;
+    let full_keys = if nest { Some(full_keys(&items)) } else { None };
+    let mut root = Entries::default();
+    let mut section: Option<String> = None;
+    for item in items {
+        match item {
+            Item::Section(name) => {
+                root.object_mut(&name);
+                section = Some(name);
+            }
+            Item::Pair(pair) => {
+                let target = section_mut(&mut root, section.as_deref());
+                let value =
+                    coerce(pair.value, pair.quoted, ingest.typed_values);
+                insert(target, pair.key, value, full_keys.as_ref());
+            }
+        }
+    }
+    KvValue::Object(root)
+}
+
+// Pairs before the first INI section belong to the root.
+fn section_mut<'a>(
+    root: &'a mut Entries,
+    section: Option<&str>,
+) -> &'a mut Entries {
+    match section {
+        Some(name) => root.object_mut(name),
+        None => root,
+    }
+}
+
+fn full_keys(items: &[Item]) -> HashSet<String> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Pair(pair) => Some(pair.key.clone()),
+            Item::Section(_) => None,
+        })
+        .collect()
+}
+
+// Nested when the keys of the file are given, flat otherwise.
+fn insert(
+    target: &mut Entries,
+    key: String,
+    value: KvValue,
+    full_keys: Option<&HashSet<String>>,
+) {
+    match full_keys {
+        Some(keys) => insert_nested(target, &key, value, keys),
+        None => target.insert(key, value),
+    }
+}
+
+// `a.b.c` goes to `{a: {b: {c}}}`, except at dots where the part before is
+// itself a key of the file: `a=1` and `a.b=2` give `{a: 1, "a.b": 2}`
+// rather than a value that is both a leaf and an object.
+fn insert_nested(
+    entries: &mut Entries,
+    key: &str,
+    value: KvValue,
+    full_keys: &HashSet<String>,
+) {
+    if key.split('.').any(str::is_empty) {
+        entries.insert(key.to_string(), value);
+        return;
+    }
+    let mut target = entries;
+    let mut start = 0;
+    for (dot, _) in key.match_indices('.') {
+        if full_keys.contains(&key[..dot]) {
+            continue;
+        }
+        target = target.object_mut(&key[start..dot]);
+        start = dot + 1;
+    }
+    target.insert(key[start..].to_string(), value);
+}
+
+// With `typed`, unquoted `true`/`false` and JSON-style numbers become
+// booleans and numbers; everything else stays a string.
+pub(crate) fn coerce(raw: String, quoted: bool, typed: bool) -> KvValue {
+    if !typed || quoted {
+        return KvValue::String(raw);
+    }
+    if raw.eq_ignore_ascii_case("true") {
+        KvValue::Bool(true)
+    } else if raw.eq_ignore_ascii_case("false") {
+        KvValue::Bool(false)
+    } else if is_json_number(&raw) {
+        KvValue::Number(raw)
+    } else {
+        KvValue::String(raw)
+    }
+}
+
+// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`, so codes with leading
+// zeros such as `007` stay strings.
+fn is_json_number(s: &str) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s);
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    let (int, frac) = match mantissa.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None),
+    };
+    let digits =
+        |d: &str| !d.is_empty() && d.bytes().all(|c| c.is_ascii_digit());
+    let int_ok = digits(int) && (int == "0" || !int.starts_with('0'));
+    let exponent_ok = exponent
+        .is_none_or(|e| digits(e.strip_prefix(['+', '-']).unwrap_or(e)));
+    int_ok && frac.is_none_or(digits) && exponent_ok
+}
+
+// Object entries in source order. A repeated key keeps its first position
+// and takes the last value, as config parsers do.
+#[derive(Default)]
+pub(crate) struct Entries {
+    items: Vec<(String, KvValue)>,
+    index: HashMap<String, usize>,
+}
+
+impl Entries {
+    pub(crate) fn insert(&mut self, key: String, value: KvValue) {
+        if let Some(&i) = self.index.get(&key) {
+            self.items[i].1 = value;
+        } else {
+            self.index.insert(key.clone(), self.items.len());
+            self.items.push((key, value));
+        }
+    }
+
+    // The object under `key`, created empty if missing. A plain value
+    // under the same key is replaced.
+    fn object_mut(&mut self, key: &str) -> &mut Entries {
+        let i = if let Some(&i) = self.index.get(key) {
+            i
+        } else {
+            self.insert(key.to_string(), KvValue::Object(Entries::default()));
+            self.items.len() - 1
+        };
+        let slot = &mut self.items[i].1;
+        if !matches!(slot, KvValue::Object(_)) {
+            *slot = KvValue::Object(Entries::default());
+        }
+        match slot {
+            KvValue::Object(entries) => entries,
+            _ => unreachable!("slot was just made an object"),
+        }
+    }
+}
+
+// Document model handed to the shared serde builder. Numbers travel as
+// their source text so `1.50` is not reformatted.
+pub(crate) enum KvValue {
+    String(String),
+    Bool(bool),
+    Number(String),
+    Object(Entries),
+}
+
+impl<'de> serde::Deserializer<'de> for KvValue {
+    type Error = ValueError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            KvValue::String(s) => visitor.visit_string(s),
+            KvValue::Bool(b) => visitor.visit_bool(b),
+            KvValue::Number(token) => visitor.visit_map(MapDeserializer::new(
+                std::iter::once((NUMBER_TOKEN, token)),
+            )),
+            KvValue::Object(entries) => visitor
+                .visit_map(MapDeserializer::new(entries.items.into_iter())),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, ValueError> for KvValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        RenderConfig,
+        serialization::types::{ColorMode, OutputTemplate, Style},
+    };
+
+    fn render(
+        text: &str,
+        format: KeyValueFormat,
+        ingest: &IngestOptions,
+    ) -> String {
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let bytes = text.as_bytes();
+        let arena =
+            build_keyvalue_tree_arena_from_bytes(bytes, format, ingest, &cfg)
+                .unwrap();
+        let render = RenderConfig {
+            template: OutputTemplate::Json,
+            indent_unit: String::new(),
+            space: String::new(),
+            newline: String::new(),
+            prefer_tail_arrays: false,
+            color_mode: ColorMode::Off,
+            color_enabled: false,
+            style: Style::Strict,
+        };
+        let order = crate::order::build_order(&arena, &cfg).unwrap();
+        crate::find_largest_render_under_budget(&order, &render, usize::MAX)
+    }
+
+    #[test]
+    fn ini_sections_become_objects() {
+        let ingest = IngestOptions::default();
+        let text = "top = 1\n; note\n[server]\nhost = \"a b\"\nport: 80\n\
+                    bare\n[options]\ninstall_requires =\n    x>=1\n    y\n\
+                    [server]\nport = 81\n";
+        assert_eq!(
+            render(text, KeyValueFormat::Ini, &ingest),
+            r#"{"top":"1","server":{"host":"a b","port":"81","bare":""},"options":{"install_requires":"x>=1\ny"}}"#
+        );
+    }
+
+    #[test]
+    fn dotenv_quoting_and_comments() {
+        let ingest = IngestOptions::default();
+        let text = "# env\nexport A=1 # one\nB='$x \\n'\nC=\"multi\nline \\\"q\\\"\"\nD=x#y\nnot a pair\n";
+        assert_eq!(
+            render(text, KeyValueFormat::Dotenv, &ingest),
+            r#"{"A":"1","B":"$x \\n","C":"multi\nline \"q\"","D":"x#y"}"#
+        );
+    }
+
+    #[test]
+    fn typed_values_coerce_unquoted_numbers_and_booleans() {
+        let mut ingest = IngestOptions::default();
+        let text = "a=1.50\nb=TRUE\nc=007\nd=\"42\"\ne=1e3\nf=-0.5x\n";
+        assert_eq!(
+            render(text, KeyValueFormat::Dotenv, &ingest),
+            r#"{"a":"1.50","b":"TRUE","c":"007","d":"42","e":"1e3","f":"-0.5x"}"#
+        );
+        ingest.typed_values = true;
+        assert_eq!(
+            render(text, KeyValueFormat::Dotenv, &ingest),
+            r#"{"a":1.50,"b":true,"c":"007","d":"42","e":1e3,"f":"-0.5x"}"#
+        );
+    }
+
+    #[test]
+    fn dotted_keys_nest_unless_a_prefix_is_a_key() {
+        let ingest = IngestOptions {
+            nest_dotted_keys: true,
+            ..IngestOptions::default()
+        };
+        let text =
+            "db.host=x\ndb.port=5\nlog=on\nlog.level=debug\nodd..key=1\n";
+        assert_eq!(
+            render(text, KeyValueFormat::Properties, &ingest),
+            r#"{"db":{"host":"x","port":"5"},"log":"on","log.level":"debug","odd..key":"1"}"#
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use super::{Item, Pair};
+
+// Java `.properties`: `#` and `!` comment lines, keys separated from
+// values by `=`, `:` or whitespace, and a trailing backslash continuing the
+// value on the next line. Keys and values are unescaped.
+pub(super) fn parse(text: &str) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with(['#', '!']) {
+            continue;
+        }
+        let mut logical = line.to_string();
+        while ends_with_continuation(&logical) {
+            logical.pop();
+            match lines.next() {
+                Some(next) => logical.push_str(next.trim_start()),
+                None => break,
+            }
+        }
+        items.push(Item::Pair(split_pair(&logical)));
+    }
+    items
+}
+
+// An odd number of trailing backslashes; `\\` is an escaped backslash.
+fn ends_with_continuation(line: &str) -> bool {
+    let slashes = line.len() - line.trim_end_matches('\\').len();
+    slashes % 2 == 1
+}
+
+// The key runs to the first unescaped `=`, `:` or whitespace; whitespace
+// around the separator is skipped.
+fn split_pair(line: &str) -> Pair {
+    let mut escaped = false;
+    let mut key_end = line.len();
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '=' | ':' | ' ' | '\t' | '\u{c}' => {
+                key_end = i;
+                break;
+            }
+            _ => {}
+        }
+    }
+    let rest = line[key_end..].trim_start_matches([' ', '\t', '\u{c}']);
+    let rest = rest
+        .strip_prefix(['=', ':'])
+        .map_or(rest, |r| r.trim_start_matches([' ', '\t', '\u{c}']));
+    Pair {
+        key: unescape(&line[..key_end]),
+        value: unescape(rest),
+        quoted: false,
+    }
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('f') => out.push('\u{c}'),
+            Some('u') => out.push(unicode_escape(&mut chars)),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+// `\uXXXX`; a malformed escape becomes U+FFFD.
+fn unicode_escape(chars: &mut std::str::Chars<'_>) -> char {
+    let hex: String = chars.by_ref().take(4).collect();
+    u32::from_str_radix(&hex, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(text: &str) -> Vec<(String, String)> {
+        parse(text)
+            .into_iter()
+            .filter_map(|item| match item {
+                Item::Pair(p) => Some((p.key, p.value)),
+                Item::Section(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn separators_escapes_and_continuations() {
+        let text = "# comment\n! also\na=1\nb : two\nc three\n\
+                    path\\ name=C:\\\\tmp\nlong=one, \\\n    two\n\
+                    uni=caf\\u00e9\n";
+        let expected = [
+            ("a", "1"),
+            ("b", "two"),
+            ("c", "three"),
+            ("path name", "C:\\tmp"),
+            ("long", "one, two"),
+            ("uni", "café"),
+        ];
+        let expected: Vec<(String, String)> = expected
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect();
+        assert_eq!(pairs(text), expected);
+    }
+}
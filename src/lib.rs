@@ -20,31 +20,55 @@
 
 use anyhow::Result;
 
+mod archive;
+mod bson_ingest;
+mod cbor_ingest;
+mod compression;
+mod csv_ingest;
+mod encoding;
 mod format;
+mod hcl_ingest;
 mod ingest;
+mod json5_ingest;
 mod json_ingest;
+mod keyvalue_ingest;
+mod logfmt_ingest;
+mod mapped;
+mod markdown_ingest;
+mod msgpack_ingest;
 mod order;
 mod serialization;
 mod text_ingest;
+mod toml_ingest;
 mod utils;
+mod xml_ingest;
 mod yaml_ingest;
-pub use order::types::{ArrayBias, ArraySamplerStrategy};
+pub use archive::Archive;
+pub use compression::{Compression, decompressing_reader};
+pub use encoding::{Encoding, sniff_utf16};
+pub use format::Format;
+pub use ingest::IngestOptions;
+pub use json_ingest::{DuplicateKey, JsonRecovery};
+pub use mapped::MappedInput;
+pub use order::types::{ArrayBias, ArraySamplerStrategy, DuplicateKeys};
 pub use order::{
     NodeId, NodeKind, PriorityConfig, PriorityOrder, RankedNode, build_order,
 };
 
 pub use serialization::color::resolve_color_enabled;
+pub use serialization::fileset::fileset_entry_min_len;
 pub use serialization::types::{
     ColorMode, OutputTemplate, RenderConfig, Style,
 };
 
 pub fn headson(
     input: Vec<u8>,
+    ingest: &IngestOptions,
     config: &RenderConfig,
     priority_cfg: &PriorityConfig,
     budget: usize,
 ) -> Result<String> {
-    let arena = crate::ingest::parse_json_one(input, priority_cfg)?;
+    let arena = crate::ingest::parse_json_one(input, ingest, priority_cfg)?;
     let order_build = order::build_order(&arena, priority_cfg)?;
     let out = find_largest_render_under_budget(&order_build, config, budget);
     Ok(out)
@@ -52,24 +76,219 @@ pub fn headson(
 
 pub fn headson_many(
     inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
     config: &RenderConfig,
     priority_cfg: &PriorityConfig,
     budget: usize,
 ) -> Result<String> {
-    let arena = crate::ingest::parse_json_many(inputs, priority_cfg)?;
+    let arena = crate::ingest::parse_json_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson`, but parses a memory-mapped file in place, so a large
+/// input is not read into a buffer of its own first. simd-json still
+/// copies it into its padded working buffer. With `json5_on_comment`, JSON
+/// that turns out to contain `//` or `/*` comments is read as JSON5, as
+/// auto mode reads a commented `.json` file; the comments are found by the
+/// parse rather than by a scan of the whole file beforehand.
+pub fn headson_mapped(
+    input: &mut MappedInput,
+    ingest: &IngestOptions,
+    json5_on_comment: bool,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_json_mapped(
+        input,
+        ingest,
+        priority_cfg,
+        json5_on_comment,
+    )?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson`, but JSON that is cut off or malformed halfway through
+/// is previewed up to the failure instead of rejected. The returned
+/// `JsonRecovery` says where parsing stopped; the detailed style marks the
+/// containers that were cut off with a comment.
+pub fn headson_recovering(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<(String, Option<JsonRecovery>)> {
+    let (arena, recovery) =
+        crate::ingest::parse_json_one_recovering(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok((out, recovery))
+}
+
+/// Same as `headson`, but also lists the keys that were repeated within an
+/// object. Only reports anything when `ingest.duplicate_keys` is set;
+/// the detailed style names the repeated keys in a comment.
+pub fn headson_reporting_duplicates(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<(String, Vec<DuplicateKey>)> {
+    let arena = crate::ingest::parse_json_one(input, ingest, priority_cfg)?;
+    render_reporting_duplicates(&arena, ingest, config, priority_cfg, budget)
+}
+
+/// Same as `headson_many`, but also lists the keys that were repeated
+/// within an object, with the file they came from.
+pub fn headson_many_reporting_duplicates(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<(String, Vec<DuplicateKey>)> {
+    let arena = crate::ingest::parse_json_many(inputs, ingest, priority_cfg)?;
+    render_reporting_duplicates(&arena, ingest, config, priority_cfg, budget)
+}
+
+/// Same as `headson_many_auto`, but also lists the keys that were repeated
+/// within an object of a JSON input, with the file they came from.
+pub fn headson_many_auto_reporting_duplicates(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<(String, Vec<DuplicateKey>, Vec<String>)> {
+    let mut arena =
+        crate::ingest::parse_many_auto(inputs, ingest, priority_cfg)?;
+    let (out, duplicates) = render_reporting_duplicates(
+        &arena,
+        ingest,
+        config,
+        priority_cfg,
+        budget,
+    )?;
+    Ok((out, duplicates, std::mem::take(&mut arena.text_fallbacks)))
+}
+
+fn render_reporting_duplicates(
+    arena: &utils::tree_arena::JsonTreeArena,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<(String, Vec<DuplicateKey>)> {
+    let out = render_arena(arena, ingest, config, priority_cfg, budget)?;
+    Ok((out, json_ingest::duplicate_key_report(arena)))
+}
+
+// A fileset also counts the inputs its caller left unread
+// (`ingest.unread_files`) in its `N more files` summary.
+fn render_arena(
+    arena: &utils::tree_arena::JsonTreeArena,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let mut order_build = order::build_order(arena, priority_cfg)?;
+    order_build.unread_files = ingest.unread_files;
+    Ok(find_largest_render_under_budget(
+        &order_build,
+        config,
+        budget,
+    ))
+}
+
+/// Same as `headson` but using the lenient JSON5/JSONC ingest path.
+pub fn headson_json5(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_json5_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the lenient JSON5/JSONC ingest path.
+pub fn headson_many_json5(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_json5_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson_many` but picks the ingest path per input from its
+/// filename (`.json`, `.json5`/`.jsonc`, `.jsonl`/`.ndjson`, `.yaml`/`.yml`, `.toml`,
+/// `.csv`, `.tsv`, `.xml`, `.html`, `.md`, `.ini`, `.env`, `.properties`, `.tf`, `.logfmt`, `.msgpack`, `.cbor`, `.bson`, ...),
+/// sniffing the content when the extension is unknown, so mixed filesets
+/// keep the structure of every file. Also returns the names of the inputs
+/// that failed to parse in their detected format and are shown as plain
+/// text instead.
+pub fn headson_many_auto(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<(String, Vec<String>)> {
+    let mut arena =
+        crate::ingest::parse_many_auto(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok((out, std::mem::take(&mut arena.text_fallbacks)))
+}
+
+/// Same as `headson` but using the JSON Lines (NDJSON) ingest path.
+pub fn headson_jsonl(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_jsonl_one(input, ingest, priority_cfg)?;
     let order_build = order::build_order(&arena, priority_cfg)?;
     let out = find_largest_render_under_budget(&order_build, config, budget);
     Ok(out)
 }
 
+/// Same as `headson_many` but using the JSON Lines (NDJSON) ingest path.
+pub fn headson_many_jsonl(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_jsonl_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
 /// Same as `headson` but using the YAML ingest path.
 pub fn headson_yaml(
     input: Vec<u8>,
+    ingest: &IngestOptions,
     config: &RenderConfig,
     priority_cfg: &PriorityConfig,
     budget: usize,
 ) -> Result<String> {
-    let arena = crate::ingest::parse_yaml_one(input, priority_cfg)?;
+    let arena = crate::ingest::parse_yaml_one(input, ingest, priority_cfg)?;
     let order_build = order::build_order(&arena, priority_cfg)?;
     let out = find_largest_render_under_budget(&order_build, config, budget);
     Ok(out)
@@ -78,24 +297,410 @@ pub fn headson_yaml(
 /// Same as `headson_many` but using the YAML ingest path.
 pub fn headson_many_yaml(
     inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_yaml_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the TOML ingest path.
+pub fn headson_toml(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_toml_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the TOML ingest path.
+pub fn headson_many_toml(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_toml_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the CSV ingest path.
+pub fn headson_csv(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_csv_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the CSV ingest path.
+pub fn headson_many_csv(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_csv_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the TSV ingest path.
+pub fn headson_tsv(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_tsv_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the TSV ingest path.
+pub fn headson_many_tsv(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_tsv_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the XML ingest path.
+pub fn headson_xml(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_xml_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the XML ingest path.
+pub fn headson_many_xml(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_xml_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the HTML ingest path.
+pub fn headson_html(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_html_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the HTML ingest path.
+pub fn headson_many_html(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
     config: &RenderConfig,
     priority_cfg: &PriorityConfig,
     budget: usize,
 ) -> Result<String> {
-    let arena = crate::ingest::parse_yaml_many(inputs, priority_cfg)?;
+    let arena = crate::ingest::parse_html_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the Markdown ingest path.
+pub fn headson_markdown(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena =
+        crate::ingest::parse_markdown_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the Markdown ingest path.
+pub fn headson_many_markdown(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena =
+        crate::ingest::parse_markdown_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the INI ingest path.
+pub fn headson_ini(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_ini_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the INI ingest path.
+pub fn headson_many_ini(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_ini_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the dotenv ingest path.
+pub fn headson_dotenv(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_dotenv_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the dotenv ingest path.
+pub fn headson_many_dotenv(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena =
+        crate::ingest::parse_dotenv_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the `.properties` ingest path.
+pub fn headson_properties(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena =
+        crate::ingest::parse_properties_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the `.properties` ingest path.
+pub fn headson_many_properties(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena =
+        crate::ingest::parse_properties_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the HCL (Terraform) ingest path.
+pub fn headson_hcl(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_hcl_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the HCL (Terraform) ingest path.
+pub fn headson_many_hcl(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_hcl_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the logfmt ingest path.
+pub fn headson_logfmt(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_logfmt_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the logfmt ingest path.
+pub fn headson_many_logfmt(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena =
+        crate::ingest::parse_logfmt_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the MessagePack ingest path.
+pub fn headson_msgpack(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_msgpack_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the MessagePack ingest path.
+pub fn headson_many_msgpack(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena =
+        crate::ingest::parse_msgpack_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the CBOR ingest path.
+pub fn headson_cbor(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_cbor_one(input, ingest, priority_cfg)?;
+    let order_build = order::build_order(&arena, priority_cfg)?;
+    let out = find_largest_render_under_budget(&order_build, config, budget);
+    Ok(out)
+}
+
+/// Same as `headson_many` but using the CBOR ingest path.
+pub fn headson_many_cbor(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_cbor_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but using the BSON ingest path.
+pub fn headson_bson(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_bson_one(input, ingest, priority_cfg)?;
     let order_build = order::build_order(&arena, priority_cfg)?;
     let out = find_largest_render_under_budget(&order_build, config, budget);
     Ok(out)
 }
 
+/// Same as `headson_many` but using the BSON ingest path.
+pub fn headson_many_bson(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_bson_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
 /// Same as `headson` but using the Text ingest path.
 pub fn headson_text(
     input: Vec<u8>,
+    ingest: &IngestOptions,
     config: &RenderConfig,
     priority_cfg: &PriorityConfig,
     budget: usize,
 ) -> Result<String> {
-    let arena = crate::ingest::parse_text_one(input, priority_cfg)?;
+    let arena = crate::ingest::parse_text_one(input, ingest, priority_cfg)?;
     let order_build = order::build_order(&arena, priority_cfg)?;
     let out = find_largest_render_under_budget(&order_build, config, budget);
     Ok(out)
@@ -104,16 +709,45 @@ pub fn headson_text(
 /// Same as `headson_many` but using the Text ingest path.
 pub fn headson_many_text(
     inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena = crate::ingest::parse_text_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
+/// Same as `headson` but nesting text by indentation and brackets, so the
+/// preview keeps top-level lines and elides nested bodies first.
+pub fn headson_outline(
+    input: Vec<u8>,
+    ingest: &IngestOptions,
     config: &RenderConfig,
     priority_cfg: &PriorityConfig,
     budget: usize,
 ) -> Result<String> {
-    let arena = crate::ingest::parse_text_many(inputs, priority_cfg)?;
+    let arena = crate::ingest::parse_outline_one(input, ingest, priority_cfg)?;
     let order_build = order::build_order(&arena, priority_cfg)?;
     let out = find_largest_render_under_budget(&order_build, config, budget);
     Ok(out)
 }
 
+/// Same as `headson_many` but using the outline ingest path.
+pub fn headson_many_outline(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &RenderConfig,
+    priority_cfg: &PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    let arena =
+        crate::ingest::parse_outline_many(inputs, ingest, priority_cfg)?;
+    let out = render_arena(&arena, ingest, config, priority_cfg, budget)?;
+    Ok(out)
+}
+
 fn find_largest_render_under_budget(
     order_build: &PriorityOrder,
     config: &RenderConfig,
@@ -0,0 +1,237 @@
+use anyhow::Result;
+use serde::de::value::Error as ValueError;
+use serde::de::{DeserializeSeed, SeqAccess};
+
+use crate::PriorityConfig;
+use crate::ingest::IngestOptions;
+use crate::json_ingest::{
+    build_tree_arena_from_many_seqs, build_tree_arena_from_seq,
+};
+use crate::keyvalue_ingest::{Entries, KvValue, coerce};
+use crate::utils::tree_arena::JsonTreeArena;
+
+// Number of leading non-blank lines checked when sniffing for logfmt.
+const PROBE_LINES: usize = 20;
+
+/// Build an arena from logfmt (`key=value key2="quoted value"`) lines.
+/// Every non-blank line becomes an element of a root array: an object of
+/// its pairs, or the raw line when it is not a sequence of pairs.
+pub fn build_logfmt_tree_arena_from_bytes(
+    bytes: &[u8],
+    ingest: &IngestOptions,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut lines = LogfmtLines::new(&text, ingest.typed_values);
+    let mut arena = build_tree_arena_from_seq(&mut lines, config)?;
+    arena.preserve_source_key_order();
+    Ok(arena)
+}
+
+pub fn build_logfmt_tree_arena_from_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    ingest: &IngestOptions,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let texts: Vec<(String, String)> = inputs
+        .into_iter()
+        .map(|(key, bytes)| {
+            (key, String::from_utf8_lossy(&bytes).into_owned())
+        })
+        .collect();
+    let readers: Vec<(String, LogfmtLines<'_>)> = texts
+        .iter()
+        .map(|(key, text)| {
+            (key.clone(), LogfmtLines::new(text, ingest.typed_values))
+        })
+        .collect();
+    let mut arena = build_tree_arena_from_many_seqs(readers, config)?;
+    arena.preserve_source_key_order();
+    Ok(arena)
+}
+
+/// Whether the start of an input reads as logfmt: the first non-blank
+/// line and most of the lines after it hold at least two pairs, so a
+/// stray panic or banner line does not spoil detection. A last line cut
+/// off by the sniffing window is ignored unless the input is `complete`.
+pub(crate) fn looks_like_logfmt(text: &str, complete: bool) -> bool {
+    let mut lines: Vec<&str> = text.lines().collect();
+    if !complete {
+        lines.pop();
+    }
+    let records: Vec<bool> = lines
+        .into_iter()
+        .filter(|line| !line.trim().is_empty())
+        .take(PROBE_LINES)
+        .map(|line| split_pairs(line).is_some_and(|p| p.len() >= 2))
+        .collect();
+    let matching = records.iter().filter(|&&is_record| is_record).count();
+    records.first() == Some(&true) && matching * 2 > records.len()
+}
+
+// Sequence access over the lines of a log, handed to the array samplers
+// one record at a time.
+struct LogfmtLines<'a> {
+    lines: std::str::Lines<'a>,
+    typed: bool,
+}
+
+impl<'a> LogfmtLines<'a> {
+    fn new(text: &'a str, typed: bool) -> Self {
+        Self {
+            lines: text.lines(),
+            typed,
+        }
+    }
+
+    fn record(&self, line: &str) -> KvValue {
+        let Some(pairs) = split_pairs(line) else {
+            return KvValue::String(line.to_string());
+        };
+        let mut entries = Entries::default();
+        for pair in pairs {
+            let value = coerce(pair.value, pair.quoted, self.typed);
+            entries.insert(pair.key.to_string(), value);
+        }
+        KvValue::Object(entries)
+    }
+}
+
+impl<'de> SeqAccess<'de> for LogfmtLines<'_> {
+    type Error = ValueError;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let Some(line) = self.lines.find(|line| !line.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+        seed.deserialize(self.record(line)).map(Some)
+    }
+}
+
+struct Pair<'a> {
+    key: &'a str,
+    value: String,
+    // Quoted values stay strings even with typed coercion.
+    quoted: bool,
+}
+
+// The pairs of a line made only of `key=value` tokens, where a value is
+// either bare (up to the next space) or double-quoted with backslash
+// escapes. `None` for anything else, including bare words.
+fn split_pairs(line: &str) -> Option<Vec<Pair<'_>>> {
+    let mut pairs = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let key_len = rest.find(['=', '"', ' ', '\t']).unwrap_or(rest.len());
+        let (key, after) = rest.split_at(key_len);
+        let after = after.strip_prefix('=')?;
+        if key.is_empty() {
+            return None;
+        }
+        let (value, quoted, remaining) = split_value(after)?;
+        if !remaining.is_empty() && !remaining.starts_with([' ', '\t']) {
+            return None;
+        }
+        pairs.push(Pair { key, value, quoted });
+        rest = remaining.trim_start();
+    }
+    (!pairs.is_empty()).then_some(pairs)
+}
+
+// The value at the start of `text`, whether it was quoted, and the text
+// after it.
+fn split_value(text: &str) -> Option<(String, bool, &str)> {
+    if let Some(body) = text.strip_prefix('"') {
+        let (value, remaining) = unquote(body)?;
+        return Some((value, true, remaining));
+    }
+    let len = text.find([' ', '\t']).unwrap_or(text.len());
+    Some((text[..len].to_string(), false, &text[len..]))
+}
+
+// A quoted value whose body starts `body`, and the text after its closing
+// quote.
+fn unquote(body: &str) -> Option<(String, &str)> {
+    let mut value = String::with_capacity(body.len());
+    let mut chars = body.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &body[i + 1..])),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                other => value.push(other),
+            },
+            _ => value.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        RenderConfig,
+        serialization::types::{ColorMode, OutputTemplate, Style},
+    };
+
+    fn render(text: &str, ingest: &IngestOptions) -> String {
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            build_logfmt_tree_arena_from_bytes(text.as_bytes(), ingest, &cfg)
+                .unwrap();
+        let render = RenderConfig {
+            template: OutputTemplate::Json,
+            indent_unit: String::new(),
+            space: String::new(),
+            newline: String::new(),
+            prefer_tail_arrays: false,
+            color_mode: ColorMode::Off,
+            color_enabled: false,
+            style: Style::Strict,
+        };
+        let order = crate::order::build_order(&arena, &cfg).unwrap();
+        crate::find_largest_render_under_budget(&order, &render, usize::MAX)
+    }
+
+    #[test]
+    fn lines_become_objects_or_stay_raw() {
+        let mut ingest = IngestOptions::default();
+        let text = "level=info msg=\"hello \\\"world\\\"\" n=3 empty=\n\n\
+                    Starting server on :80\nat=error code=\"H12\n";
+        assert_eq!(
+            render(text, &ingest),
+            r#"[{"level":"info","msg":"hello \"world\"","n":"3","empty":""},"Starting server on :80","at=error code=\"H12"]"#
+        );
+        ingest.typed_values = true;
+        assert_eq!(
+            render("n=3 ok=true q=\"4\"\n", &ingest),
+            r#"[{"n":3,"ok":true,"q":"4"}]"#
+        );
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn sniffing_needs_two_pairs_on_every_line() {
+        let log = "time=1 level=info msg=up\ntime=2 level=warn msg=\"slow\"\n";
+        assert!(looks_like_logfmt(log, true));
+        assert!(looks_like_logfmt("a=1 b=2\nc=3 d=\"cut", false));
+        assert!(!looks_like_logfmt("KEY=value\nOTHER=1\n", true));
+        assert!(!looks_like_logfmt("name = \"x\"\nport = 80\n", true));
+        assert!(looks_like_logfmt("a=1 b=2\nc=3 d=4\npanic: x\n", true));
+        assert!(!looks_like_logfmt("a=1 b=2\nplain text\n", true));
+        assert!(!looks_like_logfmt("banner\na=1 b=2\nc=3 d=4\n", true));
+    }
+}
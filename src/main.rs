@@ -13,13 +13,13 @@ use content_inspector::{ContentType, inspect};
 
 type InputEntry = (String, Vec<u8>);
 type InputEntries = Vec<InputEntry>;
-type IgnoreNotices = Vec<String>;
+type Notices = Vec<String>;
 
 #[derive(Parser, Debug)]
 #[command(
     name = "headson",
     version,
-    about = "Get a small but useful preview of JSON or YAML"
+    about = "Get a small but useful preview of JSON, YAML or TOML"
 )]
 struct Cli {
     #[arg(short = 'n', long = "budget")]
@@ -29,7 +29,7 @@ struct Cli {
         long = "format",
         value_enum,
         default_value_t = OutputFormat::Auto,
-        help = "Output format: auto|json|yaml|text (filesets: auto is per-file)."
+        help = "Output format: auto|json|yaml|toml|csv|tsv|text (filesets: auto is per-file)."
     )]
     format: OutputFormat,
     #[arg(
@@ -102,17 +102,82 @@ struct Cli {
         value_name = "INPUT",
         value_hint = clap::ValueHint::FilePath,
         num_args = 0..,
-        help = "Optional file paths. If omitted, reads input from stdin. Multiple input files are supported. Directories and binary files are ignored with a notice on stderr."
+        help = "Optional file paths. If omitted, reads input from stdin. Multiple input files are supported; tar and zip archives (also compressed) expand into their member files. Directories (unless --recursive) and binary files are ignored with a summary notice on stderr, except MessagePack, CBOR and BSON documents, which are detected in auto mode or read with --input-format."
     )]
     inputs: Vec<PathBuf>,
+    #[arg(
+        short = 'r',
+        long = "recursive",
+        default_value_t = false,
+        help = "Walk directory inputs and preview the files under them as a fileset. Files ignored by .gitignore or .ignore and hidden entries are skipped."
+    )]
+    recursive: bool,
+    #[arg(
+        long = "include",
+        value_name = "GLOB",
+        requires = "recursive",
+        help = "Only preview walked files matching this glob, e.g. '*.json'. Globs match like .gitignore lines relative to the walked directory. Repeatable."
+    )]
+    include: Vec<String>,
+    #[arg(
+        long = "exclude",
+        value_name = "GLOB",
+        requires = "recursive",
+        help = "Skip walked files and directories matching this glob, e.g. 'vendor/'. Takes precedence over --include. Repeatable."
+    )]
+    exclude: Vec<String>,
+    #[arg(
+        long = "no-ignore",
+        default_value_t = false,
+        requires = "recursive",
+        help = "Walk files ignored by .gitignore or .ignore and hidden entries too."
+    )]
+    no_ignore: bool,
     #[arg(
         short = 'i',
         long = "input-format",
         value_enum,
-        default_value_t = InputFormat::Json,
-        help = "Input ingestion format: json|yaml|text."
+        help = "Input ingestion format: json|json5|jsonl|yaml|toml|csv|tsv|xml|html|markdown|ini|dotenv|properties|hcl|logfmt|msgpack|cbor|bson|text|outline. Defaults to json; with --format auto it is detected from the extension or content."
     )]
-    input_format: InputFormat,
+    input_format: Option<InputFormat>,
+    #[arg(
+        long = "recover",
+        default_value_t = false,
+        help = "Preview JSON that is cut off or malformed up to the point where parsing fails, closing open containers. The failure offset is reported on stderr. Applies to a single JSON input."
+    )]
+    recover: bool,
+    #[arg(
+        long = "duplicate-keys",
+        value_enum,
+        value_name = "KEEP",
+        conflicts_with = "recover",
+        help = "Resolve keys repeated within a JSON object by keeping the first or the last occurrence: first|last. Each repeated key is reported on stderr with its path; the detailed style also notes it in the object. By default every occurrence is kept."
+    )]
+    duplicate_keys: Option<DuplicateKeysArg>,
+    #[arg(
+        long = "encoding",
+        value_name = "LABEL",
+        value_parser = parse_encoding,
+        help = "Encoding of text input without a byte order mark, e.g. latin1, windows-1252, shift_jis or utf-16le. Input with a BOM and UTF-16 input are detected automatically; everything else is read as UTF-8."
+    )]
+    encoding: Option<&'static headson::Encoding>,
+    #[arg(
+        long = "typed-values",
+        default_value_t = false,
+        help = "Read unquoted numbers and true/false in INI, dotenv, .properties and logfmt input as numbers and booleans instead of strings."
+    )]
+    typed_values: bool,
+    #[arg(
+        long = "nest-dotted-keys",
+        default_value_t = false,
+        help = "Nest dotted .properties keys into objects: a.b.c=1 becomes {a: {b: {c: 1}}}. A key that is also the prefix of others stays flat."
+    )]
+    nest_dotted_keys: bool,
+}
+
+fn parse_encoding(label: &str) -> Result<&'static headson::Encoding, String> {
+    headson::Encoding::for_label(label.as_bytes())
+        .ok_or_else(|| format!("unknown encoding: {label}"))
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -120,6 +185,9 @@ enum OutputFormat {
     Auto,
     Json,
     Yaml,
+    Toml,
+    Csv,
+    Tsv,
     Text,
 }
 
@@ -130,11 +198,50 @@ enum StyleArg {
     Detailed,
 }
 
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum DuplicateKeysArg {
+    First,
+    Last,
+}
+
 #[derive(Copy, Clone, Debug, ValueEnum)]
 enum InputFormat {
     Json,
+    #[value(alias = "jsonc")]
+    Json5,
+    #[value(alias = "ndjson")]
+    Jsonl,
     Yaml,
+    Toml,
+    Csv,
+    Tsv,
+    Xml,
+    Html,
+    #[value(alias = "md")]
+    Markdown,
+    Ini,
+    #[value(alias = "env")]
+    Dotenv,
+    Properties,
+    #[value(alias = "tf", alias = "terraform")]
+    Hcl,
+    Logfmt,
+    #[value(alias = "mpk")]
+    Msgpack,
+    Cbor,
+    Bson,
     Text,
+    #[value(alias = "code")]
+    Outline,
+}
+
+impl InputFormat {
+    fn is_binary(self) -> bool {
+        matches!(
+            self,
+            InputFormat::Msgpack | InputFormat::Cbor | InputFormat::Bson
+        )
+    }
 }
 
 fn main() -> Result<()> {
@@ -144,14 +251,14 @@ fn main() -> Result<()> {
     // Resolve color auto-detection now (stdout is the surface for user output).
     let _color_enabled =
         render_cfg.color_mode.effective(io::stdout().is_terminal());
-    let (output, ignore_notices) = if cli.inputs.is_empty() {
-        (run_from_stdin(&cli, &render_cfg)?, Vec::new())
+    let (output, notices) = if cli.inputs.is_empty() {
+        run_from_stdin(&cli, &render_cfg)?
     } else {
         run_from_paths(&cli, &render_cfg)?
     };
     println!("{output}");
 
-    for notice in ignore_notices {
+    for notice in notices {
         eprintln!("{notice}");
     }
 
@@ -172,163 +279,584 @@ fn compute_priority(
     effective_budget: usize,
     input_count: usize,
 ) -> headson::PriorityConfig {
-    let per_file_for_priority =
-        if cli.global_budget.is_some() && cli.budget.is_some() {
+    let per_file_for_priority = match (cli.global_budget, cli.budget) {
+        (Some(_), Some(n)) => {
             // When both limits are provided, base per-file heuristics on the per-file
             // budget but also respect the effective per-file slice of the final global.
             let eff_per_file = (effective_budget / input_count.max(1)).max(1);
-            cli.budget.unwrap().min(eff_per_file).max(1)
-        } else {
-            (effective_budget / input_count.max(1)).max(1)
-        };
+            n.min(eff_per_file).max(1)
+        }
+        _ => (effective_budget / input_count.max(1)).max(1),
+    };
     get_priority_config(per_file_for_priority, cli)
 }
 
 fn run_from_stdin(
     cli: &Cli,
     render_cfg: &headson::RenderConfig,
-) -> Result<String> {
+) -> Result<(String, Notices)> {
     let input_bytes = read_stdin()?;
     let input_count = 1usize;
     let eff = compute_effective_budget(cli, input_count);
     let prio = compute_priority(cli, eff, input_count);
+    // Stdin has no name to go by; in auto mode classify the bytes instead.
+    let input_format =
+        resolve_input_format(cli, || headson::Format::sniff(&input_bytes));
     let mut cfg = render_cfg.clone();
-    // Resolve effective output template for stdin:
-    cfg.template = resolve_effective_template_for_stdin(cli.format, cfg.style);
-    match cli.input_format {
-        InputFormat::Json => headson::headson(input_bytes, &cfg, &prio, eff),
-        InputFormat::Yaml => {
-            headson::headson_yaml(input_bytes, &cfg, &prio, eff)
-        }
-        InputFormat::Text => {
-            headson::headson_text(input_bytes, &cfg, &prio, eff)
-        }
-    }
+    cfg.template =
+        resolve_effective_template(cli.format, cfg.style, input_format);
+    let input_bytes = InputBytes::Read(input_bytes);
+    render_single(cli, input_format, input_bytes, &cfg, &prio, eff)
 }
 
-#[allow(
-    clippy::cognitive_complexity,
-    reason = "Keeps ingest selection and rendering logic co-located for clarity."
-)]
 fn run_from_paths(
     cli: &Cli,
     render_cfg: &headson::RenderConfig,
-) -> Result<(String, IgnoreNotices)> {
-    let (entries, ignored) = ingest_paths(&cli.inputs)?;
+) -> Result<(String, Notices)> {
+    let ingested = ingest_paths(
+        &cli.inputs,
+        binary_inputs(cli),
+        walk_options(cli).as_ref(),
+        cli.global_budget,
+        |path| maps_as_json(cli, path),
+    )?;
+    let ignored = ingested.skip_notices();
+    let Ingested {
+        entries,
+        expanded,
+        unread_files,
+        ..
+    } = ingested;
     let included = entries.len();
-    let input_count = included.max(1);
+    // Unread files still take their share of a per-file budget, as they
+    // would have if they had been read.
+    let input_count = (included + unread_files).max(1);
     let eff = compute_effective_budget(cli, input_count);
     let prio = compute_priority(cli, eff, input_count);
-    // In Auto template mode, choose ingestion strategy based on extensions for filesets:
-    // if any included input has a YAML extension, prefer YAML ingest (can parse JSON too).
-    fn any_yaml_ext(entries: &InputEntries) -> bool {
-        entries.iter().any(|(name, _)| {
-            let lower = name.to_ascii_lowercase();
-            lower.ends_with(".yaml") || lower.ends_with(".yml")
-        })
-    }
-    fn all_json_ext(entries: &InputEntries) -> bool {
-        entries.iter().all(|(name, _)| {
-            let lower = name.to_ascii_lowercase();
-            lower.ends_with(".json")
-        })
-    }
-    if cli.inputs.len() > 1 {
-        let chosen_input = if matches!(cli.format, OutputFormat::Auto) {
-            if any_yaml_ext(&entries) {
-                InputFormat::Yaml
-            } else if all_json_ext(&entries) {
-                InputFormat::Json
-            } else {
-                // Mixed or unknown extensions: treat as text to avoid JSON/YAML parse errors
-                InputFormat::Text
-            }
-        } else {
-            cli.input_format
-        };
+    if cli.inputs.len() > 1 || expanded {
         let mut cfg = render_cfg.clone();
         // For filesets: if format=auto, enable per-file template selection.
         cfg.template = match cli.format {
             OutputFormat::Auto => headson::OutputTemplate::Auto,
             OutputFormat::Json => map_json_template_for_style(cfg.style),
             OutputFormat::Yaml => headson::OutputTemplate::Yaml,
+            OutputFormat::Toml => headson::OutputTemplate::Toml,
+            OutputFormat::Csv => headson::OutputTemplate::Csv,
+            OutputFormat::Tsv => headson::OutputTemplate::Tsv,
             OutputFormat::Text => headson::OutputTemplate::Text,
         };
-        let out = match chosen_input {
-            InputFormat::Json => {
-                headson::headson_many(entries, &cfg, &prio, eff)?
-            }
-            InputFormat::Yaml => {
-                headson::headson_many_yaml(entries, &cfg, &prio, eff)?
-            }
-            InputFormat::Text => {
-                headson::headson_many_text(entries, &cfg, &prio, eff)?
-            }
-        };
-        Ok((out, ignored))
+        // Only a lone file is ever mapped, so these are all read already.
+        let entries = entries
+            .into_iter()
+            .map(|(key, bytes)| (key, bytes.into_vec()))
+            .collect();
+        let (out, mut notices) =
+            render_fileset(cli, entries, unread_files, &cfg, &prio, eff)?;
+        notices.extend(ignored);
+        Ok((out, notices))
     } else if included == 0 {
         Ok((String::new(), ignored))
     } else {
         let (name, bytes) = entries.into_iter().next().unwrap();
         // Single file: pick ingest and output template per CLI format+style.
-        let lower = name.to_ascii_lowercase();
-        let is_yaml_ext = lower.ends_with(".yaml") || lower.ends_with(".yml");
-        let chosen_input = match cli.format {
-            OutputFormat::Auto => {
-                if is_yaml_ext {
-                    InputFormat::Yaml
-                } else if lower.ends_with(".json") {
-                    InputFormat::Json
-                } else {
-                    InputFormat::Text
-                }
-            }
-            _ => cli.input_format,
-        };
+        // A mapped file is JSON by name (see `maps_as_json`); its parse
+        // tells JSON with comments apart rather than a scan of the map.
+        let input_format = resolve_input_format(cli, || match &bytes {
+            InputBytes::Mapped(_) => headson::Format::Json,
+            InputBytes::Read(bytes) => headson::Format::detect(&name, bytes),
+        });
         let mut cfg = render_cfg.clone();
-        cfg.template = resolve_effective_template_for_single(
-            cli.format, cfg.style, &lower,
-        );
-        let out = match chosen_input {
-            InputFormat::Json => headson::headson(bytes, &cfg, &prio, eff)?,
-            InputFormat::Yaml => {
-                headson::headson_yaml(bytes, &cfg, &prio, eff)?
-            }
-            InputFormat::Text => {
-                headson::headson_text(bytes, &cfg, &prio, eff)?
-            }
+        cfg.template =
+            resolve_effective_template(cli.format, cfg.style, input_format);
+        let (out, mut notices) =
+            render_single(cli, input_format, bytes, &cfg, &prio, eff)?;
+        notices.extend(ignored);
+        Ok((out, notices))
+    }
+}
+
+// An explicit `-i` always wins. Otherwise auto mode detects the format
+// (extension and/or content) and the other modes keep the JSON default.
+fn resolve_input_format(
+    cli: &Cli,
+    detect: impl FnOnce() -> headson::Format,
+) -> InputFormat {
+    match (cli.input_format, cli.format) {
+        (Some(explicit), _) => explicit,
+        (None, OutputFormat::Auto) => match detect() {
+            headson::Format::Json => InputFormat::Json,
+            headson::Format::Json5 => InputFormat::Json5,
+            headson::Format::Jsonl => InputFormat::Jsonl,
+            headson::Format::Yaml => InputFormat::Yaml,
+            headson::Format::Toml => InputFormat::Toml,
+            headson::Format::Csv => InputFormat::Csv,
+            headson::Format::Tsv => InputFormat::Tsv,
+            headson::Format::Xml => InputFormat::Xml,
+            headson::Format::Html => InputFormat::Html,
+            headson::Format::Markdown => InputFormat::Markdown,
+            headson::Format::Ini => InputFormat::Ini,
+            headson::Format::Dotenv => InputFormat::Dotenv,
+            headson::Format::Properties => InputFormat::Properties,
+            headson::Format::Hcl => InputFormat::Hcl,
+            headson::Format::Logfmt => InputFormat::Logfmt,
+            headson::Format::Msgpack => InputFormat::Msgpack,
+            headson::Format::Cbor => InputFormat::Cbor,
+            headson::Format::Bson => InputFormat::Bson,
+            headson::Format::Unknown => InputFormat::Text,
+        },
+        (None, _) => InputFormat::Json,
+    }
+}
+
+fn render_fileset(
+    cli: &Cli,
+    entries: InputEntries,
+    unread_files: usize,
+    cfg: &headson::RenderConfig,
+    prio: &headson::PriorityConfig,
+    budget: usize,
+) -> Result<(String, Notices)> {
+    let input_format = cli.input_format.unwrap_or(InputFormat::Json);
+    let ingest = headson::IngestOptions {
+        unread_files,
+        ..get_ingest_options(cli)
+    };
+    // In Auto mode each file is ingested by its own format.
+    let auto =
+        matches!(cli.format, OutputFormat::Auto) && cli.input_format.is_none();
+    if ingest.duplicate_keys.is_some()
+        && (auto || matches!(input_format, InputFormat::Json))
+    {
+        let (out, duplicates, fallbacks) = if auto {
+            headson::headson_many_auto_reporting_duplicates(
+                entries, &ingest, cfg, prio, budget,
+            )?
+        } else {
+            let (out, duplicates) =
+                headson::headson_many_reporting_duplicates(
+                    entries, &ingest, cfg, prio, budget,
+                )?;
+            (out, duplicates, Vec::new())
         };
-        Ok((out, ignored))
+        let mut notices = duplicate_notices(&duplicates, &ingest);
+        notices.extend(text_fallback_notice(&fallbacks));
+        return Ok((out, notices));
+    }
+    if auto {
+        let (out, fallbacks) =
+            headson::headson_many_auto(entries, &ingest, cfg, prio, budget)?;
+        return Ok((
+            out,
+            text_fallback_notice(&fallbacks).into_iter().collect(),
+        ));
+    }
+    let out = render_many(input_format, entries, &ingest, cfg, prio, budget)?;
+    Ok((out, Vec::new()))
+}
+
+fn text_fallback_notice(paths: &[String]) -> Option<String> {
+    match paths {
+        [] => None,
+        [path] => Some(format!("Could not parse, shown as text: {path}")),
+        _ => Some(format!(
+            "Could not parse {} files, shown as text: {}",
+            paths.len(),
+            path_list(paths)
+        )),
+    }
+}
+
+fn render_single(
+    cli: &Cli,
+    input_format: InputFormat,
+    bytes: InputBytes,
+    cfg: &headson::RenderConfig,
+    prio: &headson::PriorityConfig,
+    budget: usize,
+) -> Result<(String, Notices)> {
+    let ingest = get_ingest_options(cli);
+    if cli.recover && matches!(input_format, InputFormat::Json) {
+        let (out, recovery) = headson::headson_recovering(
+            bytes.into_vec(),
+            &ingest,
+            cfg,
+            prio,
+            budget,
+        )?;
+        let notices =
+            recovery.as_ref().map(recovery_notice).into_iter().collect();
+        return Ok((out, notices));
+    }
+    if ingest.duplicate_keys.is_some()
+        && matches!(input_format, InputFormat::Json)
+    {
+        let (out, duplicates) = headson::headson_reporting_duplicates(
+            bytes.into_vec(),
+            &ingest,
+            cfg,
+            prio,
+            budget,
+        )?;
+        return Ok((out, duplicate_notices(&duplicates, &ingest)));
+    }
+    let out = match (input_format, bytes) {
+        (InputFormat::Json, InputBytes::Mapped(mut mapped)) => {
+            // Auto mode reads a `.json` file with comments as JSON5.
+            let json5_on_comment = matches!(
+                (cli.input_format, cli.format),
+                (None, OutputFormat::Auto)
+            );
+            headson::headson_mapped(
+                &mut mapped,
+                &ingest,
+                json5_on_comment,
+                cfg,
+                prio,
+                budget,
+            )?
+        }
+        (format, bytes) => render_single_format(
+            format,
+            bytes.into_vec(),
+            &ingest,
+            cfg,
+            prio,
+            budget,
+        )?,
+    };
+    Ok((out, Vec::new()))
+}
+
+fn duplicate_notices(
+    duplicates: &[headson::DuplicateKey],
+    ingest: &headson::IngestOptions,
+) -> Notices {
+    let kept = match ingest.duplicate_keys {
+        Some(headson::DuplicateKeys::KeepLast) => "last",
+        _ => "first",
+    };
+    duplicates
+        .iter()
+        .map(|d| {
+            let file = d
+                .file
+                .as_ref()
+                .map(|f| format!(" in {f}"))
+                .unwrap_or_default();
+            format!(
+                "Duplicate key {}{file}: kept the {kept} of {} values",
+                d.path, d.occurrences
+            )
+        })
+        .collect()
+}
+
+fn recovery_notice(recovery: &headson::JsonRecovery) -> String {
+    format!(
+        "Recovered from invalid JSON at byte {}: {}",
+        recovery.offset, recovery.error
+    )
+}
+
+fn render_single_format(
+    input_format: InputFormat,
+    bytes: Vec<u8>,
+    ingest: &headson::IngestOptions,
+    cfg: &headson::RenderConfig,
+    prio: &headson::PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    match input_format {
+        InputFormat::Json => {
+            headson::headson(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Json5 => {
+            headson::headson_json5(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Jsonl => {
+            headson::headson_jsonl(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Yaml => {
+            headson::headson_yaml(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Toml => {
+            headson::headson_toml(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Csv => {
+            headson::headson_csv(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Tsv => {
+            headson::headson_tsv(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Xml => {
+            headson::headson_xml(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Html => {
+            headson::headson_html(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Markdown => {
+            headson::headson_markdown(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Ini => {
+            headson::headson_ini(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Dotenv => {
+            headson::headson_dotenv(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Properties => {
+            headson::headson_properties(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Hcl => {
+            headson::headson_hcl(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Logfmt => {
+            headson::headson_logfmt(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Msgpack => {
+            headson::headson_msgpack(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Cbor => {
+            headson::headson_cbor(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Bson => {
+            headson::headson_bson(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Text => {
+            headson::headson_text(bytes, ingest, cfg, prio, budget)
+        }
+        InputFormat::Outline => {
+            headson::headson_outline(bytes, ingest, cfg, prio, budget)
+        }
+    }
+}
+
+fn render_many(
+    input_format: InputFormat,
+    entries: InputEntries,
+    ingest: &headson::IngestOptions,
+    cfg: &headson::RenderConfig,
+    prio: &headson::PriorityConfig,
+    budget: usize,
+) -> Result<String> {
+    match input_format {
+        InputFormat::Json => {
+            headson::headson_many(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Json5 => {
+            headson::headson_many_json5(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Jsonl => {
+            headson::headson_many_jsonl(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Yaml => {
+            headson::headson_many_yaml(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Toml => {
+            headson::headson_many_toml(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Csv => {
+            headson::headson_many_csv(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Tsv => {
+            headson::headson_many_tsv(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Xml => {
+            headson::headson_many_xml(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Html => {
+            headson::headson_many_html(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Markdown => {
+            headson::headson_many_markdown(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Ini => {
+            headson::headson_many_ini(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Dotenv => {
+            headson::headson_many_dotenv(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Properties => headson::headson_many_properties(
+            entries, ingest, cfg, prio, budget,
+        ),
+        InputFormat::Hcl => {
+            headson::headson_many_hcl(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Logfmt => {
+            headson::headson_many_logfmt(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Msgpack => {
+            headson::headson_many_msgpack(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Cbor => {
+            headson::headson_many_cbor(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Bson => {
+            headson::headson_many_bson(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Text => {
+            headson::headson_many_text(entries, ingest, cfg, prio, budget)
+        }
+        InputFormat::Outline => {
+            headson::headson_many_outline(entries, ingest, cfg, prio, budget)
+        }
     }
 }
 
 fn read_stdin() -> Result<Vec<u8>> {
     let mut buf = Vec::new();
-    io::stdin()
-        .read_to_end(&mut buf)
+    headson::decompressing_reader(io::stdin().lock())
+        .and_then(|mut reader| reader.read_to_end(&mut buf))
         .context("failed to read from stdin")?;
     Ok(buf)
 }
 
-fn sniff_then_read_text(path: &Path) -> Result<Option<Vec<u8>>> {
-    // Inspect the first chunk with content_inspector; if it looks binary, skip.
-    // Otherwise, read the remainder without further inspection for speed.
-    const CHUNK: usize = 64 * 1024;
+// What to do with files that content_inspector flags as binary.
+#[derive(Copy, Clone)]
+enum BinaryInputs {
+    // `-i` names a binary format: read every file.
+    Keep,
+    // Auto mode: read files that hold a MessagePack, CBOR or BSON document.
+    Detect,
+    // A text format is expected: skip them.
+    Skip,
+}
+
+// A large file is only worth mapping when it is parsed as JSON in place:
+// other formats, and JSON read with `--recover` or `--duplicate-keys`,
+// are parsed from a buffer of their own, which a map would only be copied
+// into. In auto mode the name decides, so the map is not scanned first.
+fn maps_as_json(cli: &Cli, path: &Path) -> bool {
+    if cli.recover || cli.duplicate_keys.is_some() {
+        return false;
+    }
+    match (cli.input_format, cli.format) {
+        (Some(format), _) => matches!(format, InputFormat::Json),
+        (None, OutputFormat::Auto) => {
+            headson::Format::from_filename(&path.to_string_lossy())
+                == headson::Format::Json
+        }
+        (None, _) => true,
+    }
+}
+
+fn binary_inputs(cli: &Cli) -> BinaryInputs {
+    match (cli.input_format, cli.format) {
+        (Some(format), _) if format.is_binary() => BinaryInputs::Keep,
+        (None, OutputFormat::Auto) => BinaryInputs::Detect,
+        _ => BinaryInputs::Skip,
+    }
+}
+
+// Cheap check on the first chunk, before reading the whole file.
+fn may_keep_binary(path: &Path, binary: BinaryInputs, prefix: &[u8]) -> bool {
+    match binary {
+        BinaryInputs::Keep => true,
+        BinaryInputs::Skip => false,
+        BinaryInputs::Detect => {
+            headson::Format::from_filename(&path.to_string_lossy()).is_binary()
+                || headson::Format::may_start_binary_document(prefix)
+        }
+    }
+}
+
+fn keeps_binary(path: &Path, binary: BinaryInputs, bytes: &[u8]) -> bool {
+    match binary {
+        BinaryInputs::Keep => true,
+        BinaryInputs::Skip => false,
+        BinaryInputs::Detect => {
+            headson::Format::detect(&path.to_string_lossy(), bytes).is_binary()
+        }
+    }
+}
+
+// Files are sniffed from their first chunk before the rest is read.
+const SNIFF_LEN: usize = 64 * 1024;
+// Uncompressed files at least this large are memory-mapped instead of
+// read; smaller ones fit the preallocated read buffer.
+const MAP_MIN_LEN: u64 = 8 * 1024 * 1024;
+
+// Contents of one input: read into memory or, for a large plain JSON file
+// given on its own, a copy-on-write memory map that the JSON parser
+// rewrites in place.
+enum InputBytes {
+    Read(Vec<u8>),
+    Mapped(headson::MappedInput),
+}
+
+impl InputBytes {
+    fn into_vec(self) -> Vec<u8> {
+        match self {
+            InputBytes::Read(bytes) => bytes,
+            InputBytes::Mapped(mapped) => mapped.to_vec(),
+        }
+    }
+}
+
+impl std::ops::Deref for InputBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            InputBytes::Read(bytes) => bytes,
+            InputBytes::Mapped(mapped) => mapped,
+        }
+    }
+}
+
+// What reading one input path produced.
+enum PathInput {
+    Document(InputBytes),
+    // Regular files of a tar or zip archive, keyed by member path.
+    Archive(InputEntries),
+    Binary,
+}
+
+// BOM-less UTF-16 text is full of NUL bytes but still text.
+fn looks_binary(prefix: &[u8]) -> bool {
+    matches!(inspect(prefix), ContentType::BINARY)
+        && headson::sniff_utf16(prefix).is_none()
+}
+
+fn sniff_then_read(
+    path: &Path,
+    binary_inputs: BinaryInputs,
+    map: bool,
+) -> Result<PathInput> {
+    // Large plain files are mapped when `map` allows it. Compressed files are decoded as a
+    // stream, but to the end. Archives are read whole and split into their members.
+    // Inspect the first chunk with content_inspector; if it looks binary,
+    // skip it unless it may be a binary document we can ingest. Otherwise,
+    // read the remainder without further inspection for speed.
     let file = File::open(path).with_context(|| {
         format!("failed to open input file: {}", path.display())
     })?;
     let meta_len = file.metadata().ok().map(|m| m.len());
-    let mut reader = io::BufReader::with_capacity(CHUNK, file);
+    if map
+        && meta_len.is_some_and(|len| len >= MAP_MIN_LEN)
+        && let Some(input) = map_plain_file(path, &file, binary_inputs)
+    {
+        return Ok(input);
+    }
+    let read_error =
+        || format!("failed to read input file: {}", path.display());
+    let mut reader = headson::decompressing_reader(
+        io::BufReader::with_capacity(SNIFF_LEN, file),
+    )
+    .with_context(read_error)?;
 
-    let mut first = [0u8; CHUNK];
-    let n = reader.read(&mut first).with_context(|| {
-        format!("failed to read input file: {}", path.display())
-    })?;
+    let mut first = Vec::with_capacity(SNIFF_LEN);
+    let n = reader
+        .by_ref()
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut first)
+        .with_context(read_error)?;
     if n == 0 {
-        return Ok(Some(Vec::new()));
+        return Ok(PathInput::Document(InputBytes::Read(Vec::new())));
     }
-    if matches!(inspect(&first[..n]), ContentType::BINARY) {
-        return Ok(None);
+    let archive = headson::Archive::sniff(&first[..n]);
+    let binary = archive.is_none() && looks_binary(&first[..n]);
+    if binary && !may_keep_binary(path, binary_inputs, &first[..n]) {
+        return Ok(PathInput::Binary);
     }
 
     // Preallocate buffer: first chunk + estimated remainder (capped)
@@ -336,34 +864,261 @@ fn sniff_then_read_text(path: &Path) -> Result<Option<Vec<u8>>> {
         n + meta_len
             .map(|m| m.saturating_sub(n as u64) as usize)
             .unwrap_or(0)
-            .min(8 * 1024 * 1024),
+            .min(MAP_MIN_LEN as usize),
     );
     buf.extend_from_slice(&first[..n]);
-    reader.read_to_end(&mut buf).with_context(|| {
-        format!("failed to read input file: {}", path.display())
-    })?;
-    Ok(Some(buf))
+    reader.read_to_end(&mut buf).with_context(read_error)?;
+    if let Some(archive) = archive {
+        let members =
+            archive.members(io::Cursor::new(buf)).with_context(|| {
+                format!("failed to read archive: {}", path.display())
+            })?;
+        return Ok(PathInput::Archive(members));
+    }
+    if binary && !keeps_binary(path, binary_inputs, &buf) {
+        return Ok(PathInput::Binary);
+    }
+    Ok(PathInput::Document(InputBytes::Read(buf)))
 }
 
-fn ingest_paths(paths: &[PathBuf]) -> Result<(InputEntries, IgnoreNotices)> {
-    let mut out: InputEntries = Vec::with_capacity(paths.len());
-    let mut ignored: IgnoreNotices = Vec::new();
-    for path in paths.iter() {
+// Map `file` without reading it. `None` when it cannot be mapped or is
+// compressed or an archive, which take the streaming path.
+fn map_plain_file(
+    path: &Path,
+    file: &File,
+    binary_inputs: BinaryInputs,
+) -> Option<PathInput> {
+    let mapped = headson::MappedInput::new(file).ok()?;
+    let prefix = &mapped[..mapped.len().min(SNIFF_LEN)];
+    if headson::Compression::sniff(prefix).is_some()
+        || headson::Archive::sniff(prefix).is_some()
+    {
+        return None;
+    }
+    if looks_binary(prefix) && !keeps_binary(path, binary_inputs, &mapped) {
+        return Some(PathInput::Binary);
+    }
+    Some(PathInput::Document(InputBytes::Mapped(mapped)))
+}
+
+// Archive members go through the same binary filter as files on disk.
+fn keeps_member(
+    name: &str,
+    bytes: &[u8],
+    binary_inputs: BinaryInputs,
+) -> bool {
+    let prefix = &bytes[..bytes.len().min(SNIFF_LEN)];
+    !looks_binary(prefix)
+        || keeps_binary(Path::new(name), binary_inputs, bytes)
+}
+
+// How many paths a summarized skip notice names before counting the rest.
+const SKIP_NOTICE_PATHS: usize = 5;
+
+// Files read from the input paths, with archives expanded into their
+// members and, with `--recursive`, directories into their files.
+#[derive(Default)]
+struct Ingested {
+    entries: Vec<(String, InputBytes)>,
+    skipped_dirs: Vec<String>,
+    skipped_binaries: Vec<String>,
+    // An archive or directory was expanded, so the output is a fileset
+    // even for a single path.
+    expanded: bool,
+    // Files left unread because the global budget cannot reach them.
+    unread_files: usize,
+}
+
+impl Ingested {
+    // Lower bound on the output of the entry just read from `file`. Archive
+    // members count nothing: their keys sort elsewhere in the fileset.
+    fn entry_min_len(&self, file: &Path) -> usize {
+        match self.entries.last() {
+            Some((key, _)) if Path::new(key) == file => {
+                headson::fileset_entry_min_len(key)
+            }
+            _ => 0,
+        }
+    }
+
+    fn add_file(
+        &mut self,
+        path: &Path,
+        binary_inputs: BinaryInputs,
+        map: bool,
+    ) -> Result<()> {
         let display = path.display().to_string();
-        if let Ok(meta) = std::fs::metadata(path) {
-            if meta.is_dir() {
-                ignored.push(format!("Ignored directory: {display}"));
-                continue;
+        match sniff_then_read(path, binary_inputs, map)? {
+            PathInput::Document(bytes) => self.entries.push((display, bytes)),
+            PathInput::Archive(members) => {
+                self.add_archive(&display, members, binary_inputs);
             }
+            PathInput::Binary => self.skipped_binaries.push(display),
         }
-        if let Some(bytes) = sniff_then_read_text(path)? {
-            out.push((display, bytes))
-        } else {
-            ignored.push(format!("Ignored binary file: {display}"));
-            continue;
+        Ok(())
+    }
+
+    fn add_archive(
+        &mut self,
+        display: &str,
+        members: InputEntries,
+        binary_inputs: BinaryInputs,
+    ) {
+        self.expanded = true;
+        for (name, bytes) in members {
+            if keeps_member(&name, &bytes, binary_inputs) {
+                self.entries.push((name, InputBytes::Read(bytes)));
+            } else {
+                self.skipped_binaries.push(format!("{display}: {name}"));
+            }
+        }
+    }
+
+    // One notice per kind of skipped input rather than one per path, so a
+    // walked tree full of images does not flood stderr.
+    fn skip_notices(&self) -> Notices {
+        [
+            skip_notice("directory", "directories", &self.skipped_dirs),
+            skip_notice("binary file", "binary files", &self.skipped_binaries),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+fn skip_notice(
+    singular: &str,
+    plural: &str,
+    paths: &[String],
+) -> Option<String> {
+    match paths {
+        [] => None,
+        [path] => Some(format!("Ignored {singular}: {path}")),
+        _ => Some(format!(
+            "Ignored {} {plural}: {}",
+            paths.len(),
+            path_list(paths)
+        )),
+    }
+}
+
+// The first few paths of a notice, then how many were left out.
+fn path_list(paths: &[String]) -> String {
+    let shown = paths[..paths.len().min(SKIP_NOTICE_PATHS)].join(", ");
+    let rest = paths.len().saturating_sub(SKIP_NOTICE_PATHS);
+    if rest > 0 {
+        format!("{shown} and {rest} more")
+    } else {
+        shown
+    }
+}
+
+// Directory walking for `--recursive`.
+struct WalkOptions<'a> {
+    include: &'a [String],
+    exclude: &'a [String],
+    // Honor .gitignore and .ignore files and skip hidden entries.
+    standard_filters: bool,
+}
+
+fn walk_options(cli: &Cli) -> Option<WalkOptions<'_>> {
+    cli.recursive.then(|| WalkOptions {
+        include: &cli.include,
+        exclude: &cli.exclude,
+        standard_filters: !cli.no_ignore,
+    })
+}
+
+// Regular files under `dir` in path order. Include and exclude globs are
+// matched like .gitignore lines relative to `dir`.
+fn walk_dir(dir: &Path, walk: &WalkOptions<'_>) -> Result<Vec<PathBuf>> {
+    let mut overrides = ignore::overrides::OverrideBuilder::new(dir);
+    for glob in walk.include {
+        overrides
+            .add(glob)
+            .with_context(|| format!("invalid --include glob: {glob}"))?;
+    }
+    for glob in walk.exclude {
+        overrides
+            .add(&format!("!{glob}"))
+            .with_context(|| format!("invalid --exclude glob: {glob}"))?;
+    }
+    let walker = ignore::WalkBuilder::new(dir)
+        .standard_filters(walk.standard_filters)
+        .require_git(false)
+        .overrides(overrides.build()?)
+        .sort_by_file_name(Ord::cmp)
+        .build();
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = entry.with_context(|| {
+            format!("failed to walk directory: {}", dir.display())
+        })?;
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(files)
+}
+
+// Files are listed (and directories walked) from metadata alone before
+// anything is read. With a global budget, files are then read in the key
+// order filesets are shown in, and reading stops once the entries read so
+// far cannot all fit: the files after them could not be shown anyway.
+fn ingest_paths(
+    paths: &[PathBuf],
+    binary_inputs: BinaryInputs,
+    walk: Option<&WalkOptions<'_>>,
+    global_budget: Option<usize>,
+    maps_as_json: impl Fn(&Path) -> bool,
+) -> Result<Ingested> {
+    let mut ingested = Ingested::default();
+    let mut files: Vec<PathBuf> = Vec::with_capacity(paths.len());
+    for path in paths.iter() {
+        let is_dir = std::fs::metadata(path).is_ok_and(|m| m.is_dir());
+        match (is_dir, walk) {
+            (false, _) => files.push(path.clone()),
+            (true, None) => {
+                ingested.skipped_dirs.push(path.display().to_string());
+            }
+            (true, Some(walk)) => {
+                ingested.expanded = true;
+                files.extend(walk_dir(path, walk)?);
+            }
+        }
+    }
+    let map = maps_lone_file(&files, ingested.expanded, maps_as_json);
+    let Some(budget) = global_budget else {
+        ingested.entries.reserve(files.len());
+        for file in &files {
+            ingested.add_file(file, binary_inputs, map)?;
+        }
+        return Ok(ingested);
+    };
+    files.sort_by_cached_key(|file| file.display().to_string());
+    let mut min_len = 0usize;
+    for (i, file) in files.iter().enumerate() {
+        if min_len > budget {
+            ingested.unread_files = files.len() - i;
+            break;
         }
+        ingested.add_file(file, binary_inputs, map)?;
+        min_len += ingested.entry_min_len(file);
+    }
+    Ok(ingested)
+}
+
+// Only a file given on its own may be mapped; see `maps_as_json`.
+fn maps_lone_file(
+    files: &[PathBuf],
+    expanded: bool,
+    maps_as_json: impl Fn(&Path) -> bool,
+) -> bool {
+    match files {
+        [file] => !expanded && maps_as_json(file),
+        _ => false,
     }
-    Ok((out, ignored))
 }
 
 fn get_render_config_from(cli: &Cli) -> headson::RenderConfig {
@@ -384,6 +1139,9 @@ fn get_render_config_from(cli: &Cli) -> headson::RenderConfig {
             map_json_template_for_style(map_style(cli.style))
         }
         OutputFormat::Yaml => headson::OutputTemplate::Yaml,
+        OutputFormat::Toml => headson::OutputTemplate::Toml,
+        OutputFormat::Csv => headson::OutputTemplate::Csv,
+        OutputFormat::Tsv => headson::OutputTemplate::Tsv,
         OutputFormat::Text => headson::OutputTemplate::Text,
     };
     let space = if cli.compact || cli.no_space { "" } else { " " }.to_string();
@@ -432,6 +1190,19 @@ fn get_priority_config(
     }
 }
 
+fn get_ingest_options(cli: &Cli) -> headson::IngestOptions {
+    headson::IngestOptions {
+        duplicate_keys: cli.duplicate_keys.map(|keep| match keep {
+            DuplicateKeysArg::First => headson::DuplicateKeys::KeepFirst,
+            DuplicateKeysArg::Last => headson::DuplicateKeys::KeepLast,
+        }),
+        encoding: cli.encoding,
+        typed_values: cli.typed_values,
+        nest_dotted_keys: cli.nest_dotted_keys,
+        unread_files: 0,
+    }
+}
+
 fn map_style(s: StyleArg) -> headson::Style {
     match s {
         StyleArg::Strict => headson::Style::Strict,
@@ -450,37 +1221,44 @@ fn map_json_template_for_style(
     }
 }
 
-fn resolve_effective_template_for_stdin(
+// In auto mode the template follows the ingested format: YAML, TOML and
+// delimited text render as themselves, INI, dotenv and `.properties` as
+// TOML, text as raw lines, and everything else
+// (including XML/HTML, HCL and the binary formats) in the JSON family.
+fn resolve_effective_template(
     fmt: OutputFormat,
     style: headson::Style,
-) -> headson::OutputTemplate {
-    match fmt {
-        OutputFormat::Auto | OutputFormat::Json => {
-            map_json_template_for_style(style)
-        }
-        OutputFormat::Yaml => headson::OutputTemplate::Yaml,
-        OutputFormat::Text => headson::OutputTemplate::Text,
-    }
-}
-
-fn resolve_effective_template_for_single(
-    fmt: OutputFormat,
-    style: headson::Style,
-    lower_name: &str,
+    input_format: InputFormat,
 ) -> headson::OutputTemplate {
     match fmt {
         OutputFormat::Json => map_json_template_for_style(style),
         OutputFormat::Yaml => headson::OutputTemplate::Yaml,
+        OutputFormat::Toml => headson::OutputTemplate::Toml,
+        OutputFormat::Csv => headson::OutputTemplate::Csv,
+        OutputFormat::Tsv => headson::OutputTemplate::Tsv,
         OutputFormat::Text => headson::OutputTemplate::Text,
-        OutputFormat::Auto => {
-            if lower_name.ends_with(".yaml") || lower_name.ends_with(".yml") {
-                headson::OutputTemplate::Yaml
-            } else if lower_name.ends_with(".json") {
-                map_json_template_for_style(style)
-            } else {
-                // Unknown extension: prefer text template.
+        OutputFormat::Auto => match input_format {
+            InputFormat::Json
+            | InputFormat::Json5
+            | InputFormat::Jsonl
+            | InputFormat::Xml
+            | InputFormat::Html
+            | InputFormat::Markdown
+            | InputFormat::Hcl
+            | InputFormat::Logfmt
+            | InputFormat::Msgpack
+            | InputFormat::Cbor
+            | InputFormat::Bson => map_json_template_for_style(style),
+            InputFormat::Yaml => headson::OutputTemplate::Yaml,
+            InputFormat::Toml
+            | InputFormat::Ini
+            | InputFormat::Dotenv
+            | InputFormat::Properties => headson::OutputTemplate::Toml,
+            InputFormat::Csv => headson::OutputTemplate::Csv,
+            InputFormat::Tsv => headson::OutputTemplate::Tsv,
+            InputFormat::Text | InputFormat::Outline => {
                 headson::OutputTemplate::Text
             }
-        }
+        },
     }
 }
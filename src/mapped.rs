@@ -0,0 +1,63 @@
+use std::fs::File;
+use std::io;
+use std::ops::{Deref, DerefMut};
+
+use memmap2::{MmapMut, MmapOptions};
+
+/// A private, copy-on-write memory map of an input file, so a large input
+/// need not be read into a buffer. The JSON parser rewrites it in place
+/// (simd-json unescapes strings where they stand): written pages get a
+/// private copy, and changes never reach the file.
+pub struct MappedInput {
+    map: MmapMut,
+}
+
+impl MappedInput {
+    /// Map all of `file`.
+    pub fn new(file: &File) -> io::Result<Self> {
+        let map = map_copy(file)?;
+        Ok(Self { map })
+    }
+}
+
+fn map_copy(file: &File) -> io::Result<MmapMut> {
+    // SAFETY: the map is private, so writes through it stay in memory.
+    // Another process truncating or rewriting the file while it is mapped
+    // can still change or invalidate unwritten pages; as with other tools
+    // that map their inputs, files are expected to stay put while headson
+    // reads them.
+    unsafe { MmapOptions::new().map_copy(file) }
+}
+
+impl Deref for MappedInput {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+impl DerefMut for MappedInput {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn writes_stay_in_the_map() {
+        let source = b"{\"a\": \"x\\ny\"}";
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(source).unwrap();
+        let mut mapped = MappedInput::new(file.as_file()).unwrap();
+        mapped[0] = b'[';
+        assert_eq!(&mapped[..2], b"[\"");
+        assert_eq!(&mapped[..], b"[\"a\": \"x\\ny\"}");
+        // The file as reopened from disk still holds what was written.
+        assert_eq!(std::fs::read(file.path()).unwrap(), source);
+    }
+}
@@ -0,0 +1,572 @@
+use anyhow::{Context, Result};
+use serde::de::value::{
+    Error as ValueError, MapDeserializer, SeqDeserializer,
+};
+use serde::de::{IntoDeserializer, Visitor};
+
+use crate::PriorityConfig;
+use crate::format::Format;
+use crate::json_ingest::build_tree_arena_from_deserializer;
+use crate::utils::tree_arena::JsonTreeArena;
+
+// Synthetic keys for the parts of a document that have no heading.
+const FRONT_MATTER_KEY: &str = "#front_matter";
+const CONTENT_KEY: &str = "#content";
+
+// Lists nested deeper than this keep their remaining lines as text.
+const MAX_DEPTH: usize = 64;
+
+pub fn build_markdown_tree_arena_from_bytes(
+    bytes: &[u8],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let text = String::from_utf8_lossy(bytes).replace("\r\n", "\n");
+    let (front_matter, body) = split_front_matter(&text);
+    let mut arena =
+        build_tree_arena_from_deserializer(parse_document(body), config)?;
+    arena.preserve_source_key_order();
+    if let Some(yaml) = front_matter {
+        let yaml = crate::yaml_ingest::build_yaml_tree_arena_from_bytes(
+            yaml.as_bytes(),
+            config,
+        )
+        .context("invalid YAML front matter")?;
+        let yaml_id = arena.graft(yaml);
+        attach_front_matter(&mut arena, yaml_id);
+    }
+    Ok(arena)
+}
+
+pub fn build_markdown_tree_arena_from_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut arena = JsonTreeArena::default();
+    let mut keys: Vec<String> = Vec::with_capacity(inputs.len());
+    let mut children: Vec<usize> = Vec::with_capacity(inputs.len());
+    for (key, bytes) in inputs {
+        let doc = build_markdown_tree_arena_from_bytes(&bytes, config)
+            .with_context(|| format!("failed to parse {key}"))?;
+        children.push(arena.graft(doc));
+        keys.push(key);
+    }
+    arena.fileset_formats = vec![Format::Markdown; keys.len()];
+    arena.root_id = arena.push_object(keys, children);
+    arena.is_fileset = true;
+    Ok(arena)
+}
+
+// Put the front matter first in the root object.
+fn attach_front_matter(arena: &mut JsonTreeArena, yaml_id: usize) {
+    let root = &arena.nodes[arena.root_id];
+    let keys = &arena.obj_keys
+        [root.obj_keys_start..root.obj_keys_start + root.obj_keys_len];
+    let children = &arena.children
+        [root.children_start..root.children_start + root.children_len];
+    let keys = std::iter::once(FRONT_MATTER_KEY.to_string())
+        .chain(keys.iter().cloned())
+        .collect();
+    let children = std::iter::once(yaml_id)
+        .chain(children.iter().copied())
+        .collect();
+    let root_id = arena.push_object(keys, children);
+    arena.nodes[root_id].preserve_key_order = true;
+    arena.root_id = root_id;
+}
+
+// YAML front matter: a `---` first line up to the next `---` or `...`.
+fn split_front_matter(text: &str) -> (Option<&str>, &str) {
+    let Some(rest) = text.strip_prefix("---\n") else {
+        return (None, text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            let body = &rest[offset + line.len()..];
+            return (Some(&rest[..offset]), body);
+        }
+        offset += line.len();
+    }
+    (None, text)
+}
+
+// Document model handed to the shared serde builder. A section is an
+// object of its subsections keyed by heading, preceded by `#content` for
+// the blocks before the first subsection. Paragraphs are strings with
+// their lines joined, lists arrays of items and code blocks strings.
+enum MdValue {
+    Text(String),
+    List(Vec<MdValue>),
+    Section(Vec<(String, MdValue)>),
+}
+
+impl<'de> serde::Deserializer<'de> for MdValue {
+    type Error = ValueError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            MdValue::Text(s) => visitor.visit_string(s),
+            MdValue::List(items) => {
+                visitor.visit_seq(SeqDeserializer::new(items.into_iter()))
+            }
+            MdValue::Section(entries) => {
+                visitor.visit_map(MapDeserializer::new(entries.into_iter()))
+            }
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, ValueError> for MdValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+enum Block {
+    Heading(usize, String),
+    Paragraph(Vec<String>),
+    Code(String),
+    List(Vec<MdValue>),
+}
+
+impl Block {
+    fn into_value(self) -> MdValue {
+        match self {
+            Block::Heading(_, text) | Block::Code(text) => MdValue::Text(text),
+            Block::Paragraph(lines) => MdValue::Text(lines.join(" ")),
+            Block::List(items) => MdValue::List(items),
+        }
+    }
+}
+
+struct Section {
+    level: usize,
+    title: String,
+    content: Vec<MdValue>,
+    subsections: Vec<(String, MdValue)>,
+}
+
+impl Section {
+    fn new(level: usize, title: String) -> Self {
+        Self {
+            level,
+            title,
+            content: Vec::new(),
+            subsections: Vec::new(),
+        }
+    }
+
+    fn into_value(self) -> MdValue {
+        let mut entries = Vec::with_capacity(self.subsections.len() + 1);
+        if !self.content.is_empty() {
+            entries
+                .push((CONTENT_KEY.to_string(), MdValue::List(self.content)));
+        }
+        entries.extend(self.subsections);
+        MdValue::Section(entries)
+    }
+}
+
+// Nest the blocks under their headings: a heading closes every open
+// section of the same or a deeper level.
+fn parse_document(text: &str) -> MdValue {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut stack = vec![Section::new(0, String::new())];
+    for block in parse_blocks(&lines, 0) {
+        let Block::Heading(level, title) = block else {
+            if let Some(open) = stack.last_mut() {
+                open.content.push(block.into_value());
+            }
+            continue;
+        };
+        while stack.len() > 1 && stack[stack.len() - 1].level >= level {
+            close_section(&mut stack);
+        }
+        stack.push(Section::new(level, title));
+    }
+    while stack.len() > 1 {
+        close_section(&mut stack);
+    }
+    stack
+        .pop()
+        .map_or(MdValue::Section(Vec::new()), Section::into_value)
+}
+
+fn close_section(stack: &mut Vec<Section>) {
+    if let (Some(section), Some(parent)) = (stack.pop(), stack.last_mut()) {
+        let title = section.title.clone();
+        parent.subsections.push((title, section.into_value()));
+    }
+}
+
+fn parse_blocks(lines: &[&str], depth: usize) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let (block, next) = take_block(lines, i, depth);
+        blocks.extend(block);
+        i = next;
+    }
+    blocks
+}
+
+// The block starting at line `start` and the line after it; blank lines
+// and thematic breaks yield no block.
+fn take_block(
+    lines: &[&str],
+    start: usize,
+    depth: usize,
+) -> (Option<Block>, usize) {
+    let line = lines[start];
+    if line.trim().is_empty() || is_thematic_break(line) {
+        return (None, start + 1);
+    }
+    if let Some(fence) = fence_of(line) {
+        let (code, next) = take_fenced(lines, start, fence);
+        return (Some(Block::Code(code)), next);
+    }
+    if let Some(heading) = atx_heading(line) {
+        return (Some(heading), start + 1);
+    }
+    if depth < MAX_DEPTH && list_marker(line).is_some() {
+        let (items, next) = take_list(lines, start, depth);
+        return (Some(Block::List(items)), next);
+    }
+    let (block, next) = take_paragraph(lines, start);
+    (Some(block), next)
+}
+
+// Lines up to the next blank line or block start. An `===` or `---`
+// underline turns them into a heading instead.
+fn take_paragraph(lines: &[&str], start: usize) -> (Block, usize) {
+    let mut paragraph = vec![lines[start].trim_end().to_string()];
+    let mut i = start + 1;
+    while let Some(line) = lines.get(i) {
+        if let Some(level) = setext_level(line) {
+            return (Block::Heading(level, paragraph.join(" ")), i + 1);
+        }
+        if line.trim().is_empty() || starts_block(line) {
+            break;
+        }
+        paragraph.push(line.trim_end().to_string());
+        i += 1;
+    }
+    (Block::Paragraph(paragraph), i)
+}
+
+fn starts_block(line: &str) -> bool {
+    fence_of(line).is_some()
+        || atx_heading(line).is_some()
+        || is_thematic_break(line)
+        || list_marker(line).is_some()
+}
+
+fn leading_spaces(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn atx_heading(line: &str) -> Option<Block> {
+    if leading_spaces(line) > 3 {
+        return None;
+    }
+    let trimmed = line.trim();
+    let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+    let rest = &trimmed[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' '))
+    {
+        return None;
+    }
+    // An optional closing run of `#` is not part of the title.
+    let title = rest.trim_end_matches('#');
+    let title = if title.is_empty() || title.ends_with(' ') {
+        title
+    } else {
+        rest
+    };
+    Some(Block::Heading(level, title.trim().to_string()))
+}
+
+fn setext_level(line: &str) -> Option<usize> {
+    let trimmed = line.trim();
+    if leading_spaces(line) > 3 || trimmed.is_empty() {
+        return None;
+    }
+    if trimmed.chars().all(|c| c == '=') {
+        Some(1)
+    } else if trimmed.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+fn is_thematic_break(line: &str) -> bool {
+    let marks: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    leading_spaces(line) <= 3
+        && marks.len() >= 3
+        && ["-", "*", "_"]
+            .iter()
+            .any(|m| marks.chars().all(|c| c.to_string() == *m))
+}
+
+// Opening fence: three or more backticks or tildes. Returns the fence
+// character and length a closing fence has to match.
+fn fence_of(line: &str) -> Option<(char, usize)> {
+    if leading_spaces(line) > 3 {
+        return None;
+    }
+    let trimmed = line.trim_start();
+    let c = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.len() - trimmed.trim_start_matches(c).len();
+    (len >= 3).then_some((c, len))
+}
+
+// The code block with its fences, so the language tag is kept. An
+// unclosed fence runs to the end of the input.
+fn take_fenced(
+    lines: &[&str],
+    start: usize,
+    (c, len): (char, usize),
+) -> (String, usize) {
+    let mut end = start + 1;
+    while end < lines.len() {
+        let closes = fence_of(lines[end]).is_some_and(|(close, close_len)| {
+            close == c
+                && close_len >= len
+                && lines[end].trim().chars().all(|ch| ch == c)
+        });
+        end += 1;
+        if closes {
+            break;
+        }
+    }
+    (lines[start..end].join("\n"), end)
+}
+
+struct ListMarker {
+    ordered: bool,
+    // Column where the item's text starts; continuation lines are
+    // indented at least this far.
+    content: usize,
+}
+
+fn list_marker(line: &str) -> Option<ListMarker> {
+    let indent = leading_spaces(line);
+    if indent > 3 || is_thematic_break(line) {
+        return None;
+    }
+    let rest = &line[indent..];
+    let (ordered, marker_len) = marker_of(rest)?;
+    let after = &rest[marker_len..];
+    let spaces = leading_spaces(after);
+    if spaces == 0 && !after.trim().is_empty() {
+        return None;
+    }
+    // Text indented by more than four spaces is code inside the item.
+    let gap = if (1..=4).contains(&spaces) { spaces } else { 1 };
+    Some(ListMarker {
+        ordered,
+        content: indent + marker_len + gap,
+    })
+}
+
+// `-`, `*` or `+` for bullet lists, up to nine digits followed by `.` or
+// `)` for ordered ones. Returns whether the list is ordered and the
+// marker's length.
+fn marker_of(rest: &str) -> Option<(bool, usize)> {
+    let digits = rest.len()
+        - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    match rest.as_bytes().first()? {
+        b'-' | b'*' | b'+' => Some((false, 1)),
+        _ if (1..=9).contains(&digits) => {
+            matches!(rest.as_bytes().get(digits), Some(b'.' | b')'))
+                .then_some((true, digits + 1))
+        }
+        _ => None,
+    }
+}
+
+// Consecutive items of one list. Item lines are stripped of the marker
+// and indentation and parsed as blocks of their own.
+fn take_list(
+    lines: &[&str],
+    start: usize,
+    depth: usize,
+) -> (Vec<MdValue>, usize) {
+    let Some(first) = list_marker(lines[start]) else {
+        return (Vec::new(), start);
+    };
+    let mut items: Vec<Vec<String>> = Vec::new();
+    let mut content = first.content;
+    let mut i = start;
+    while i < lines.len() {
+        let line = lines[i];
+        if let Some(marker) = list_marker(line)
+            .filter(|m| m.ordered == first.ordered)
+            .filter(|_| leading_spaces(line) < content)
+        {
+            content = marker.content;
+            items.push(vec![strip_indent(line, content)]);
+        } else if let Some(text) =
+            continuation_of(lines, i, content, first.ordered)
+        {
+            if let Some(item) = items.last_mut() {
+                item.push(text);
+            }
+        } else {
+            break;
+        }
+        i += 1;
+    }
+    let values = items
+        .into_iter()
+        .map(|item| {
+            let item: Vec<&str> = item.iter().map(String::as_str).collect();
+            item_value(parse_blocks(&item, depth + 1))
+        })
+        .collect();
+    (values, i)
+}
+
+// Line `i` as part of the current item: blank, indented as item content
+// or a lazy continuation of the item's paragraph. `None` ends the list.
+fn continuation_of(
+    lines: &[&str],
+    i: usize,
+    content: usize,
+    ordered: bool,
+) -> Option<String> {
+    let line = lines[i];
+    if line.trim().is_empty() {
+        return continues_list(lines, i + 1, content, ordered)
+            .then(String::new);
+    }
+    if leading_spaces(line) >= content {
+        return Some(strip_indent(line, content));
+    }
+    let lazy = i > 0 && !lines[i - 1].trim().is_empty() && !starts_block(line);
+    lazy.then(|| line.trim().to_string())
+}
+
+// A blank line stays in the list when the next non-blank line is another
+// item or indented as item content.
+fn continues_list(
+    lines: &[&str],
+    from: usize,
+    content: usize,
+    ordered: bool,
+) -> bool {
+    lines[from..]
+        .iter()
+        .find(|l| !l.trim().is_empty())
+        .is_some_and(|next| {
+            leading_spaces(next) >= content
+                || list_marker(next).is_some_and(|m| m.ordered == ordered)
+        })
+}
+
+// Drop the first `width` columns: the marker and the indentation.
+fn strip_indent(line: &str, width: usize) -> String {
+    let skip = line
+        .char_indices()
+        .nth(width)
+        .map_or(line.len(), |(i, _)| i);
+    line[skip..].trim_end().to_string()
+}
+
+// An item is its text; one holding nested lists or other blocks becomes
+// an array of the text followed by those blocks.
+fn item_value(blocks: Vec<Block>) -> MdValue {
+    let mut values: Vec<MdValue> =
+        blocks.into_iter().map(Block::into_value).collect();
+    if values.len() > 1 {
+        return MdValue::List(values);
+    }
+    values.pop().unwrap_or_else(|| MdValue::Text(String::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::NodeKind;
+
+    // Render a node as compact JSON-like text with keys in arena order.
+    fn shape(arena: &JsonTreeArena, id: usize) -> String {
+        let node = &arena.nodes[id];
+        let children = &arena.children
+            [node.children_start..node.children_start + node.children_len];
+        match node.kind {
+            NodeKind::Object => {
+                let keys = &arena.obj_keys[node.obj_keys_start..];
+                let entries: Vec<String> = children
+                    .iter()
+                    .zip(keys)
+                    .map(|(&c, k)| format!("{k}: {}", shape(arena, c)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            NodeKind::Array => {
+                let items: Vec<String> =
+                    children.iter().map(|&c| shape(arena, c)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            NodeKind::String => node.string_value.clone().unwrap_or_default(),
+            _ => node.atomic_token.clone().unwrap_or_default(),
+        }
+    }
+
+    fn parse(text: &str) -> String {
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            build_markdown_tree_arena_from_bytes(text.as_bytes(), &cfg)
+                .unwrap();
+        shape(&arena, arena.root_id)
+    }
+
+    #[test]
+    fn headings_nest_sections() {
+        let doc = "Intro line\n\n# Title\n\nFirst\nparagraph.\n\n## Install\n\n```sh\ncargo install x\n```\n\n## Usage\n\n### Flags\n\nSecond\n=====\n";
+        assert_eq!(
+            parse(doc),
+            "{#content: [Intro line], Title: {#content: [First paragraph.], \
+             Install: {#content: [```sh\ncargo install x\n```]}, Usage: \
+             {Flags: {}}}, Second: {}}"
+        );
+    }
+
+    #[test]
+    fn list_items_keep_nested_lists() {
+        let doc = "- one\n- two\n  continued\n  - nested\n  - more\n\n1. first\n2) second\n";
+        assert_eq!(
+            parse(doc),
+            "{#content: [[one, [two continued, [nested, more]]], [first, \
+             second]]}"
+        );
+    }
+
+    #[test]
+    fn front_matter_is_read_as_yaml() {
+        let doc = "---\ntitle: Notes\ntags: [a, b]\n---\nBody text\n";
+        assert_eq!(
+            parse(doc),
+            "{#front_matter: {title: Notes, tags: [a, b]}, #content: \
+             [Body text]}"
+        );
+    }
+
+    #[test]
+    fn thematic_breaks_are_not_list_items() {
+        assert_eq!(parse("a\n\n---\n\n* * *\n\nb\n"), "{#content: [a, b]}");
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize as _;
+
+use crate::PriorityConfig;
+use crate::json_ingest::{
+    build_tree_arena_from_deserializer,
+    build_tree_arena_from_many_deserializers,
+};
+use crate::utils::tree_arena::JsonTreeArena;
+
+/// Build an arena from a single MessagePack value. The deserializer feeds
+/// the JSON builder directly, so arrays are sampled while they are read.
+/// Binary blobs become hex strings and ext values `[type, hex]` pairs.
+pub fn build_msgpack_tree_arena_from_bytes(
+    bytes: &[u8],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut rest = bytes;
+    let arena = build_tree_arena_from_deserializer(
+        &mut rmp_serde::Deserializer::new(&mut rest),
+        config,
+    )?;
+    ensure_consumed(rest)?;
+    Ok(arena)
+}
+
+pub fn build_msgpack_tree_arena_from_many(
+    inputs: &[(String, Vec<u8>)],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut readers: Vec<(&str, MsgpackReader<'_>)> = inputs
+        .iter()
+        .map(|(key, bytes)| {
+            (key.as_str(), rmp_serde::Deserializer::new(bytes.as_slice()))
+        })
+        .collect();
+    let arena = build_tree_arena_from_many_deserializers(
+        readers
+            .iter_mut()
+            .map(|(key, de)| ((*key).to_owned(), de))
+            .collect(),
+        config,
+    )?;
+    for (key, de) in &readers {
+        ensure_consumed(de.get_ref())
+            .with_context(|| format!("failed to parse {key}"))?;
+    }
+    Ok(arena)
+}
+
+type MsgpackReader<'a> =
+    rmp_serde::Deserializer<rmp_serde::decode::ReadReader<&'a [u8]>>;
+
+fn ensure_consumed(rest: &[u8]) -> Result<()> {
+    if !rest.is_empty() {
+        bail!(
+            "trailing data after MessagePack value ({} bytes)",
+            rest.len()
+        );
+    }
+    Ok(())
+}
+
+/// True when `bytes` hold exactly one well-formed MessagePack value.
+pub(crate) fn is_single_msgpack_value(bytes: &[u8]) -> bool {
+    let mut rest = bytes;
+    let parsed = serde::de::IgnoredAny::deserialize(
+        &mut rmp_serde::Deserializer::new(&mut rest),
+    );
+    parsed.is_ok() && rest.is_empty()
+}
+
+/// True when `prefix`, cut off from a longer input, reads as the start of
+/// a MessagePack value: decoding only fails for lack of data.
+pub(crate) fn is_msgpack_prefix(prefix: &[u8]) -> bool {
+    use rmp_serde::decode::Error;
+    let mut rest = prefix;
+    let parsed = serde::de::IgnoredAny::deserialize(
+        &mut rmp_serde::Deserializer::new(&mut rest),
+    );
+    match parsed {
+        Ok(_) => rest.is_empty(),
+        Err(Error::InvalidMarkerRead(e) | Error::InvalidDataRead(e)) => {
+            e.kind() == std::io::ErrorKind::UnexpectedEof
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::NodeKind;
+
+    #[test]
+    fn maps_with_integer_keys_and_binary_values() {
+        // {1: b"\x01\xff", "n": [1.5, nil]}
+        let bytes = vec![
+            0x82, 0x01, 0xc4, 0x02, 0x01, 0xff, 0xa1, b'n', 0x92, 0xcb, 0x3f,
+            0xf8, 0, 0, 0, 0, 0, 0, 0xc0,
+        ];
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena =
+            build_msgpack_tree_arena_from_bytes(&bytes, &cfg).expect("parse");
+        let root = &arena.nodes[arena.root_id];
+        assert_eq!(root.kind, NodeKind::Object);
+        assert_eq!(arena.obj_keys[root.obj_keys_start], "1");
+        let blob = &arena.nodes[arena.children[root.children_start]];
+        assert_eq!(blob.string_value.as_deref(), Some("01ff"));
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        assert!(
+            build_msgpack_tree_arena_from_bytes(&[0x01, 0x02], &cfg).is_err()
+        );
+        assert!(is_single_msgpack_value(&[0x92, 0x01, 0x02]));
+        assert!(!is_single_msgpack_value(&[0x92, 0x01]));
+        assert!(is_msgpack_prefix(&[0x92, 0x01]));
+    }
+}
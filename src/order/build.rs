@@ -1,11 +1,11 @@
 use anyhow::Result;
 use std::cmp::Reverse;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use unicode_segmentation::UnicodeSegmentation;
 
 use super::scoring::*;
 use super::types::*;
-use crate::utils::tree_arena::JsonTreeArena;
+use crate::utils::tree_arena::{BlockScalar, JsonTreeArena};
 
 #[derive(Clone)]
 struct Entry {
@@ -44,6 +44,18 @@ struct CommonChild {
     index_in_parent_array: Option<usize>,
 }
 
+// The arena's per-format side tables, re-keyed by PQ id.
+#[derive(Default)]
+struct SideTables {
+    repeated_keys: HashMap<usize, Vec<String>>,
+    tags: HashMap<usize, String>,
+    block_scalars: HashMap<usize, BlockScalar>,
+    outline_indents: HashMap<usize, String>,
+    anchors: HashMap<usize, String>,
+    datetimes: HashSet<usize>,
+    csv_headers: HashMap<usize, Vec<String>>,
+}
+
 struct Scope<'a> {
     arena: &'a JsonTreeArena,
     config: &'a PriorityConfig,
@@ -56,6 +68,7 @@ struct Scope<'a> {
     safety_cap: usize,
     object_type: &'a mut Vec<ObjectType>,
     index_in_parent_array: &'a mut Vec<Option<usize>>,
+    side: &'a mut SideTables,
 }
 
 impl<'a> Scope<'a> {
@@ -88,6 +101,14 @@ impl<'a> Scope<'a> {
             .array_len
             .unwrap_or(self.arena.nodes[arena_id].children_len);
         self.metrics[id].array_len = Some(array_len);
+        self.metrics[id].source_truncated =
+            self.arena.nodes[arena_id].truncated;
+        if let Some(indent) = self.arena.outline_indents.get(&arena_id) {
+            self.side.outline_indents.insert(id, indent.clone());
+        }
+        if let Some(header) = self.arena.csv_headers.get(&arena_id) {
+            self.side.csv_headers.insert(id, header.clone());
+        }
     }
 
     fn record_object_metrics(&mut self, id: usize, arena_id: usize) {
@@ -95,9 +116,20 @@ impl<'a> Scope<'a> {
             .object_len
             .unwrap_or(self.arena.nodes[arena_id].children_len);
         self.metrics[id].object_len = Some(object_len);
+        self.metrics[id].source_truncated =
+            self.arena.nodes[arena_id].truncated;
+        let repeated = self.arena.repeated_keys_of(arena_id);
+        if !repeated.is_empty() {
+            self.side
+                .repeated_keys
+                .insert(id, repeated.iter().map(|r| r.key.clone()).collect());
+        }
     }
 
-    fn record_string_metrics(&mut self, id: usize) {
+    fn record_string_metrics(&mut self, id: usize, arena_id: usize) {
+        if self.arena.datetimes.contains(&arena_id) {
+            self.side.datetimes.insert(id);
+        }
         let s = match &self.nodes[id] {
             RankedNode::SplittableLeaf { value, .. } => value.as_str(),
             _ => unreachable!(
@@ -122,9 +154,22 @@ impl<'a> Scope<'a> {
         match kind {
             NodeKind::Array => self.record_array_metrics(id, arena_id),
             NodeKind::Object => self.record_object_metrics(id, arena_id),
-            NodeKind::String => self.record_string_metrics(id),
+            NodeKind::String => self.record_string_metrics(id, arena_id),
             _ => {}
         }
+        self.record_yaml_style(id, arena_id);
+    }
+
+    fn record_yaml_style(&mut self, id: usize, arena_id: usize) {
+        if let Some(tag) = self.arena.tags.get(&arena_id) {
+            self.side.tags.insert(id, tag.clone());
+        }
+        if let Some(&block) = self.arena.block_scalars.get(&arena_id) {
+            self.side.block_scalars.insert(id, block);
+        }
+        if let Some(name) = self.arena.anchors.get(&arena_id) {
+            self.side.anchors.insert(id, name.clone());
+        }
     }
 
     fn array_extra_for_index(&self, i: usize, kept: usize) -> u128 {
@@ -149,6 +194,21 @@ impl<'a> Scope<'a> {
         }
     }
 
+    // Outline blocks keep their header line first and their nested lines
+    // in source order from the head (or the tail with `--tail`), after
+    // every line of the enclosing level.
+    fn outline_extra_for_index(&self, i: usize, kept: usize) -> u128 {
+        if i == 0 {
+            return 0;
+        }
+        let ii = if self.config.prefer_tail_arrays {
+            kept.saturating_sub(1).saturating_sub(i)
+        } else {
+            i - 1
+        } as u128;
+        OUTLINE_BODY_WEIGHT + ii * ii * ii * ARRAY_INDEX_CUBIC_WEIGHT
+    }
+
     #[allow(
         clippy::cognitive_complexity,
         reason = "Array child expansion mixes scoring, arena index mapping, and PQ wiring; splitting would obscure the flow"
@@ -168,8 +228,15 @@ impl<'a> Scope<'a> {
             } else {
                 i
             };
-            let extra: u128 = self.array_extra_for_index(i, kept);
-            let score = entry.score + ARRAY_CHILD_BASE_INCREMENT + extra;
+            let extra: u128 =
+                if self.arena.outline_indents.contains_key(&arena_id) {
+                    self.outline_extra_for_index(i, kept)
+                } else {
+                    self.array_extra_for_index(i, kept)
+                };
+            let score = entry
+                .score
+                .saturating_add(ARRAY_CHILD_BASE_INCREMENT + extra);
             let child_node = &self.arena.nodes[child_arena_id];
             let atomic = child_node.atomic_token.clone();
             self.push_child_common(
@@ -225,14 +292,16 @@ impl<'a> Scope<'a> {
             let child_arena_id = self.arena.children[node.children_start + i];
             items.push((key_idx, child_arena_id));
         }
-        items.sort_by(|a, b| {
-            let ka = &self.arena.obj_keys[a.0];
-            let kb = &self.arena.obj_keys[b.0];
-            match ka.cmp(kb) {
-                std::cmp::Ordering::Equal => a.0.cmp(&b.0),
-                other => other,
-            }
-        });
+        if !node.preserve_key_order {
+            items.sort_by(|a, b| {
+                let ka = &self.arena.obj_keys[a.0];
+                let kb = &self.arena.obj_keys[b.0];
+                match ka.cmp(kb) {
+                    std::cmp::Ordering::Equal => a.0.cmp(&b.0),
+                    other => other,
+                }
+            });
+        }
         for (key_idx, child_arena_id) in items {
             let child_kind = self.arena.nodes[child_arena_id].kind;
             let child_priority_index = *self.next_pq_id;
@@ -436,6 +505,7 @@ pub fn build_order(
         arena_index: Some(root_ar),
     }));
 
+    let mut side = SideTables::default();
     while let Some(Reverse(entry)) = heap.pop() {
         let mut scope = Scope {
             arena,
@@ -449,6 +519,7 @@ pub fn build_order(
             safety_cap: SAFETY_CAP,
             object_type: &mut object_type,
             index_in_parent_array: &mut index_in_parent_array,
+            side: &mut side,
         };
         scope.process_entry(&entry, &mut order);
         if next_pq_id >= SAFETY_CAP {
@@ -466,9 +537,39 @@ pub fn build_order(
         by_priority: order,
         total_nodes: total,
         object_type,
+        fileset_formats: fileset_formats_in_key_order(arena),
+        unread_files: 0,
+        repeated_keys: side.repeated_keys,
+        tags: side.tags,
+        block_scalars: side.block_scalars,
+        outline_indents: side.outline_indents,
+        anchors: side.anchors,
+        datetimes: side.datetimes,
+        csv_headers: side.csv_headers,
     })
 }
 
+// Object children are expanded in key order, so reorder the per-entry
+// fileset formats the same way to line them up with `children[ROOT]`.
+fn fileset_formats_in_key_order(
+    arena: &JsonTreeArena,
+) -> Vec<crate::format::Format> {
+    if arena.fileset_formats.is_empty() {
+        return Vec::new();
+    }
+    let root = &arena.nodes[arena.root_id];
+    let mut positions: Vec<usize> = (0..root.children_len).collect();
+    positions.sort_by(|&a, &b| {
+        let ka = &arena.obj_keys[root.obj_keys_start + a];
+        let kb = &arena.obj_keys[root.obj_keys_start + b];
+        ka.cmp(kb).then(a.cmp(&b))
+    });
+    positions
+        .into_iter()
+        .filter_map(|i| arena.fileset_formats.get(i).copied())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -11,6 +11,12 @@ pub(crate) const ARRAY_CHILD_BASE_INCREMENT: u128 = 1;
 /// The large multiplier ensures array index dominates depth ties.
 pub(crate) const ARRAY_INDEX_CUBIC_WEIGHT: u128 = 1_000_000_000_000;
 
+/// Penalty on the nested lines of a text outline block, larger than any
+/// array index term, so every line of a level is kept before the lines
+/// nested under it.
+pub(crate) const OUTLINE_BODY_WEIGHT: u128 =
+    1_000_000_000_000_000_000_000_000_000_000;
+
 /// Small base increment so object properties appear right after their object.
 pub(crate) const OBJECT_CHILD_BASE_INCREMENT: u128 = 1;
 
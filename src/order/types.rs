@@ -1,3 +1,7 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::utils::tree_arena::BlockScalar;
+
 #[derive(Clone, Debug)]
 pub struct PriorityConfig {
     pub max_string_graphemes: usize,
@@ -57,6 +61,15 @@ pub enum ArraySamplerStrategy {
     Tail,
 }
 
+/// Which occurrence of a key repeated within one object is kept.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DuplicateKeys {
+    KeepFirst,
+    // Keeps the last value at the position of the first occurrence, like
+    // `JSON.parse`.
+    KeepLast,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum RankedNode {
     Array {
@@ -124,6 +137,8 @@ pub struct NodeMetrics {
     pub object_len: Option<usize>,
     pub string_len: Option<usize>,
     pub string_truncated: bool,
+    // Container whose source was cut off (see `JsonTreeNode::truncated`).
+    pub source_truncated: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -140,6 +155,23 @@ pub struct PriorityOrder {
     pub by_priority: Vec<NodeId>, // ids sorted by ascending priority (PQ ids)
     pub total_nodes: usize,
     pub object_type: Vec<ObjectType>,
+    // For fileset roots: detected format of each top-level entry, in the
+    // same order as `children[ROOT_PQ_ID]`. Empty for non-fileset inputs.
+    pub fileset_formats: Vec<crate::format::Format>,
+    // For fileset roots: inputs the caller left unread, counted in the
+    // `N more files` summary along with the entries the budget omits.
+    pub unread_files: usize,
+    // Details only some formats carry, by PQ id (see `JsonTreeArena`):
+    // keys repeated within an object, YAML tags, block styles and reused
+    // anchors, the nested-line indentation of text outline blocks, TOML
+    // datetimes, and the header of CSV tables without data rows.
+    pub repeated_keys: HashMap<usize, Vec<String>>,
+    pub tags: HashMap<usize, String>,
+    pub block_scalars: HashMap<usize, BlockScalar>,
+    pub outline_indents: HashMap<usize, String>,
+    pub anchors: HashMap<usize, String>,
+    pub datetimes: HashSet<usize>,
+    pub csv_headers: HashMap<usize, Vec<String>>,
 }
 
 pub const ROOT_PQ_ID: usize = 0;
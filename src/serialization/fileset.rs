@@ -3,6 +3,14 @@ use crate::format::Format;
 use crate::order::{ObjectType, ROOT_PQ_ID};
 use crate::serialization::types::OutputTemplate;
 
+/// Fewest output bytes a fileset entry named `key` takes once it is shown:
+/// the name is printed in its section header, or as an object key when
+/// headers are off. Entries are shown in key order, so a caller can stop
+/// reading inputs once these lower bounds exceed the budget.
+pub fn fileset_entry_min_len(key: &str) -> usize {
+    key.len() + 1
+}
+
 impl<'a> RenderScope<'a> {
     pub(super) fn try_render_fileset_root(
         &mut self,
@@ -24,7 +32,7 @@ impl<'a> RenderScope<'a> {
             return out;
         };
         let mut kept = 0usize;
-        for &child_id in children_ids.iter() {
+        for (pos, &child_id) in children_ids.iter().enumerate() {
             if self.inclusion_flags[child_id.0] != self.render_set_id {
                 continue;
             }
@@ -36,7 +44,7 @@ impl<'a> RenderScope<'a> {
                 self.order.nodes[child_id.0].key_in_object().unwrap_or("");
             out.push_str(&self.fileset_header_line(depth, raw_key));
             let rendered =
-                self.fileset_render_child(child_id.0, depth, raw_key);
+                self.fileset_render_child(child_id.0, depth, pos, raw_key);
             out.push_str(&rendered);
         }
         let total = self
@@ -45,9 +53,10 @@ impl<'a> RenderScope<'a> {
             .get(ROOT_PQ_ID)
             .and_then(|m| m.object_len)
             .unwrap_or(children_ids.len());
-        if total > kept && !self.config.newline.is_empty() {
+        let omitted = total - kept + self.order.unread_files;
+        if omitted > 0 && !self.config.newline.is_empty() {
             self.fileset_push_section_gap(&mut out);
-            out.push_str(&self.fileset_summary_line(depth, total - kept));
+            out.push_str(&self.fileset_summary_line(depth, omitted));
         }
         out
     }
@@ -79,13 +88,37 @@ impl<'a> RenderScope<'a> {
         &mut self,
         child_id: usize,
         depth: usize,
+        pos: usize,
         raw_key: &str,
     ) -> String {
         if matches!(self.config.template, OutputTemplate::Auto) {
-            let fmt = Format::from_filename(raw_key);
+            // Prefer the format detected at ingest (it may come from content
+            // sniffing); fall back to the extension for other ingest paths.
+            let fmt = self
+                .order
+                .fileset_formats
+                .get(pos)
+                .copied()
+                .unwrap_or_else(|| Format::from_filename(raw_key));
             let template = match fmt {
                 Format::Yaml => OutputTemplate::Yaml,
-                Format::Json => match self.config.style {
+                Format::Toml
+                | Format::Ini
+                | Format::Dotenv
+                | Format::Properties => OutputTemplate::Toml,
+                Format::Csv => OutputTemplate::Csv,
+                Format::Tsv => OutputTemplate::Tsv,
+                Format::Json
+                | Format::Json5
+                | Format::Jsonl
+                | Format::Xml
+                | Format::Html
+                | Format::Markdown
+                | Format::Hcl
+                | Format::Logfmt
+                | Format::Msgpack
+                | Format::Cbor
+                | Format::Bson => match self.config.style {
                     crate::serialization::types::Style::Strict => {
                         OutputTemplate::Json
                     }
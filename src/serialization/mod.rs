@@ -1,7 +1,7 @@
 use crate::order::ObjectType;
 use crate::order::{NodeKind, PriorityOrder, ROOT_PQ_ID, RankedNode};
 pub mod color;
-mod fileset;
+pub mod fileset;
 pub mod output;
 pub mod templates;
 pub mod types;
@@ -97,7 +97,15 @@ impl<'a> RenderScope<'a> {
         out: &mut Out<'_>,
     ) {
         let config = self.config;
-        let (children_pairs, kept) = self.gather_array_children(id, depth);
+        let (mut children_pairs, mut kept) =
+            self.gather_array_children(id, depth);
+        self.drop_cut_rows(
+            id,
+            (depth, inline),
+            config.template,
+            &mut children_pairs,
+            &mut kept,
+        );
         let omitted = self.omitted_for(id, kept).unwrap_or(0);
         let ctx = ArrayCtx {
             children: children_pairs,
@@ -106,6 +114,10 @@ impl<'a> RenderScope<'a> {
             depth,
             inline_open: inline,
             omitted_at_start: config.prefer_tail_arrays,
+            source_truncated: self.order.metrics[id].source_truncated,
+            outline_indent: self.order.outline_indents.get(&id).cloned(),
+            table_path: self.toml_array_path(id, depth, config.template),
+            header: self.csv_header(id),
         };
         render_array(config.template, &ctx, out)
     }
@@ -122,19 +134,16 @@ impl<'a> RenderScope<'a> {
             out.push_str(&rendered);
             return;
         }
-        let (children_pairs, kept) = self.gather_object_children(id, depth);
+        let (mut children_pairs, mut kept) =
+            self.gather_object_children(id, depth);
+        self.drop_cut_rows(
+            id,
+            (depth, inline),
+            config.template,
+            &mut children_pairs,
+            &mut kept,
+        );
         let omitted = self.omitted_for(id, kept).unwrap_or(0);
-        let ctx = ObjectCtx {
-            children: children_pairs,
-            children_len: kept,
-            omitted,
-            depth,
-            inline_open: inline,
-            space: &config.space,
-            fileset_root: id == ROOT_PQ_ID
-                && self.order.object_type.get(id)
-                    == Some(&ObjectType::Fileset),
-        };
         // In non-fileset contexts, Auto uses JSON-family renderer based on style.
         let tmpl = match config.template {
             crate::OutputTemplate::Auto => match config.style {
@@ -150,14 +159,32 @@ impl<'a> RenderScope<'a> {
             },
             other => other,
         };
+        let (table_path, section_children) =
+            self.toml_object_layout(id, depth, tmpl);
+        let ctx = ObjectCtx {
+            children: children_pairs,
+            children_len: kept,
+            omitted,
+            depth,
+            inline_open: inline,
+            space: &config.space,
+            fileset_root: id == ROOT_PQ_ID
+                && self.order.object_type.get(id)
+                    == Some(&ObjectType::Fileset),
+            source_truncated: self.order.metrics[id].source_truncated,
+            repeated_keys: self
+                .order
+                .repeated_keys
+                .get(&id)
+                .map_or(&[], Vec::as_slice),
+            table_path,
+            section_children,
+        };
         render_object(tmpl, &ctx, out)
     }
 
-    #[allow(
-        clippy::cognitive_complexity,
-        reason = "Keeps string omission logic in one place for clarity."
-    )]
-    fn serialize_string(&mut self, id: usize) -> String {
+    // The kept part of a string leaf, ending in `…` when it was cut short.
+    fn kept_string_text(&self, id: usize) -> String {
         let kept = self.count_kept_children(id);
         let omitted = self.omitted_for(id, kept).unwrap_or(0);
         let full: &str = match &self.order.nodes[id] {
@@ -166,59 +193,73 @@ impl<'a> RenderScope<'a> {
                 "serialize_string called for non-string node: id={id}"
             ),
         };
-        if matches!(
-            self.config.template,
-            crate::serialization::types::OutputTemplate::Text
-        ) {
-            if omitted == 0 {
-                full.to_string()
-            } else {
-                let prefix = crate::utils::text::take_n_graphemes(full, kept);
-                format!("{prefix}…")
-            }
-        } else if omitted == 0 {
-            crate::utils::json::json_string(full)
+        if omitted == 0 {
+            full.to_string()
         } else {
             let prefix = crate::utils::text::take_n_graphemes(full, kept);
-            let truncated = format!("{prefix}…");
-            crate::utils::json::json_string(&truncated)
+            format!("{prefix}…")
         }
     }
 
-    #[allow(
-        clippy::cognitive_complexity,
-        reason = "Keeps string omission logic in one place for clarity."
-    )]
+    fn serialize_string(&mut self, id: usize) -> String {
+        self.serialize_string_with_template(id, self.config.template)
+    }
+
     fn serialize_string_with_template(
         &mut self,
         id: usize,
         template: crate::serialization::types::OutputTemplate,
     ) -> String {
-        let kept = self.count_kept_children(id);
-        let omitted = self.omitted_for(id, kept).unwrap_or(0);
-        let full: &str = match &self.order.nodes[id] {
-            RankedNode::SplittableLeaf { value, .. } => value.as_str(),
-            _ => unreachable!(
-                "serialize_string called for non-string node: id={id}"
-            ),
-        };
-        if matches!(
-            template,
-            crate::serialization::types::OutputTemplate::Text
-        ) {
-            if omitted == 0 {
-                full.to_string()
-            } else {
-                let prefix = crate::utils::text::take_n_graphemes(full, kept);
-                format!("{prefix}…")
-            }
-        } else if omitted == 0 {
-            crate::utils::json::json_string(full)
+        let text = self.kept_string_text(id);
+        let bare_datetime = template
+            == crate::serialization::types::OutputTemplate::Toml
+            && self.order.datetimes.contains(&id)
+            && !self.order.metrics[id].string_truncated;
+        if bare_datetime
+            || matches!(
+                template,
+                crate::serialization::types::OutputTemplate::Text
+            )
+        {
+            text
         } else {
-            let prefix = crate::utils::text::take_n_graphemes(full, kept);
-            let truncated = format!("{prefix}…");
-            crate::utils::json::json_string(&truncated)
+            crate::utils::json::json_string(&text)
+        }
+    }
+
+    // YAML output keeps the tag and block style of YAML input nodes, and
+    // the detailed style names the anchor of reused collections. The
+    // template finds them as a header line (`!Ref`, `|-`, `# &defaults`)
+    // before the value.
+    fn with_yaml_style(
+        &self,
+        id: usize,
+        depth: usize,
+        template: crate::serialization::types::OutputTemplate,
+        rendered: String,
+    ) -> String {
+        let tag = self.order.tags.get(&id);
+        let block_scalar = self.order.block_scalars.get(&id).copied();
+        let anchor = self.order.anchors.get(&id).filter(|_| {
+            self.config.style == crate::serialization::types::Style::Detailed
+        });
+        if template != crate::serialization::types::OutputTemplate::Yaml
+            || self.config.indent_unit.is_empty()
+            || (tag.is_none() && block_scalar.is_none() && anchor.is_none())
+        {
+            return rendered;
         }
+        let indent = self.config.indent_unit.repeat(depth);
+        let is_string =
+            matches!(self.order.nodes[id], RankedNode::SplittableLeaf { .. });
+        let block = block_scalar.filter(|_| is_string).and_then(|style| {
+            templates::yaml_block_scalar(
+                &self.kept_string_text(id),
+                style,
+                &indent,
+            )
+        });
+        yaml_header(tag, anchor, block.unwrap_or(rendered))
     }
 
     fn serialize_atomic(&self, id: usize) -> String {
@@ -242,6 +283,14 @@ impl<'a> RenderScope<'a> {
             RankedNode::Object { .. } => {
                 self.write_object(id, depth, inline, out)
             }
+            RankedNode::SplittableLeaf { .. } | RankedNode::AtomicLeaf { .. }
+                if depth == 0
+                    && self.config.template
+                        == crate::serialization::types::OutputTemplate::Toml =>
+            {
+                let s = self.render_node_to_string(id, depth, inline);
+                out.push_str(&s);
+            }
             RankedNode::SplittableLeaf { .. } => {
                 let s = self.serialize_string(id);
                 if matches!(
@@ -396,7 +445,7 @@ impl<'a> RenderScope<'a> {
         depth: usize,
         inline: bool,
     ) -> String {
-        match &self.order.nodes[id] {
+        let rendered = match &self.order.nodes[id] {
             RankedNode::Array { .. } => {
                 let mut s = String::new();
                 let mut ow = Out::new(
@@ -426,7 +475,15 @@ impl<'a> RenderScope<'a> {
             RankedNode::LeafPart { .. } => {
                 unreachable!("string part not rendered")
             }
-        }
+        };
+        let rendered =
+            self.with_yaml_style(id, depth, self.config.template, rendered);
+        self.with_toml_root(
+            id,
+            (depth, inline),
+            self.config.template,
+            rendered,
+        )
     }
 
     // Render helpers that apply a specific OutputTemplate instead of config.template.
@@ -440,8 +497,15 @@ impl<'a> RenderScope<'a> {
         template: crate::serialization::types::OutputTemplate,
     ) {
         let config = self.config;
-        let (children_pairs, kept) =
+        let (mut children_pairs, mut kept) =
             self.gather_array_children_with_template(id, depth, template);
+        self.drop_cut_rows(
+            id,
+            (depth, inline),
+            template,
+            &mut children_pairs,
+            &mut kept,
+        );
         let omitted = self.omitted_for(id, kept).unwrap_or(0);
         let ctx = ArrayCtx {
             children: children_pairs,
@@ -450,6 +514,10 @@ impl<'a> RenderScope<'a> {
             depth,
             inline_open: inline,
             omitted_at_start: config.prefer_tail_arrays,
+            source_truncated: self.order.metrics[id].source_truncated,
+            outline_indent: self.order.outline_indents.get(&id).cloned(),
+            table_path: self.toml_array_path(id, depth, template),
+            header: self.csv_header(id),
         };
         render_array(template, &ctx, out)
     }
@@ -463,9 +531,18 @@ impl<'a> RenderScope<'a> {
         template: crate::serialization::types::OutputTemplate,
     ) {
         let config = self.config;
-        let (children_pairs, kept) =
+        let (mut children_pairs, mut kept) =
             self.gather_object_children_with_template(id, depth, template);
+        self.drop_cut_rows(
+            id,
+            (depth, inline),
+            template,
+            &mut children_pairs,
+            &mut kept,
+        );
         let omitted = self.omitted_for(id, kept).unwrap_or(0);
+        let (table_path, section_children) =
+            self.toml_object_layout(id, depth, template);
         let ctx = ObjectCtx {
             children: children_pairs,
             children_len: kept,
@@ -476,6 +553,14 @@ impl<'a> RenderScope<'a> {
             fileset_root: id == ROOT_PQ_ID
                 && self.order.object_type.get(id)
                     == Some(&ObjectType::Fileset),
+            source_truncated: self.order.metrics[id].source_truncated,
+            repeated_keys: self
+                .order
+                .repeated_keys
+                .get(&id)
+                .map_or(&[], Vec::as_slice),
+            table_path,
+            section_children,
         };
         render_object(template, &ctx, out)
     }
@@ -488,7 +573,7 @@ impl<'a> RenderScope<'a> {
         inline: bool,
         template: crate::serialization::types::OutputTemplate,
     ) -> String {
-        match &self.order.nodes[id] {
+        let rendered = match &self.order.nodes[id] {
             RankedNode::Array { .. } => {
                 let mut s = String::new();
                 let mut ow = Out::new(
@@ -524,7 +609,29 @@ impl<'a> RenderScope<'a> {
             RankedNode::LeafPart { .. } => {
                 unreachable!("string part not rendered")
             }
+        };
+        let rendered = self.with_yaml_style(id, depth, template, rendered);
+        self.with_toml_root(id, (depth, inline), template, rendered)
+    }
+}
+
+// Nested collections start on the line after the tag and anchor name;
+// scalars and block scalars follow the tag on its line.
+fn yaml_header(
+    tag: Option<&String>,
+    anchor: Option<&String>,
+    value: String,
+) -> String {
+    let nested =
+        value.contains(['\n', '\r']) && !value.starts_with(['|', '>']);
+    match (tag, anchor) {
+        (Some(tag), Some(name)) if nested => {
+            format!("{tag} # &{name}\n{value}")
         }
+        (None, Some(name)) if nested => format!("# &{name}\n{value}"),
+        (Some(tag), _) if nested => format!("{tag}\n{value}"),
+        (Some(tag), _) => format!("{tag} {value}"),
+        (None, _) => value,
     }
 }
 
@@ -1261,6 +1368,10 @@ mod tests {
             depth: 0,
             inline_open: false,
             omitted_at_start: false,
+            source_truncated: false,
+            outline_indent: None,
+            table_path: None,
+            header: Vec::new(),
         }
     }
 
@@ -27,10 +27,10 @@ fn maybe_push_internal_gap<S: Style>(
     prev_index: Option<usize>,
     orig_index: usize,
 ) {
-    if let Some(prev) = prev_index {
-        if orig_index > prev.saturating_add(1) {
-            S::array_push_internal_gap(out, ctx, orig_index - prev - 1);
-        }
+    if let Some(prev) = prev_index
+        && orig_index > prev.saturating_add(1)
+    {
+        S::array_push_internal_gap(out, ctx, orig_index - prev - 1);
     }
 }
 
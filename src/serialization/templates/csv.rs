@@ -0,0 +1,411 @@
+use super::{ArrayCtx, ObjectCtx};
+use crate::order::RankedNode;
+use crate::serialization::RenderScope;
+use crate::serialization::output::Out;
+use crate::serialization::types::{OutputTemplate, Style};
+
+// Rows are rendered before the table that holds them, but only the table
+// knows the header (the union of all row keys). Rows therefore encode
+// their fields with control characters that cannot appear in rendered
+// output (strings and keys are JSON-escaped), and the table lays them
+// out into columns:
+//   RECORD <omitted> (FIELD <key> VALUE <token>)*   an object row
+//   LIST <omitted> (FIELD <token>)*                 an array row
+// Values nested deeper than a row become single-line JSON in their cell.
+const RECORD: char = '\u{1}';
+const FIELD: char = '\u{2}';
+const VALUE: char = '\u{3}';
+const LIST: char = '\u{4}';
+
+enum Row<'a> {
+    Record {
+        omitted: bool,
+        fields: Vec<(&'a str, &'a str)>,
+    },
+    List {
+        omitted: bool,
+        cells: Vec<&'a str>,
+    },
+    Scalar(&'a str),
+}
+
+fn split_omitted(body: &str) -> (bool, &str) {
+    let end = body.find(FIELD).unwrap_or(body.len());
+    let omitted = body[..end].parse::<usize>().is_ok_and(|n| n > 0);
+    (omitted, &body[end..])
+}
+
+fn parse_row(s: &str) -> Row<'_> {
+    if let Some(body) = s.strip_prefix(RECORD) {
+        let (omitted, rest) = split_omitted(body);
+        let fields = rest
+            .split(FIELD)
+            .skip(1)
+            .map(|f| f.split_once(VALUE).unwrap_or((f, "")))
+            .collect();
+        return Row::Record { omitted, fields };
+    }
+    if let Some(body) = s.strip_prefix(LIST) {
+        let (omitted, rest) = split_omitted(body);
+        let cells = rest.split(FIELD).skip(1).collect();
+        return Row::List { omitted, cells };
+    }
+    Row::Scalar(s)
+}
+
+fn is_row(depth: usize, inline_open: bool) -> bool {
+    depth == 1 && !inline_open
+}
+
+fn is_table(depth: usize, inline_open: bool) -> bool {
+    depth == 0 && !inline_open
+}
+
+// Cells hold the raw text of strings and the JSON token of everything
+// else; fields containing the delimiter, quotes or line breaks are quoted.
+fn cell_text(token: &str) -> String {
+    serde_json::from_str::<String>(token).unwrap_or_else(|_| token.to_string())
+}
+
+fn quote_cell(text: &str, delimiter: char) -> String {
+    if text.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// Header: every key in order of first appearance across the kept rows.
+fn collect_columns<'a>(rows: &[Row<'a>]) -> Vec<&'a str> {
+    let mut columns: Vec<&str> = Vec::new();
+    for row in rows {
+        if let Row::Record { fields, .. } = row {
+            for (key, _) in fields {
+                if !columns.contains(key) {
+                    columns.push(key);
+                }
+            }
+        }
+    }
+    columns
+}
+
+fn push_cells<'a, I>(out: &mut Out<'_>, delimiter: char, cells: I)
+where
+    I: IntoIterator<Item = Option<&'a str>>,
+{
+    for (i, cell) in cells.into_iter().enumerate() {
+        if i > 0 {
+            out.push_char(delimiter);
+        }
+        if let Some(token) = cell {
+            out.push_string_unquoted(&quote_cell(
+                &cell_text(token),
+                delimiter,
+            ));
+        }
+    }
+}
+
+fn push_header(out: &mut Out<'_>, delimiter: char, columns: &[&str]) {
+    for (i, key) in columns.iter().enumerate() {
+        if i > 0 {
+            out.push_char(delimiter);
+        }
+        out.push_key(&quote_cell(&cell_text(key), delimiter));
+    }
+    out.push_newline();
+}
+
+// Cells of a row missing from it; rows that lost fields to the budget
+// mark them with `…` unless the style is strict.
+fn missing_cell(out: &Out<'_>, omitted: bool) -> Option<&'static str> {
+    (omitted && out.style() != Style::Strict).then_some("\"…\"")
+}
+
+fn push_row(
+    out: &mut Out<'_>,
+    delimiter: char,
+    columns: &[&str],
+    row: &Row<'_>,
+) {
+    match row {
+        Row::Record { omitted, fields } => {
+            let missing = missing_cell(out, *omitted);
+            let cells: Vec<Option<&str>> = columns
+                .iter()
+                .map(|col| {
+                    fields
+                        .iter()
+                        .find(|(key, _)| key == col)
+                        .map(|(_, v)| *v)
+                        .or(missing)
+                })
+                .collect();
+            push_cells(out, delimiter, cells);
+        }
+        Row::List { omitted, cells } => {
+            let missing = missing_cell(out, *omitted);
+            let cells =
+                cells.iter().map(|c| Some(*c)).chain(missing.map(Some));
+            push_cells(out, delimiter, cells);
+        }
+        Row::Scalar(token) => push_cells(out, delimiter, [Some(*token)]),
+    }
+    out.push_newline();
+}
+
+fn push_omitted_rows(out: &mut Out<'_>, omitted: usize) {
+    if omitted == 0 {
+        return;
+    }
+    match out.style() {
+        Style::Strict => {}
+        Style::Default => {
+            out.push_omission();
+            out.push_newline();
+        }
+        Style::Detailed => {
+            out.push_omission();
+            let rows = if omitted == 1 { "row" } else { "rows" };
+            out.push_str(&format!(" {omitted} more {rows} "));
+            out.push_omission();
+            out.push_newline();
+        }
+    }
+}
+
+// A row whose fields were all cut by the budget carries no data; it is
+// counted with the omitted rows around it instead of printing `…` cells.
+fn is_hollow(row: &Row<'_>) -> bool {
+    matches!(row, Row::Record { omitted: true, fields } if fields.is_empty())
+        || matches!(row, Row::List { omitted: true, cells } if cells.is_empty())
+}
+
+// Kept rows carry their original indices, so omissions before, between
+// and after them are all marked where they happened.
+fn push_table(ctx: &ArrayCtx, out: &mut Out<'_>, delimiter: char) {
+    let rows: Vec<Row<'_>> = ctx
+        .children
+        .iter()
+        .map(|(_, (_, item))| parse_row(item))
+        .collect();
+    let mut columns = collect_columns(&rows);
+    if rows.is_empty() && ctx.omitted == 0 {
+        columns = ctx.header.iter().map(String::as_str).collect();
+    }
+    if !columns.is_empty() {
+        push_header(out, delimiter, &columns);
+    }
+    let mut next_index = 0usize;
+    let mut remaining = ctx.omitted;
+    let mut pending = 0usize;
+    for ((index, _), row) in ctx.children.iter().zip(rows.iter()) {
+        let gap = index.saturating_sub(next_index).min(remaining);
+        remaining -= gap;
+        pending += gap;
+        next_index = index + 1;
+        if is_hollow(row) {
+            pending += 1;
+            continue;
+        }
+        push_omitted_rows(out, pending);
+        pending = 0;
+        push_row(out, delimiter, &columns, row);
+    }
+    push_omitted_rows(out, pending + remaining);
+}
+
+fn push_inline_omission(out: &mut Out<'_>, omitted: usize, first: bool) {
+    if omitted > 0 && out.style() != Style::Strict {
+        if !first {
+            out.push_str(", ");
+        }
+        out.push_omission();
+    }
+}
+
+fn push_array_inline(ctx: &ArrayCtx, out: &mut Out<'_>) {
+    out.push_char('[');
+    for (i, (_, (_, item))) in ctx.children.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str(item);
+    }
+    push_inline_omission(out, ctx.omitted, ctx.children.is_empty());
+    out.push_char(']');
+}
+
+fn push_object_inline(ctx: &ObjectCtx<'_>, out: &mut Out<'_>) {
+    out.push_char('{');
+    for (i, (_, (k, v))) in ctx.children.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_key(k);
+        out.push_str(": ");
+        out.push_str(v);
+    }
+    push_inline_omission(out, ctx.omitted, ctx.children.is_empty());
+    out.push_char('}');
+}
+
+pub(super) fn render_array(
+    ctx: &ArrayCtx,
+    out: &mut Out<'_>,
+    delimiter: char,
+) {
+    if out.is_compact_mode() {
+        super::json::render_array(ctx, out);
+        return;
+    }
+    if is_table(ctx.depth, ctx.inline_open) {
+        push_table(ctx, out, delimiter);
+    } else if is_row(ctx.depth, ctx.inline_open) {
+        out.push_char(LIST);
+        out.push_str(&ctx.omitted.to_string());
+        for (_, (_, item)) in ctx.children.iter() {
+            out.push_char(FIELD);
+            out.push_str(item);
+        }
+    } else {
+        push_array_inline(ctx, out);
+    }
+}
+
+pub(super) fn render_object(
+    ctx: &ObjectCtx<'_>,
+    out: &mut Out<'_>,
+    delimiter: char,
+) {
+    if out.is_compact_mode() {
+        super::json::render_object(ctx, out);
+        return;
+    }
+    let table = is_table(ctx.depth, ctx.inline_open);
+    if !table && !is_row(ctx.depth, ctx.inline_open) {
+        push_object_inline(ctx, out);
+        return;
+    }
+    let mut record = String::new();
+    record.push(RECORD);
+    record.push_str(&ctx.omitted.to_string());
+    for (_, (k, v)) in ctx.children.iter() {
+        record.push(FIELD);
+        record.push_str(k);
+        record.push(VALUE);
+        record.push_str(v);
+    }
+    if table {
+        // A lone object is a table with a single row.
+        let row = parse_row(&record);
+        let columns = collect_columns(std::slice::from_ref(&row));
+        push_header(out, delimiter, &columns);
+        push_row(out, delimiter, &columns, &row);
+    } else {
+        out.push_str(&record);
+    }
+}
+
+impl RenderScope<'_> {
+    pub(in crate::serialization) fn csv_header(
+        &self,
+        id: usize,
+    ) -> Vec<String> {
+        self.order
+            .csv_headers
+            .get(&id)
+            .map_or_else(Vec::new, |columns| {
+                columns
+                    .iter()
+                    .map(|c| crate::utils::json::json_string(c))
+                    .collect()
+            })
+    }
+
+    // Whether the kept part of `id` is all of it: no string cut short and
+    // no array items or object fields left out at any depth.
+    fn is_kept_whole(&self, id: usize) -> bool {
+        if matches!(self.order.nodes[id], RankedNode::AtomicLeaf { .. }) {
+            return true;
+        }
+        if self.omitted_for(id, self.count_kept_children(id)).is_some() {
+            return false;
+        }
+        matches!(self.order.nodes[id], RankedNode::SplittableLeaf { .. })
+            || self.order.children.get(id).is_none_or(|kids| {
+                kids.iter().all(|c| {
+                    self.inclusion_flags[c.0] != self.render_set_id
+                        || self.is_kept_whole(c.0)
+                })
+            })
+    }
+
+    // Strict CSV has no way to mark a value the budget cut short, so a
+    // table leaves out the rows (or, for a lone record, the fields) that
+    // are not kept whole; they count as omitted like rows beyond the
+    // budget.
+    pub(in crate::serialization) fn drop_cut_rows<T>(
+        &self,
+        id: usize,
+        (depth, inline): (usize, bool),
+        template: OutputTemplate,
+        children: &mut Vec<(usize, T)>,
+        kept: &mut usize,
+    ) {
+        if depth != 0
+            || inline
+            || self.config.style != Style::Strict
+            || !matches!(template, OutputTemplate::Csv | OutputTemplate::Tsv)
+        {
+            return;
+        }
+        let Some(children_ids) = self.order.children.get(id) else {
+            return;
+        };
+        let mut cut: Vec<usize> = children_ids
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| {
+                self.inclusion_flags[c.0] == self.render_set_id
+                    && !self.is_kept_whole(c.0)
+            })
+            .map(|(i, c)| {
+                self.order
+                    .index_in_parent_array
+                    .get(c.0)
+                    .and_then(|o| *o)
+                    .unwrap_or(i)
+            })
+            .collect();
+        cut.sort_unstable();
+        children.retain(|(i, _)| cut.binary_search(i).is_err());
+        *kept -= cut.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn quotes_only_cells_that_need_it() {
+        assert_eq!(quote_cell("plain", ','), "plain");
+        assert_eq!(quote_cell("a,b", ','), "\"a,b\"");
+        assert_eq!(quote_cell("a,b", '\t'), "a,b");
+        assert_eq!(quote_cell("say \"hi\"", ','), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote_cell("two\nlines", ','), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn cells_decode_json_strings_only() {
+        assert_eq!(cell_text("\"x\\ty\""), "x\ty");
+        assert_eq!(cell_text("42"), "42");
+        assert_eq!(cell_text("{\"a\": 1}"), "{\"a\": 1}");
+    }
+}
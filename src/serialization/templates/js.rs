@@ -3,9 +3,39 @@ use super::core::{
 };
 use super::{ArrayCtx, ObjectCtx};
 use crate::serialization::output::Out;
+use crate::utils::json::json_string;
 
 struct Js;
 
+const TRUNCATED_COMMENT: &str = "/* truncated input */";
+
+// Containers the source broke off inside get a closing note on their own
+// line; empty ones carry it inline like an omission count.
+fn push_truncated(out: &mut Out<'_>, depth: usize, truncated: bool) {
+    if truncated {
+        out.push_indent(depth + 1);
+        out.push_comment(TRUNCATED_COMMENT);
+        out.push_newline();
+    }
+}
+
+fn push_truncated_inline(out: &mut Out<'_>, space: &str, truncated: bool) {
+    if truncated {
+        out.push_str(space);
+        out.push_comment(TRUNCATED_COMMENT);
+        out.push_str(space);
+    }
+}
+
+// Objects whose source repeated keys name them in a closing note.
+fn duplicate_keys_comment(keys: &[String]) -> Option<String> {
+    if keys.is_empty() {
+        return None;
+    }
+    let names: Vec<String> = keys.iter().map(|k| json_string(k)).collect();
+    Some(format!("/* duplicate keys: {} */", names.join(", ")))
+}
+
 impl Style for Js {
     fn array_push_omitted(out: &mut Out<'_>, ctx: &ArrayCtx) {
         if ctx.omitted > 0 {
@@ -47,6 +77,7 @@ fn render_array_empty(ctx: &ArrayCtx, out: &mut Out<'_>) {
         out.push_comment(format!("/* {} more items */", ctx.omitted));
         out.push_str(" ");
     }
+    push_truncated_inline(out, " ", ctx.source_truncated);
     out.push_char(']');
 }
 
@@ -59,6 +90,7 @@ fn render_array_nonempty(ctx: &ArrayCtx, out: &mut Out<'_>) {
         if !ctx.omitted_at_start {
             <Js as Style>::array_push_omitted(o, ctx);
         }
+        push_truncated(o, ctx.depth, ctx.source_truncated);
     });
 }
 
@@ -85,6 +117,12 @@ fn render_object_empty(ctx: &ObjectCtx<'_>, out: &mut Out<'_>) {
         out.push_comment(format!("/* {} more {label} */", ctx.omitted));
         out.push_str(ctx.space);
     }
+    push_truncated_inline(out, ctx.space, ctx.source_truncated);
+    if let Some(comment) = duplicate_keys_comment(ctx.repeated_keys) {
+        out.push_str(ctx.space);
+        out.push_comment(comment);
+        out.push_str(ctx.space);
+    }
     out.push_char('}');
 }
 
@@ -92,6 +130,12 @@ fn render_object_nonempty(ctx: &ObjectCtx<'_>, out: &mut Out<'_>) {
     wrap_block(out, ctx.depth, ctx.inline_open, '{', '}', |o| {
         push_object_items(o, ctx);
         <Js as Style>::object_push_omitted(o, ctx);
+        push_truncated(o, ctx.depth, ctx.source_truncated);
+        if let Some(comment) = duplicate_keys_comment(ctx.repeated_keys) {
+            o.push_indent(ctx.depth + 1);
+            o.push_comment(comment);
+            o.push_newline();
+        }
     });
 }
 
@@ -3,11 +3,15 @@ use crate::order::NodeKind;
 use crate::serialization::output::Out;
 
 mod core;
+mod csv;
 mod js;
 mod json;
 mod pseudo;
 mod text;
+mod toml;
 mod yaml;
+
+pub(crate) use yaml::block_scalar as yaml_block_scalar;
 //
 pub struct ArrayCtx {
     pub children: Vec<(usize, (NodeKind, String))>,
@@ -16,6 +20,14 @@ pub struct ArrayCtx {
     pub depth: usize,
     pub inline_open: bool,
     pub omitted_at_start: bool,
+    pub source_truncated: bool,
+    // Text outline blocks: indentation of the nested lines.
+    pub outline_indent: Option<String>,
+    // TOML: key path of an array of tables written as `[[path]]` sections;
+    // None when the array is written inline.
+    pub table_path: Option<String>,
+    // CSV/TSV: JSON-quoted column names of a table without data rows.
+    pub header: Vec<String>,
 }
 
 pub struct ObjectCtx<'a> {
@@ -26,6 +38,15 @@ pub struct ObjectCtx<'a> {
     pub inline_open: bool,
     pub space: &'a str,
     pub fileset_root: bool,
+    pub source_truncated: bool,
+    // Keys that occurred more than once in the source object.
+    pub repeated_keys: &'a [String],
+    // TOML: key path of a table written as a `[path]` section (empty for
+    // the document root); None when the table is written inline.
+    pub table_path: Option<String>,
+    // TOML: for each of `children`, whether it is written as a section of
+    // its own after this table's `key = value` entries.
+    pub section_children: Vec<bool>,
 }
 
 // Color helpers facade so templates don't pass flags around.
@@ -43,6 +64,9 @@ pub fn render_array(
         }
         OutputTemplate::Js => js::render_array(ctx, out),
         OutputTemplate::Yaml => yaml::render_array(ctx, out),
+        OutputTemplate::Toml => toml::render_array(ctx, out),
+        OutputTemplate::Csv => csv::render_array(ctx, out, ','),
+        OutputTemplate::Tsv => csv::render_array(ctx, out, '\t'),
         OutputTemplate::Text => text::render_array(ctx, out),
     }
 }
@@ -59,6 +83,9 @@ pub fn render_object(
         }
         OutputTemplate::Js => js::render_object(ctx, out),
         OutputTemplate::Yaml => yaml::render_object(ctx, out),
+        OutputTemplate::Toml => toml::render_object(ctx, out),
+        OutputTemplate::Csv => csv::render_object(ctx, out, ','),
+        OutputTemplate::Tsv => csv::render_object(ctx, out, '\t'),
         OutputTemplate::Text => text::render_object(ctx, out),
     }
 }
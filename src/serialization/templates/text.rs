@@ -1,14 +1,19 @@
 use super::{ArrayCtx, ObjectCtx};
+use crate::order::NodeKind;
 use crate::serialization::output::Out;
 
-fn push_text_omission_line(out: &mut Out<'_>, omitted: usize) {
+// Omitted lines of an outline block are marked at the indentation of the
+// lines they stand for.
+fn push_text_omission_line(out: &mut Out<'_>, omitted: usize, indent: &str) {
     match out.style() {
         crate::serialization::types::Style::Strict => {}
         crate::serialization::types::Style::Default => {
+            out.push_str(indent);
             out.push_omission();
             out.push_newline();
         }
         crate::serialization::types::Style::Detailed => {
+            out.push_str(indent);
             out.push_omission();
             out.push_str(" ");
             out.push_str(&format!("{omitted} more lines "));
@@ -18,18 +23,35 @@ fn push_text_omission_line(out: &mut Out<'_>, omitted: usize) {
     }
 }
 
+fn push_line(out: &mut Out<'_>, kind: NodeKind, item: &str) {
+    out.push_str(item);
+    // Nested outline blocks already end each of their lines.
+    if kind != NodeKind::Array {
+        out.push_newline();
+    }
+}
+
 pub(super) fn render_array(ctx: &ArrayCtx, out: &mut Out<'_>) {
     // For text, arrays are treated as raw lines of text. We do not emit
     // brackets or indentation; we only write lines and optional omission markers.
+    let indent = ctx.outline_indent.as_deref().unwrap_or("");
+    // An outline block's header line always comes before its omissions.
+    let header = usize::from(
+        ctx.outline_indent.is_some()
+            && ctx.children.first().is_some_and(|(i, _)| *i == 0),
+    );
+    let (header, items) = ctx.children.split_at(header);
+    for (_, (kind, item)) in header {
+        push_line(out, *kind, item);
+    }
     if ctx.omitted_at_start && ctx.omitted > 0 {
-        push_text_omission_line(out, ctx.omitted);
+        push_text_omission_line(out, ctx.omitted, indent);
     }
-    for (_, (_, item)) in ctx.children.iter() {
-        out.push_str(item);
-        out.push_newline();
+    for (_, (kind, item)) in items {
+        push_line(out, *kind, item);
     }
     if !ctx.omitted_at_start && ctx.omitted > 0 {
-        push_text_omission_line(out, ctx.omitted);
+        push_text_omission_line(out, ctx.omitted, indent);
     }
 }
 
@@ -0,0 +1,409 @@
+use super::{ArrayCtx, ObjectCtx};
+use crate::order::RankedNode;
+use crate::serialization::RenderScope;
+use crate::serialization::output::Out;
+use crate::serialization::types::{OutputTemplate, Style};
+
+// Children are rendered before their parent, but in TOML the parent
+// decides how a nested table is written (`[a.b]` section vs inline table).
+// The serializer therefore works out each table's key path up front (see
+// `table_path` on the contexts): tables with a path render as sections
+// with absolute `[path]` headers, and their parent appends those blocks
+// after its own `key = value` entries. Everything else renders inline.
+
+// Key that a document root which is not a table is written under, since
+// a TOML document is always a table (`value = [1, 2, 3]`).
+const ROOT_VALUE_KEY: &str = "value";
+
+// The JSON token of null, which has no TOML spelling.
+const NULL: &str = "null";
+
+fn is_document_root(depth: usize, inline_open: bool) -> bool {
+    depth == 0 && !inline_open
+}
+
+fn decode_json_string(quoted: &str) -> Option<String> {
+    serde_json::from_str::<String>(quoted).ok()
+}
+
+fn is_bare_key(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+// Keys arrive JSON-quoted; bare keys drop the quotes, everything else
+// stays a basic string (JSON escapes are valid TOML escapes).
+fn toml_key_text(quoted: &str) -> String {
+    match decode_json_string(quoted) {
+        Some(raw) if is_bare_key(&raw) => raw,
+        _ => quoted.to_string(),
+    }
+}
+
+// The `[section]` path of key `raw_key` within the table at `parent`.
+fn join_path(parent: &str, raw_key: &str) -> String {
+    let key = toml_key_text(&crate::utils::json::json_string(raw_key));
+    if parent.is_empty() {
+        key
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+fn push_value_token(out: &mut Out<'_>, v: &str) {
+    if v.starts_with('"') {
+        out.push_string_literal(v);
+    } else {
+        out.push_str(v);
+    }
+}
+
+fn push_comment_line(out: &mut Out<'_>, detailed: String) {
+    match out.style() {
+        Style::Strict => {}
+        Style::Default => {
+            out.push_comment("# …");
+            out.push_newline();
+        }
+        Style::Detailed => {
+            out.push_comment(detailed);
+            out.push_newline();
+        }
+    }
+}
+
+// A `key = value` line. Null has no TOML spelling: the entry becomes a
+// comment, or is left out in the strict style. Reports whether a line was
+// written.
+fn push_entry(out: &mut Out<'_>, key: &str, space: &str, token: &str) -> bool {
+    if token == NULL {
+        if out.style() == Style::Strict {
+            return false;
+        }
+        out.push_comment(format!("# {key}{space}={space}{NULL}"));
+        out.push_newline();
+        return true;
+    }
+    out.push_key(key);
+    out.push_str(&format!("{space}={space}"));
+    push_value_token(out, token);
+    out.push_newline();
+    true
+}
+
+// A document root that is not a table, written as the value of
+// `ROOT_VALUE_KEY`.
+fn push_root_value(out: &mut Out<'_>, token: &str) {
+    push_entry(out, ROOT_VALUE_KEY, " ", token);
+}
+
+// Comma-separated items of an inline array or table.
+struct InlineList {
+    first: bool,
+}
+
+impl InlineList {
+    fn new() -> Self {
+        Self { first: true }
+    }
+
+    fn next(&mut self, out: &mut Out<'_>) {
+        if !self.first {
+            out.push_str(", ");
+        }
+        self.first = false;
+    }
+
+    fn omission(&mut self, out: &mut Out<'_>, omitted: usize) {
+        if omitted > 0 && out.style() != Style::Strict {
+            self.next(out);
+            out.push_omission();
+        }
+    }
+}
+
+fn push_section_gap(out: &mut Out<'_>, started: &mut bool) {
+    if *started {
+        out.push_newline();
+    }
+    *started = true;
+}
+
+fn is_section(ctx: &ObjectCtx<'_>, i: usize) -> bool {
+    ctx.section_children.get(i).copied().unwrap_or(false)
+}
+
+// A table only needs its own `[header]` when it has something to put
+// under it; tables holding nothing but sub-tables stay implicit.
+fn needs_header(ctx: &ObjectCtx<'_>) -> bool {
+    ctx.omitted > 0
+        || ctx.children.is_empty()
+        || (0..ctx.children.len()).any(|i| !is_section(ctx, i))
+}
+
+// The body of a table: a `[path]` header when the table is the value of a
+// key (array elements get `[[path]]` from the array instead), its plain
+// `key = value` entries, then the sections of its nested tables.
+fn push_object_block(ctx: &ObjectCtx<'_>, out: &mut Out<'_>, path: &str) {
+    if ctx.inline_open && needs_header(ctx) {
+        out.push_key(&format!("[{path}]"));
+        out.push_newline();
+    }
+    let mut started = push_plain_entries(ctx, out);
+    if ctx.omitted > 0 {
+        let label = if ctx.fileset_root {
+            "files"
+        } else {
+            "properties"
+        };
+        push_comment_line(out, format!("# {} more {label}", ctx.omitted));
+        started = true;
+    }
+    let sections = ctx
+        .children
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| is_section(ctx, *i));
+    for (_, (_, (_, block))) in sections {
+        push_section_gap(out, &mut started);
+        out.push_str(block);
+    }
+}
+
+// Write the `key = value` entries of a table. Also reports whether
+// anything was written.
+fn push_plain_entries(ctx: &ObjectCtx<'_>, out: &mut Out<'_>) -> bool {
+    let mut started = false;
+    for (i, (_, (k, v))) in ctx.children.iter().enumerate() {
+        if !is_section(ctx, i) {
+            started |= push_entry(out, &toml_key_text(k), ctx.space, v);
+        }
+    }
+    started
+}
+
+fn push_object_inline(ctx: &ObjectCtx<'_>, out: &mut Out<'_>) {
+    if ctx.children_len == 0 && ctx.omitted == 0 {
+        out.push_str("{}");
+        return;
+    }
+    out.push_str("{ ");
+    let mut list = InlineList::new();
+    for (_, (k, v)) in ctx.children.iter().filter(|(_, (_, v))| v != NULL) {
+        list.next(out);
+        out.push_key(&toml_key_text(k));
+        out.push_str(&format!("{}={}", ctx.space, ctx.space));
+        push_value_token(out, v);
+    }
+    list.omission(out, ctx.omitted);
+    out.push_str(" }");
+}
+
+fn push_array_inline(ctx: &ArrayCtx, out: &mut Out<'_>) {
+    out.push_char('[');
+    let mut list = InlineList::new();
+    if ctx.omitted_at_start {
+        list.omission(out, ctx.omitted);
+    }
+    for (_, (_, item)) in ctx.children.iter().filter(|(_, (_, v))| v != NULL) {
+        list.next(out);
+        push_value_token(out, item);
+    }
+    if !ctx.omitted_at_start {
+        list.omission(out, ctx.omitted);
+    }
+    out.push_char(']');
+}
+
+// Left-out elements of an array of tables get one summary line of their
+// own, apart from the kept elements, so it does not read as part of the
+// neighbouring element.
+fn push_tables_omitted(
+    ctx: &ArrayCtx,
+    out: &mut Out<'_>,
+    path: &str,
+    started: &mut bool,
+) {
+    let line = match out.style() {
+        Style::Strict => return,
+        Style::Default => format!("# [[{path}]] …"),
+        Style::Detailed => format!("# {} more [[{path}]]", ctx.omitted),
+    };
+    push_section_gap(out, started);
+    out.push_comment(line);
+    out.push_newline();
+}
+
+// One `[[path]]` section per kept element of an array of tables.
+fn push_tables_block(ctx: &ArrayCtx, out: &mut Out<'_>, path: &str) {
+    let mut started = false;
+    let omitted = ctx.omitted > 0;
+    if omitted && ctx.omitted_at_start {
+        push_tables_omitted(ctx, out, path, &mut started);
+    }
+    for (_, (_, block)) in ctx.children.iter() {
+        push_section_gap(out, &mut started);
+        out.push_key(&format!("[[{path}]]"));
+        out.push_newline();
+        out.push_str(block);
+    }
+    if omitted && !ctx.omitted_at_start {
+        push_tables_omitted(ctx, out, path, &mut started);
+    }
+}
+
+pub(super) fn render_array(ctx: &ArrayCtx, out: &mut Out<'_>) {
+    if out.is_compact_mode() {
+        super::json::render_array(ctx, out);
+        return;
+    }
+    if let Some(path) = &ctx.table_path {
+        push_tables_block(ctx, out, path);
+    } else if is_document_root(ctx.depth, ctx.inline_open) {
+        out.push_key(ROOT_VALUE_KEY);
+        out.push_str(" = ");
+        push_array_inline(ctx, out);
+        out.push_newline();
+    } else {
+        push_array_inline(ctx, out);
+    }
+}
+
+pub(super) fn render_object(ctx: &ObjectCtx<'_>, out: &mut Out<'_>) {
+    if out.is_compact_mode() {
+        super::json::render_object(ctx, out);
+        return;
+    }
+    match &ctx.table_path {
+        Some(path) => push_object_block(ctx, out, path),
+        None => push_object_inline(ctx, out),
+    }
+}
+
+// The serializer's side of the section layout described at the top.
+impl RenderScope<'_> {
+    fn is_array_of_tables(&self, id: usize) -> bool {
+        let Some(kids) = self.order.children.get(id) else {
+            return false;
+        };
+        let mut kept = kids
+            .iter()
+            .filter(|c| self.inclusion_flags[c.0] == self.render_set_id)
+            .peekable();
+        matches!(self.order.nodes[id], RankedNode::Array { .. })
+            && kept.peek().is_some()
+            && kept.all(|c| {
+                matches!(self.order.nodes[c.0], RankedNode::Object { .. })
+            })
+    }
+
+    // TOML: the key path of `id` when it is written as a `[path]` section
+    // or as the `[[path]]` elements of an array of tables; None when it is
+    // written inline.
+    fn toml_table_path(&self, id: usize, depth: usize) -> Option<String> {
+        if depth == 0 {
+            return self.toml_root_path(id);
+        }
+        if !self.is_toml_table(id) {
+            return None;
+        }
+        let parent = self.order.parent.get(id).copied().flatten()?.0;
+        let parent_path = self.toml_table_path(parent, depth - 1)?;
+        match self.order.nodes[parent] {
+            RankedNode::Object { .. } => Some(join_path(
+                &parent_path,
+                self.order.nodes[id].key_in_object().unwrap_or(""),
+            )),
+            // Elements of an array of tables share the array's path.
+            _ => Some(parent_path),
+        }
+    }
+
+    // Tables and arrays of tables can be written as sections.
+    fn is_toml_table(&self, id: usize) -> bool {
+        matches!(self.order.nodes[id], RankedNode::Object { .. })
+            || self.is_array_of_tables(id)
+    }
+
+    // Document roots are sections with an empty path, and a root array of
+    // tables takes the key non-table roots are written under.
+    fn toml_root_path(&self, id: usize) -> Option<String> {
+        if matches!(self.order.nodes[id], RankedNode::Object { .. }) {
+            Some(String::new())
+        } else {
+            self.is_array_of_tables(id)
+                .then(|| ROOT_VALUE_KEY.to_string())
+        }
+    }
+
+    // TOML: the section path of object `id` and, when it has one, which of
+    // its kept children are sections of their own.
+    pub(in crate::serialization) fn toml_object_layout(
+        &self,
+        id: usize,
+        depth: usize,
+        template: OutputTemplate,
+    ) -> (Option<String>, Vec<bool>) {
+        if template != OutputTemplate::Toml {
+            return (None, Vec::new());
+        }
+        let Some(path) = self.toml_table_path(id, depth) else {
+            return (None, Vec::new());
+        };
+        let sections = self
+            .order
+            .children
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter(|c| self.inclusion_flags[c.0] == self.render_set_id)
+            .map(|c| self.is_toml_table(c.0))
+            .collect();
+        (Some(path), sections)
+    }
+
+    pub(in crate::serialization) fn toml_array_path(
+        &self,
+        id: usize,
+        depth: usize,
+        template: OutputTemplate,
+    ) -> Option<String> {
+        (template == OutputTemplate::Toml)
+            .then(|| self.toml_table_path(id, depth))
+            .flatten()
+    }
+
+    // A TOML document is a table, so a scalar document root is written as
+    // the value of a key.
+    pub(in crate::serialization) fn with_toml_root(
+        &self,
+        id: usize,
+        (depth, inline): (usize, bool),
+        template: OutputTemplate,
+        rendered: String,
+    ) -> String {
+        let is_leaf = matches!(
+            self.order.nodes[id],
+            RankedNode::SplittableLeaf { .. } | RankedNode::AtomicLeaf { .. }
+        );
+        if template != OutputTemplate::Toml || depth != 0 || inline || !is_leaf
+        {
+            return rendered;
+        }
+        let mut s = String::new();
+        let mut ow = Out::new(
+            &mut s,
+            &self.config.newline,
+            &self.config.indent_unit,
+            self.config.color_enabled,
+            self.config.style,
+        );
+        // Compact output falls back to JSON, like TOML tables do.
+        if ow.is_compact_mode() {
+            return rendered;
+        }
+        push_root_value(&mut ow, &rendered);
+        s
+    }
+}
@@ -1,6 +1,7 @@
 use super::ArrayCtx;
 use super::ObjectCtx;
 use crate::serialization::output::Out;
+use crate::utils::tree_arena::BlockScalar;
 use serde_json;
 
 fn has_newline(s: &str) -> bool {
@@ -15,8 +16,21 @@ fn push_yaml_array_item(out: &mut Out<'_>, depth: usize, item: &str) {
         out.push_newline();
         return;
     }
+    if let Some((header, body)) = split_header(item) {
+        out.push_indent(depth);
+        out.push_str("- ");
+        push_header(out, header);
+        out.push_newline();
+        if is_block_header(header) {
+            push_block(out, body);
+            return;
+        }
+        push_aligned_lines(out, depth, leading_ws(body), body.lines());
+        return;
+    }
     // Multi-line item: print first logical line after "- ", and align
-    // all following lines under the first character after the dash.
+    // all following lines under the first character after the dash,
+    // keeping their indentation relative to the first line.
     let mut iter = item.lines();
     if let Some(first) = iter.next() {
         out.push_indent(depth);
@@ -24,15 +38,139 @@ fn push_yaml_array_item(out: &mut Out<'_>, depth: usize, item: &str) {
         out.push_str(first.trim_start());
         out.push_newline();
     }
-    for rest in iter {
-        out.push_indent(depth);
-        out.push_str("  ");
-        out.push_str(rest.trim_start());
+    push_aligned_lines(out, depth, leading_ws(item), iter);
+}
+
+fn leading_ws(s: &str) -> &str {
+    &s[..s.len() - s.trim_start().len()]
+}
+
+fn push_aligned_lines<'a>(
+    out: &mut Out<'_>,
+    depth: usize,
+    base: &str,
+    lines: impl Iterator<Item = &'a str>,
+) {
+    for line in lines {
+        let rest =
+            line.strip_prefix(base).unwrap_or_else(|| line.trim_start());
+        if !rest.is_empty() {
+            out.push_indent(depth);
+            out.push_str("  ");
+            out.push_str(rest);
+        }
         out.push_newline();
     }
 }
 
+// Values of tagged nodes, block scalars and named anchors start with a
+// header line (`!Ref`, `|-`, `!Sub |`, `# &defaults`) that belongs after
+// the key or dash.
+fn split_header(v: &str) -> Option<(&str, &str)> {
+    if !v.starts_with(['!', '|', '>', '#']) {
+        return None;
+    }
+    let (header, body) = v.split_once('\n')?;
+    Some((header.trim_end_matches('\r'), body))
+}
+
+// The anchor name is a comment after the tag, if any.
+fn push_header(out: &mut Out<'_>, header: &str) {
+    match header.find('#') {
+        Some(at) => {
+            out.push_str(&header[..at]);
+            out.push_comment(&header[at..]);
+        }
+        None => out.push_str(header),
+    }
+}
+
+fn is_block_header(header: &str) -> bool {
+    header
+        .rsplit(' ')
+        .next()
+        .is_some_and(|last| last.starts_with(['|', '>']))
+}
+
+// Block scalar lines are already indented for their depth.
+fn push_block(out: &mut Out<'_>, body: &str) {
+    out.push_str(body);
+    if !body.ends_with('\n') {
+        out.push_newline();
+    }
+}
+
+/// Render `text` as a literal or folded block scalar whose lines are
+/// indented by `indent`. Returns `None` for text a block cannot hold
+/// as-is (leading spaces, control characters, several trailing newlines),
+/// which then stays a quoted string.
+pub(crate) fn block_scalar(
+    text: &str,
+    style: BlockScalar,
+    indent: &str,
+) -> Option<String> {
+    // Text without line breaks reads better as a plain or quoted scalar.
+    if !text.contains('\n') {
+        return None;
+    }
+    let (body, chomp) = match text.strip_suffix('\n') {
+        Some(body) => (body, ""),
+        None => (text, "-"),
+    };
+    let indicator = match style {
+        BlockScalar::Literal => '|',
+        BlockScalar::Folded => '>',
+    };
+    let lines = block_lines(body, style)?
+        .into_iter()
+        .map(|line| match line {
+            "" => String::new(),
+            _ => format!("{indent}{line}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(format!("{indicator}{chomp}\n{lines}"))
+}
+
+fn block_lines(body: &str, style: BlockScalar) -> Option<Vec<&str>> {
+    let representable = !body.is_empty()
+        && !body.ends_with('\n')
+        && !body.starts_with([' ', '\n'])
+        && !body
+            .chars()
+            .any(|c| c.is_control() && c != '\n' && c != '\t');
+    if !representable {
+        return None;
+    }
+    if style == BlockScalar::Literal {
+        return Some(body.split('\n').collect());
+    }
+    let mut lines: Vec<&str> = Vec::new();
+    let mut prev_blank = true;
+    for line in body.split('\n') {
+        // More indented lines are not folded, and a single line break
+        // folds into a space unless a blank line follows it.
+        if line.starts_with([' ', '\t']) {
+            return None;
+        }
+        if !line.is_empty() && !prev_blank {
+            lines.push("");
+        }
+        prev_blank = line.is_empty();
+        lines.push(line);
+    }
+    Some(lines)
+}
+
 fn push_yaml_scalar(out: &mut Out<'_>, token: &str) {
+    if token.starts_with('!')
+        && let Some((tag, value)) = token.split_once(' ')
+    {
+        out.push_str(tag);
+        out.push_str(" ");
+        push_yaml_scalar(out, value);
+        return;
+    }
     if let Some(raw) = decode_json_string(token) {
         if !needs_quotes_yaml_value(&raw) {
             out.push_string_unquoted(&raw);
@@ -136,10 +274,10 @@ fn needs_quotes_yaml_key(s: &str) -> bool {
 }
 
 fn yaml_key_text_from_json_quoted(k: &str) -> String {
-    if let Some(raw) = decode_json_string(k) {
-        if !needs_quotes_yaml_key(&raw) {
-            return raw;
-        }
+    if let Some(raw) = decode_json_string(k)
+        && !needs_quotes_yaml_key(&raw)
+    {
+        return raw;
     }
     k.to_string()
 }
@@ -151,6 +289,11 @@ fn push_object_kv(out: &mut Out<'_>, depth: usize, key_text: &str, v: &str) {
         out.push_str(": ");
         push_yaml_scalar(out, v);
         out.push_newline();
+    } else if let Some((header, body)) = split_header(v) {
+        out.push_str(": ");
+        push_header(out, header);
+        out.push_newline();
+        push_block(out, body);
     } else {
         // Multiline value: print key and start block on next line.
         out.push_str(":");
@@ -220,6 +363,8 @@ fn needs_quotes_yaml_value(s: &str) -> bool {
         || yaml_value_is_reserved(s)
         || yaml_value_looks_numeric(s)
         || yaml_value_has_disallowed_punct(s)
+        || s.starts_with("- ")
+        || s.starts_with(['"', '\'', '`'])
 }
 
 fn push_object_omitted(ctx: &ObjectCtx<'_>, out: &mut Out<'_>) {
@@ -267,3 +412,18 @@ pub(super) fn render_object(ctx: &ObjectCtx<'_>, out: &mut Out<'_>) {
     }
     render_object_pretty(ctx, out);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_scalars_round_trip_their_text() {
+        let literal = block_scalar("a\n  b\n", BlockScalar::Literal, "  ");
+        assert_eq!(literal.as_deref(), Some("|\n  a\n    b"));
+        let folded = block_scalar("a b\nc", BlockScalar::Folded, "  ");
+        assert_eq!(folded.as_deref(), Some(">-\n  a b\n\n  c"));
+        assert_eq!(block_scalar(" lead", BlockScalar::Literal, "  "), None);
+        assert_eq!(block_scalar("a\n\n", BlockScalar::Literal, "  "), None);
+    }
+}
@@ -5,6 +5,9 @@ pub enum OutputTemplate {
     Pseudo,
     Js,
     Yaml,
+    Toml,
+    Csv,
+    Tsv,
     Text,
 }
 
@@ -1,3 +1,5 @@
+mod outline;
+
 use anyhow::Result;
 use std::borrow::Cow;
 
@@ -71,6 +73,38 @@ impl TextArenaBuilder {
         id
     }
 
+    // Array of already pushed nodes; only the first `array_cap` are kept.
+    fn push_lines_array(
+        &mut self,
+        items: Vec<usize>,
+        outline_indent: Option<String>,
+    ) -> usize {
+        let id = self.push_default();
+        let total = items.len();
+        let kept = total.min(self.array_cap);
+        let children_start = self.arena.children.len();
+        self.arena.children.extend(items.into_iter().take(kept));
+        let n = &mut self.arena.nodes[id];
+        n.kind = NodeKind::Array;
+        n.children_start = children_start;
+        n.children_len = kept;
+        n.array_len = Some(total);
+        if let Some(indent) = outline_indent {
+            self.arena.outline_indents.insert(id, indent);
+        }
+        id
+    }
+
+    fn push_outline(&mut self, bytes: &[u8]) -> usize {
+        let lossy = String::from_utf8_lossy(bytes);
+        let norm = normalize_newlines(&lossy);
+        let mut outline = outline::OutlineBuilder::new(self);
+        for line in norm.split_terminator('\n') {
+            outline.push_line(line);
+        }
+        outline.finish()
+    }
+
     fn push_object_root(
         &mut self,
         keys: Vec<String>,
@@ -94,15 +128,14 @@ impl TextArenaBuilder {
 }
 
 #[allow(
-    clippy::needless_pass_by_value,
     clippy::unnecessary_wraps,
     reason = "Signature matches other ingest helpers and trait expectations"
 )]
 pub fn build_text_tree_arena_from_bytes(
-    bytes: Vec<u8>,
+    bytes: &[u8],
     config: &PriorityConfig,
 ) -> Result<JsonTreeArena> {
-    let lossy = String::from_utf8_lossy(&bytes);
+    let lossy = String::from_utf8_lossy(bytes);
     let norm = normalize_newlines(&lossy);
     // split_terminator keeps no trailing empty item for trailing newline
     let lines_vec: Vec<String> = norm
@@ -147,6 +180,45 @@ pub fn build_text_tree_arena_from_many(
     Ok(a)
 }
 
+/// Build a tree from the indentation and bracket nesting of text, for
+/// source code, stack traces and outlines. See `outline` for the shape.
+#[allow(
+    clippy::unnecessary_wraps,
+    reason = "Signature matches other ingest helpers and trait expectations"
+)]
+pub fn build_outline_tree_arena_from_bytes(
+    bytes: &[u8],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut b = TextArenaBuilder::new(config.array_max_items);
+    let root_id = b.push_outline(bytes);
+    let mut a = b.finish();
+    a.root_id = root_id;
+    Ok(a)
+}
+
+#[allow(
+    clippy::unnecessary_wraps,
+    reason = "Signature matches other ingest helpers and trait expectations"
+)]
+pub fn build_outline_tree_arena_from_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut b = TextArenaBuilder::new(config.array_max_items);
+    let mut keys: Vec<String> = Vec::with_capacity(inputs.len());
+    let mut children_ids: Vec<usize> = Vec::with_capacity(inputs.len());
+    for (key, bytes) in inputs {
+        children_ids.push(b.push_outline(&bytes));
+        keys.push(key);
+    }
+    let root_id = b.push_object_root(keys, children_ids);
+    let mut a = b.finish();
+    a.root_id = root_id;
+    a.is_fileset = true;
+    Ok(a)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -173,7 +245,14 @@ mod tests {
     fn text_roundtrip_basic() {
         let (cfg, prio) = cfg_text();
         let input = b"a\nb\nc".to_vec();
-        let out = headson_text(input, &cfg, &prio, 100).unwrap();
+        let out = headson_text(
+            input,
+            &crate::IngestOptions::default(),
+            &cfg,
+            &prio,
+            100,
+        )
+        .unwrap();
         assert_eq!(out, "a\nb\nc\n");
     }
 
@@ -186,7 +265,14 @@ mod tests {
             .join("\n");
         // Budget small so only some lines fit
         cfg.style = Style::Default;
-        let out = headson_text(input.into_bytes(), &cfg, &prio, 20).unwrap();
+        let out = headson_text(
+            input.into_bytes(),
+            &crate::IngestOptions::default(),
+            &cfg,
+            &prio,
+            20,
+        )
+        .unwrap();
         assert!(out.contains("…\n"));
     }
 }
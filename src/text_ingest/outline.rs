@@ -0,0 +1,363 @@
+//! Outline of indented text: every line that has more deeply indented
+//! lines below it, or that ends by opening a bracket, becomes a block
+//! holding its header line followed by the nested lines. Blocks are arrays
+//! whose first item is the header, so previews keep top-level lines and
+//! elide bodies first.
+
+use super::TextArenaBuilder;
+
+// Nesting deeper than this is kept as flat lines in the innermost block.
+const MAX_DEPTH: usize = 64;
+// Columns a tab advances the indentation by.
+const TAB_WIDTH: usize = 4;
+
+struct Frame {
+    // Arena id of the header line; `None` for the root.
+    header: Option<usize>,
+    indent: usize,
+    children: Vec<usize>,
+    // Indentation of the last line added, while it is still a plain line
+    // that could become the header of a new block.
+    last_line_indent: Option<usize>,
+    // Leading whitespace of the first nested line, for omission markers.
+    body_indent: Option<String>,
+    // Bracket depth before the header for blocks opened by a bracket; they
+    // close when the depth drops back instead of on dedent.
+    brackets_before: Option<usize>,
+}
+
+impl Frame {
+    fn new(header: Option<usize>, indent: usize) -> Self {
+        Self {
+            header,
+            indent,
+            children: Vec::new(),
+            last_line_indent: None,
+            body_indent: None,
+            brackets_before: None,
+        }
+    }
+
+    // Whether a line indented by `width` ends this block.
+    fn is_closed_by(&self, width: usize) -> bool {
+        match self.brackets_before {
+            _ if self.header.is_none() => false,
+            Some(_) => width < self.indent,
+            None => width <= self.indent,
+        }
+    }
+}
+
+pub(super) struct OutlineBuilder<'a> {
+    b: &'a mut TextArenaBuilder,
+    stack: Vec<Frame>,
+    // Blank lines wait for the next line so they end up in its block.
+    blanks: Vec<usize>,
+    depth: usize,
+}
+
+impl<'a> OutlineBuilder<'a> {
+    pub(super) fn new(b: &'a mut TextArenaBuilder) -> Self {
+        Self {
+            b,
+            stack: vec![Frame::new(None, 0)],
+            blanks: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    pub(super) fn push_line(&mut self, line: &str) {
+        if line.trim().is_empty() {
+            let id = self.b.push_string(line.to_string());
+            self.blanks.push(id);
+            return;
+        }
+        let width = indent_width(line);
+        let brackets = scan_brackets(line);
+        let opens_block = brackets.opens_block();
+        if opens_block {
+            // `} else {` closes the previous block before opening its own.
+            self.depth = self.depth.saturating_sub(brackets.lead);
+            self.close_bracket_blocks();
+        }
+        while self.top().is_closed_by(width) {
+            self.pop_frame();
+        }
+        self.nest_under_previous_line(width);
+        let id = self.b.push_string(line.to_string());
+        self.add_child(id, width, line);
+        if opens_block {
+            let before = self.depth;
+            self.depth += brackets.opens - brackets.closes;
+            self.open_block(Some(before));
+        } else {
+            self.depth = (self.depth + brackets.opens)
+                .saturating_sub(brackets.lead + brackets.closes);
+            self.close_bracket_blocks();
+        }
+    }
+
+    /// Close every open block and return the id of the root array.
+    pub(super) fn finish(mut self) -> usize {
+        let blanks = std::mem::take(&mut self.blanks);
+        self.top_mut().children.extend(blanks);
+        while self.stack.len() > 1 {
+            self.pop_frame();
+        }
+        let root = self.stack.pop().map(|f| f.children).unwrap_or_default();
+        self.b.push_lines_array(root, None)
+    }
+
+    fn top(&self) -> &Frame {
+        &self.stack[self.stack.len() - 1]
+    }
+
+    fn top_mut(&mut self) -> &mut Frame {
+        let last = self.stack.len() - 1;
+        &mut self.stack[last]
+    }
+
+    // A line indented deeper than the plain line before it turns that line
+    // into the header of a new block.
+    fn nest_under_previous_line(&mut self, width: usize) {
+        if self
+            .top()
+            .last_line_indent
+            .is_some_and(|previous| width > previous)
+        {
+            self.open_block(None);
+        }
+    }
+
+    fn add_child(&mut self, id: usize, width: usize, line: &str) {
+        let blanks = std::mem::take(&mut self.blanks);
+        let frame = self.top_mut();
+        frame.children.extend(blanks);
+        frame.children.push(id);
+        frame.last_line_indent = Some(width);
+        if frame.header.is_some() && frame.body_indent.is_none() {
+            let ws = line.len() - line.trim_start().len();
+            frame.body_indent = Some(line[..ws].to_string());
+        }
+    }
+
+    // Make the last line of the current block the header of a new one.
+    fn open_block(&mut self, brackets_before: Option<usize>) {
+        if self.stack.len() >= MAX_DEPTH {
+            return;
+        }
+        let frame = self.top_mut();
+        let (Some(indent), Some(header)) =
+            (frame.last_line_indent.take(), frame.children.pop())
+        else {
+            return;
+        };
+        let mut block = Frame::new(Some(header), indent);
+        block.brackets_before = brackets_before;
+        let blanks = std::mem::take(&mut self.blanks);
+        block.children.extend(blanks);
+        self.stack.push(block);
+    }
+
+    // Close bracket-opened blocks whose closing bracket has been seen,
+    // along with everything nested in them.
+    fn close_bracket_blocks(&mut self) {
+        let depth = self.depth;
+        if let Some(pos) = self
+            .stack
+            .iter()
+            .position(|f| f.brackets_before.is_some_and(|b| b >= depth))
+        {
+            while self.stack.len() > pos {
+                self.pop_frame();
+            }
+        }
+    }
+
+    fn pop_frame(&mut self) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        let Some(header) = frame.header else {
+            return;
+        };
+        let mut children = Vec::with_capacity(frame.children.len() + 1);
+        children.push(header);
+        children.extend(frame.children);
+        let id = self.b.push_lines_array(children, frame.body_indent);
+        let parent = self.top_mut();
+        parent.children.push(id);
+        parent.last_line_indent = None;
+    }
+}
+
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Brackets {
+    // Closing brackets that start the line, as in `}` or `} else {`.
+    lead: usize,
+    opens: usize,
+    closes: usize,
+    // The line's last code character opens a bracket.
+    ends_open: bool,
+}
+
+impl Brackets {
+    fn count(&mut self, c: char, leading: bool) {
+        match c {
+            '}' | ']' | ')' if leading => self.lead += 1,
+            '}' | ']' | ')' => self.closes += 1,
+            '{' | '[' | '(' => self.opens += 1,
+            _ => {}
+        }
+    }
+
+    fn opens_block(&self) -> bool {
+        self.ends_open && self.opens > self.closes
+    }
+}
+
+// Count brackets outside of string literals and line comments.
+fn scan_brackets(line: &str) -> Brackets {
+    let code = line.trim();
+    let mut scan = Scan {
+        brackets: Brackets::default(),
+        leading: true,
+        prev: ' ',
+    };
+    let mut chars = code.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if scan.starts_comment(c, chars.peek().map(|&(_, n)| n)) {
+            break;
+        }
+        if scan.starts_string(c, &code[i..]) {
+            skip_string(&mut chars, c);
+        } else {
+            scan.brackets.count(c, scan.leading);
+        }
+        scan.advance(c);
+    }
+    scan.brackets
+}
+
+struct Scan {
+    brackets: Brackets,
+    // Only closing brackets and whitespace have been seen so far.
+    leading: bool,
+    prev: char,
+}
+
+impl Scan {
+    fn starts_comment(&self, c: char, next: Option<char>) -> bool {
+        (c == '/' && next == Some('/'))
+            || (c == '#' && self.prev.is_whitespace())
+    }
+
+    fn starts_string(&self, c: char, rest: &str) -> bool {
+        match c {
+            '"' | '`' => true,
+            '\'' => !self.prev.is_alphanumeric() && quotes_string(rest),
+            _ => false,
+        }
+    }
+
+    fn advance(&mut self, c: char) {
+        self.leading &= matches!(c, '}' | ']' | ')') || c.is_whitespace();
+        self.brackets.ends_open = matches!(c, '{' | '[' | '(')
+            || (self.brackets.ends_open && c == ' ');
+        self.prev = c;
+    }
+}
+
+// A single quote starts a string when it is closed on the same line and
+// not followed by a word character, unlike apostrophes and Rust lifetimes.
+fn quotes_string(rest: &str) -> bool {
+    let body = &rest[1..];
+    body.find('\'').is_some_and(|end| {
+        !body[end + 1..]
+            .chars()
+            .next()
+            .is_some_and(char::is_alphanumeric)
+    })
+}
+
+fn skip_string(
+    chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>,
+    quote: char,
+) {
+    let mut escaped = false;
+    for (_, c) in chars.by_ref() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if c == quote => return,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tree_arena::JsonTreeArena;
+
+    // Render the block structure as nested lists of trimmed lines.
+    fn shape(arena: &JsonTreeArena, id: usize) -> String {
+        let node = &arena.nodes[id];
+        if let Some(line) = &node.string_value {
+            return line.trim().to_string();
+        }
+        let items: Vec<String> = arena.children
+            [node.children_start..node.children_start + node.children_len]
+            .iter()
+            .map(|&c| shape(arena, c))
+            .collect();
+        format!("[{}]", items.join(", "))
+    }
+
+    fn outline(text: &str) -> String {
+        let mut b = TextArenaBuilder::new(usize::MAX);
+        let root = b.push_outline(text.as_bytes());
+        shape(&b.finish(), root)
+    }
+
+    #[test]
+    fn indentation_nests_lines_under_their_header() {
+        let text =
+            "class A:\n    def f(self):\n        pass\n\n    x = 1\ny = 2\n";
+        assert_eq!(
+            outline(text),
+            "[[class A:, [def f(self):, pass], , x = 1], y = 2]"
+        );
+    }
+
+    #[test]
+    fn brackets_open_and_close_blocks() {
+        let text = "if a {\n  b();\n} else {\n  c();\n}\nd();\n";
+        assert_eq!(
+            outline(text),
+            "[[if a {, b();], [} else {, c();, }], d();]"
+        );
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn brackets_ignore_strings_and_comments() {
+        assert!(scan_brackets("fn main() {").opens_block());
+        assert!(scan_brackets("} else {").opens_block());
+        assert_eq!(scan_brackets("} else {").lead, 1);
+        assert!(!scan_brackets("let s = \"{\";").opens_block());
+        assert!(!scan_brackets("x = 1 # see (").opens_block());
+        assert!(scan_brackets("fn f<'a>(x: &'a str) {").opens_block());
+        assert!(!scan_brackets("print('(')").opens_block());
+    }
+}
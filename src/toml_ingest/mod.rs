@@ -0,0 +1,157 @@
+use anyhow::{Context, Result, anyhow};
+use toml::Value;
+
+use crate::PriorityConfig;
+use crate::json_ingest::{
+    build_tree_arena_from_deserializer,
+    build_tree_arena_from_many_deserializers,
+};
+use crate::utils::tree_arena::JsonTreeArena;
+
+/// Map key under which a datetime is handed to `visit_map` with its TOML
+/// spelling as the value.
+pub(crate) const DATETIME_TOKEN: &str = "$headson::private::Datetime";
+
+pub fn build_toml_tree_arena_from_bytes(
+    bytes: &[u8],
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let doc = parse_toml_document(bytes)?;
+    let mut arena = build_tree_arena_from_deserializer(doc, config)?;
+    arena.preserve_source_key_order();
+    Ok(arena)
+}
+
+pub fn build_toml_tree_arena_from_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut docs: Vec<(String, Value)> = Vec::with_capacity(inputs.len());
+    for (key, bytes) in inputs {
+        let doc = parse_toml_document(&bytes)
+            .with_context(|| format!("failed to parse {key}"))?;
+        docs.push((key, doc));
+    }
+    let mut arena = build_tree_arena_from_many_deserializers(docs, config)?;
+    arena.preserve_source_key_order();
+    Ok(arena)
+}
+
+fn parse_toml_document(bytes: &[u8]) -> Result<Value> {
+    let s = std::str::from_utf8(bytes)
+        .map_err(|_| anyhow!("input is not valid UTF-8 text"))?;
+    let table: toml::Table = s.parse()?;
+    let mut doc = Value::Table(table);
+    normalize_scalars(&mut doc);
+    Ok(doc)
+}
+
+// JSON has no NaN/inf, so they become strings carrying their TOML
+// spelling. The tree has no datetime kind: datetimes reach the builder as
+// one-entry `DATETIME_TOKEN` tables and become strings that TOML output
+// writes back bare. Everything else maps one to one.
+fn normalize_scalars(v: &mut Value) {
+    match v {
+        Value::Datetime(dt) => {
+            let mut token = toml::Table::new();
+            token.insert(DATETIME_TOKEN.into(), Value::String(dt.to_string()));
+            *v = Value::Table(token);
+        }
+        Value::Float(f) if f.is_nan() => *v = Value::String("nan".into()),
+        Value::Float(f) if f.is_infinite() => {
+            let sign = if f.is_sign_negative() { "-" } else { "" };
+            *v = Value::String(format!("{sign}inf"));
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize_scalars),
+        Value::Table(t) => {
+            t.iter_mut().for_each(|(_, item)| normalize_scalars(item))
+        }
+        Value::String(_)
+        | Value::Integer(_)
+        | Value::Float(_)
+        | Value::Boolean(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::NodeKind;
+
+    fn child<'a>(
+        arena: &'a JsonTreeArena,
+        id: usize,
+        key: &str,
+    ) -> &'a crate::utils::tree_arena::JsonTreeNode {
+        let n = &arena.nodes[id];
+        let keys = &arena.obj_keys[n.obj_keys_start..][..n.obj_keys_len];
+        let i = keys.iter().position(|k| k == key).expect("key present");
+        &arena.nodes[arena.children[n.children_start + i]]
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "test performs several assertions succinctly"
+    )]
+    fn tables_and_arrays_of_tables_map_to_objects_and_arrays() {
+        let src = "title = \"x\"\n\n[package]\nname = \"demo\"\n\n\
+                   [[bin]]\nname = \"a\"\n\n[[bin]]\nname = \"b\"\n";
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena = build_toml_tree_arena_from_bytes(src.as_bytes(), &cfg)
+            .expect("parse toml");
+        let root = &arena.nodes[arena.root_id];
+        assert_eq!(root.kind, NodeKind::Object);
+        assert_eq!(root.object_len, Some(3));
+        let package = child(&arena, arena.root_id, "package");
+        assert_eq!(package.kind, NodeKind::Object);
+        let bin = child(&arena, arena.root_id, "bin");
+        assert_eq!(bin.kind, NodeKind::Array);
+        assert_eq!(bin.array_len, Some(2));
+    }
+
+    #[test]
+    fn keys_keep_their_source_order() {
+        let src = "[package]\nname = \"x\"\nedition = \"2024\"\n\n\
+                   [dependencies]\nzstd = \"1\"\nanyhow = \"1\"\n";
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena = build_toml_tree_arena_from_bytes(src.as_bytes(), &cfg)
+            .expect("parse toml");
+        let keys = |id: usize| {
+            let n = &arena.nodes[id];
+            arena.obj_keys[n.obj_keys_start..][..n.obj_keys_len].to_vec()
+        };
+        let root = &arena.nodes[arena.root_id];
+        assert!(root.preserve_key_order);
+        assert_eq!(keys(arena.root_id), ["package", "dependencies"]);
+        let package = arena.children[root.children_start];
+        assert_eq!(keys(package), ["name", "edition"]);
+        let dependencies = arena.children[root.children_start + 1];
+        assert_eq!(keys(dependencies), ["zstd", "anyhow"]);
+    }
+
+    #[test]
+    fn datetimes_and_special_floats_become_strings() {
+        let src = "when = 1979-05-27T07:32:00Z\nratio = nan\nbig = -inf\n";
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena = build_toml_tree_arena_from_bytes(src.as_bytes(), &cfg)
+            .expect("parse toml");
+        let when = child(&arena, arena.root_id, "when");
+        assert_eq!(when.string_value.as_deref(), Some("1979-05-27T07:32:00Z"));
+        let root = &arena.nodes[arena.root_id];
+        assert!(
+            arena
+                .datetimes
+                .contains(&arena.children[root.children_start])
+        );
+        let big = child(&arena, arena.root_id, "big");
+        assert_eq!(big.string_value.as_deref(), Some("-inf"));
+    }
+
+    #[test]
+    fn invalid_toml_is_an_error() {
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let err = build_toml_tree_arena_from_bytes(b"a = \n", &cfg);
+        assert!(err.is_err());
+    }
+}
@@ -12,3 +12,15 @@ pub(crate) fn take_n_graphemes(s: &str, n: usize) -> String {
     }
     out
 }
+
+/// Lowercase hex rendering of raw bytes, used for binary payloads that have
+/// no tree representation of their own.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write as _;
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
+            let _ = write!(s, "{b:02x}");
+            s
+        })
+}
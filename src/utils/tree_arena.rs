@@ -1,3 +1,6 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::format::Format;
 use crate::order::NodeKind;
 
 #[derive(Debug, Default, Clone)]
@@ -12,6 +15,38 @@ pub struct JsonTreeArena {
     // True when root is a synthetic wrapper object for multi-input ingest.
     // Used to trigger fileset-specific rendering (section headers and summary).
     pub is_fileset: bool,
+    // For fileset roots: the detected format of each entry, by position.
+    // Lets the renderer pick per-file templates without re-detecting.
+    pub fileset_formats: Vec<Format>,
+    // For fileset roots: keys of the entries that failed to parse in their
+    // detected format and were ingested as plain text instead.
+    pub text_fallbacks: Vec<String>,
+    // Keys that occurred more than once in one object when duplicate keys
+    // were resolved during ingest, sorted by object id.
+    pub repeated_keys: Vec<RepeatedKey>,
+    // For YAML input: tags as written (`!Ref`, `!!binary`), by node id.
+    pub tags: HashMap<usize, String>,
+    // For YAML strings written as a literal (`|`) or folded (`>`) block.
+    pub block_scalars: HashMap<usize, BlockScalar>,
+    // For text outline blocks (an array of a header line followed by its
+    // nested lines): the leading whitespace of the nested lines.
+    pub outline_indents: HashMap<usize, String>,
+    // For YAML input: anchor names of nodes that aliases reuse.
+    pub anchors: HashMap<usize, String>,
+    // For TOML input: strings that were datetimes in the source.
+    pub datetimes: HashSet<usize>,
+    // For CSV/TSV input without data rows: the column names of the header,
+    // which is all there is to show of the (empty) table.
+    pub csv_headers: HashMap<usize, Vec<String>>,
+}
+
+/// A key that occurred more than once in one object and was collapsed to a
+/// single entry during ingest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepeatedKey {
+    pub object: usize,
+    pub key: String,
+    pub occurrences: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -30,6 +65,19 @@ pub struct JsonTreeNode {
     // the kept children for this array node.
     pub arr_indices_start: usize,
     pub arr_indices_len: usize,
+    // For objects: keep keys in source order instead of sorting them when
+    // building the priority order (e.g. CSV columns).
+    pub preserve_key_order: bool,
+    // For arrays/objects: the source broke off before the container was
+    // closed (set by JSON recovery), so more children may have followed.
+    pub truncated: bool,
+}
+
+/// Block style of a YAML string scalar in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockScalar {
+    Literal,
+    Folded,
 }
 
 impl Default for JsonTreeNode {
@@ -46,6 +94,99 @@ impl Default for JsonTreeNode {
             object_len: None,
             arr_indices_start: 0,
             arr_indices_len: 0,
+            preserve_key_order: false,
+            truncated: false,
         }
     }
 }
+
+impl JsonTreeArena {
+    /// Move every node of `other` into this arena and return the id of
+    /// `other`'s root in the combined arena. Index ranges stored on the
+    /// grafted nodes are shifted so they keep pointing at their own
+    /// children, keys and array indices.
+    pub fn graft(&mut self, other: JsonTreeArena) -> usize {
+        let node_base = self.nodes.len();
+        let children_base = self.children.len();
+        let keys_base = self.obj_keys.len();
+        let indices_base = self.arr_indices.len();
+        self.nodes.extend(other.nodes.into_iter().map(|mut n| {
+            n.children_start += children_base;
+            n.obj_keys_start += keys_base;
+            if n.arr_indices_len > 0 {
+                n.arr_indices_start += indices_base;
+            }
+            n
+        }));
+        self.children
+            .extend(other.children.into_iter().map(|c| c + node_base));
+        self.obj_keys.extend(other.obj_keys);
+        self.arr_indices.extend(other.arr_indices);
+        self.repeated_keys
+            .extend(other.repeated_keys.into_iter().map(|mut r| {
+                r.object += node_base;
+                r
+            }));
+        self.tags.extend(shift_ids(other.tags, node_base));
+        self.block_scalars
+            .extend(shift_ids(other.block_scalars, node_base));
+        self.outline_indents
+            .extend(shift_ids(other.outline_indents, node_base));
+        self.anchors.extend(shift_ids(other.anchors, node_base));
+        self.datetimes
+            .extend(other.datetimes.into_iter().map(|id| id + node_base));
+        self.csv_headers
+            .extend(shift_ids(other.csv_headers, node_base));
+        other.root_id + node_base
+    }
+
+    /// Keys of object `id` that were repeated in the source.
+    pub fn repeated_keys_of(&self, id: usize) -> &[RepeatedKey] {
+        let start = self.repeated_keys.partition_point(|r| r.object < id);
+        let end = self.repeated_keys.partition_point(|r| r.object <= id);
+        &self.repeated_keys[start..end]
+    }
+
+    /// Keep the source key order of every object, except a synthetic
+    /// fileset root. For formats whose field order carries meaning (CSV
+    /// columns, XML children).
+    pub fn preserve_source_key_order(&mut self) {
+        let fileset_root = self.is_fileset.then_some(self.root_id);
+        for (id, node) in self.nodes.iter_mut().enumerate() {
+            if node.kind == NodeKind::Object && Some(id) != fileset_root {
+                node.preserve_key_order = true;
+            }
+        }
+    }
+
+    /// Append an object node with the given keys and child ids and return
+    /// its id. Used to wrap independently built arenas in a fileset root.
+    pub fn push_object(
+        &mut self,
+        keys: Vec<String>,
+        children: Vec<usize>,
+    ) -> usize {
+        let id = self.nodes.len();
+        let count = keys.len().min(children.len());
+        let node = JsonTreeNode {
+            kind: NodeKind::Object,
+            children_start: self.children.len(),
+            children_len: count,
+            obj_keys_start: self.obj_keys.len(),
+            obj_keys_len: count,
+            object_len: Some(count),
+            ..JsonTreeNode::default()
+        };
+        self.children.extend(children.into_iter().take(count));
+        self.obj_keys.extend(keys.into_iter().take(count));
+        self.nodes.push(node);
+        id
+    }
+}
+
+fn shift_ids<V>(
+    table: HashMap<usize, V>,
+    base: usize,
+) -> impl Iterator<Item = (usize, V)> {
+    table.into_iter().map(move |(id, v)| (id + base, v))
+}
@@ -0,0 +1,502 @@
+use anyhow::{Context, Result, anyhow, bail};
+use quick_xml::Reader;
+use quick_xml::escape::{
+    resolve_html5_entity, resolve_xml_entity, unescape_with,
+};
+use quick_xml::events::{BytesRef, BytesStart, Event};
+use serde::de::value::{
+    Error as ValueError, MapDeserializer, SeqDeserializer,
+};
+use serde::de::{IntoDeserializer, Visitor};
+
+use crate::PriorityConfig;
+use crate::json_ingest::{
+    build_tree_arena_from_deserializer,
+    build_tree_arena_from_many_deserializers,
+};
+use crate::utils::tree_arena::JsonTreeArena;
+
+/// Which markup dialect to accept. HTML is parsed leniently: tag names are
+/// case-insensitive, void elements need no closing tag, unclosed elements
+/// are closed implicitly and `<script>`/`<style>` bodies are raw text.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Markup {
+    Xml,
+    Html,
+}
+
+pub fn build_markup_tree_arena_from_bytes(
+    bytes: &[u8],
+    markup: Markup,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let doc = parse_markup_document(bytes, markup)?;
+    let mut arena = build_tree_arena_from_deserializer(doc, config)?;
+    arena.preserve_source_key_order();
+    Ok(arena)
+}
+
+pub fn build_markup_tree_arena_from_many(
+    inputs: Vec<(String, Vec<u8>)>,
+    markup: Markup,
+    config: &PriorityConfig,
+) -> Result<JsonTreeArena> {
+    let mut docs: Vec<(String, XmlValue)> = Vec::with_capacity(inputs.len());
+    for (key, bytes) in inputs {
+        let doc = parse_markup_document(&bytes, markup)
+            .with_context(|| format!("failed to parse {key}"))?;
+        docs.push((key, doc));
+    }
+    let mut arena = build_tree_arena_from_many_deserializers(docs, config)?;
+    arena.preserve_source_key_order();
+    Ok(arena)
+}
+
+// Document model handed to the shared serde builder. An element is an
+// object of `@attribute` keys, child elements (repeated names grouped into
+// arrays) and, for mixed content, a `#text` key. Elements holding only
+// text collapse to that string; empty ones to null.
+enum XmlValue {
+    Null,
+    Text(String),
+    List(Vec<XmlValue>),
+    Element(Vec<(String, XmlValue)>),
+}
+
+impl<'de> serde::Deserializer<'de> for XmlValue {
+    type Error = ValueError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            XmlValue::Null => visitor.visit_unit(),
+            XmlValue::Text(s) => visitor.visit_string(s),
+            XmlValue::List(items) => {
+                visitor.visit_seq(SeqDeserializer::new(items.into_iter()))
+            }
+            XmlValue::Element(entries) => {
+                visitor.visit_map(MapDeserializer::new(entries.into_iter()))
+            }
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, ValueError> for XmlValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+// HTML elements that never have content or a closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link",
+    "meta", "param", "source", "track", "wbr",
+];
+
+// HTML elements whose body is raw text rather than markup.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+// HTML elements that end an open sibling of the same family, so
+// `<li>a<li>b` yields two items instead of nested ones.
+const SIBLING_FAMILIES: &[&[&str]] = &[
+    &["li"],
+    &["p"],
+    &["option"],
+    &["tr"],
+    &["td", "th"],
+    &["dt", "dd"],
+];
+
+fn closes_open_sibling(open: &str, new: &str) -> bool {
+    SIBLING_FAMILIES
+        .iter()
+        .any(|family| family.contains(&open) && family.contains(&new))
+}
+
+#[derive(Default)]
+struct OpenElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<(String, XmlValue)>,
+    text: String,
+}
+
+impl OpenElement {
+    fn push_text(&mut self, s: &str) {
+        self.text.push_str(s);
+    }
+
+    // Text runs separated by child elements are joined with one space.
+    fn end_text_run(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with('\u{0}') {
+            self.text.push('\u{0}');
+        }
+    }
+
+    fn into_value(self, markup: Markup) -> XmlValue {
+        let text = normalize_text(&self.text, markup);
+        if self.attributes.is_empty() && self.children.is_empty() {
+            return if text.is_empty() {
+                XmlValue::Null
+            } else {
+                XmlValue::Text(text)
+            };
+        }
+        let mut entries: Vec<(String, XmlValue)> = self
+            .attributes
+            .into_iter()
+            .map(|(k, v)| (format!("@{k}"), XmlValue::Text(v)))
+            .collect();
+        entries.extend(group_repeated(self.children));
+        if !text.is_empty() {
+            entries.push(("#text".to_string(), XmlValue::Text(text)));
+        }
+        XmlValue::Element(entries)
+    }
+}
+
+// Trim every text run, drop empty ones and join the rest; HTML also
+// collapses inner whitespace the way a browser would.
+fn normalize_text(raw: &str, markup: Markup) -> String {
+    let runs = raw
+        .split('\u{0}')
+        .map(str::trim_ascii)
+        .filter(|r| !r.is_empty());
+    match markup {
+        Markup::Xml => runs.collect::<Vec<_>>().join(" "),
+        Markup::Html => runs
+            .flat_map(str::split_ascii_whitespace)
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+// Children sharing a name become one array at the position of the first.
+fn group_repeated(
+    children: Vec<(String, XmlValue)>,
+) -> Vec<(String, XmlValue)> {
+    let mut groups: Vec<(String, Vec<XmlValue>)> = Vec::new();
+    for (name, value) in children {
+        match groups.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, values)) => values.push(value),
+            None => groups.push((name, vec![value])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(name, mut values)| {
+            let value = if values.len() == 1 {
+                values.pop().unwrap_or(XmlValue::Null)
+            } else {
+                XmlValue::List(values)
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+struct MarkupTreeBuilder {
+    markup: Markup,
+    // The bottom entry is the document itself; its children are the
+    // top-level elements.
+    stack: Vec<OpenElement>,
+}
+
+impl MarkupTreeBuilder {
+    fn new(markup: Markup) -> Self {
+        Self {
+            markup,
+            stack: vec![OpenElement::default()],
+        }
+    }
+
+    fn current(&mut self) -> &mut OpenElement {
+        let last = self.stack.len() - 1;
+        &mut self.stack[last]
+    }
+
+    fn name_of(&self, raw: &[u8]) -> String {
+        let name = String::from_utf8_lossy(raw);
+        match self.markup {
+            Markup::Xml => name.into_owned(),
+            Markup::Html => name.to_ascii_lowercase(),
+        }
+    }
+
+    fn resolve_entity(&self, name: &str) -> Option<&'static str> {
+        match self.markup {
+            Markup::Xml => resolve_xml_entity(name),
+            Markup::Html => resolve_html5_entity(name),
+        }
+    }
+
+    // Unknown entities are kept as written instead of failing the parse.
+    fn unescape(&self, raw: &str) -> String {
+        unescape_with(raw, |e| self.resolve_entity(e))
+            .map_or_else(|_| raw.to_string(), std::borrow::Cow::into_owned)
+    }
+
+    fn attributes_of(&self, e: &BytesStart<'_>) -> Vec<(String, String)> {
+        let attrs = match self.markup {
+            Markup::Xml => e.attributes(),
+            Markup::Html => e.html_attributes(),
+        };
+        attrs
+            .flatten()
+            .map(|a| {
+                let value = String::from_utf8_lossy(&a.value);
+                (self.name_of(a.key.as_ref()), self.unescape(&value))
+            })
+            .collect()
+    }
+
+    fn open(&mut self, e: &BytesStart<'_>) {
+        let name = self.name_of(e.name().as_ref());
+        if self.markup == Markup::Html
+            && self.stack.len() > 1
+            && closes_open_sibling(&self.current().name, &name)
+        {
+            self.close_top();
+        }
+        let attributes = self.attributes_of(e);
+        self.current().end_text_run();
+        self.stack.push(OpenElement {
+            name,
+            attributes,
+            ..OpenElement::default()
+        });
+    }
+
+    fn close_top(&mut self) {
+        if self.stack.len() < 2 {
+            return;
+        }
+        let Some(done) = self.stack.pop() else {
+            return;
+        };
+        let name = done.name.clone();
+        let value = done.into_value(self.markup);
+        let parent = self.current();
+        parent.end_text_run();
+        parent.children.push((name, value));
+    }
+
+    // HTML end tags close everything opened after the matching element;
+    // stray end tags are ignored. XML end names are checked by the reader.
+    fn close(&mut self, raw_name: &[u8]) {
+        let name = self.name_of(raw_name);
+        let Some(pos) = self.stack.iter().rposition(|o| o.name == name) else {
+            return;
+        };
+        while self.stack.len() > pos.max(1) {
+            self.close_top();
+        }
+    }
+
+    fn reference(&mut self, r: &BytesRef<'_>) {
+        let name = String::from_utf8_lossy(r);
+        let resolved = match r.resolve_char_ref() {
+            Ok(Some(c)) => c.to_string(),
+            _ => self
+                .resolve_entity(&name)
+                .map_or_else(|| format!("&{name};"), str::to_string),
+        };
+        self.current().push_text(&resolved);
+    }
+
+    fn finish(mut self) -> Result<XmlValue> {
+        if self.markup == Markup::Xml && self.stack.len() > 1 {
+            bail!("unclosed element <{}>", self.current().name);
+        }
+        while self.stack.len() > 1 {
+            self.close_top();
+        }
+        let doc = self.stack.pop().unwrap_or_default();
+        if self.markup == Markup::Xml && doc.children.is_empty() {
+            bail!("XML document has no root element");
+        }
+        Ok(match doc.into_value(self.markup) {
+            XmlValue::Null => XmlValue::Element(Vec::new()),
+            other => other,
+        })
+    }
+}
+
+fn new_reader(s: &str, markup: Markup) -> Reader<&[u8]> {
+    let mut reader = Reader::from_str(s);
+    if markup == Markup::Html {
+        let config = reader.config_mut();
+        config.check_end_names = false;
+        config.allow_unmatched_ends = true;
+        config.allow_dangling_amp = true;
+    }
+    reader
+}
+
+fn parse_markup_document(bytes: &[u8], markup: Markup) -> Result<XmlValue> {
+    let s = std::str::from_utf8(bytes)
+        .map_err(|_| anyhow!("input is not valid UTF-8 text"))?;
+    let mut offset = 0usize;
+    let mut reader = new_reader(s, markup);
+    let mut builder = MarkupTreeBuilder::new(markup);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                let pos = offset + to_usize(reader.buffer_position());
+                if let Some(resume) = start_element(s, pos, &mut builder, &e) {
+                    offset = resume;
+                    reader = new_reader(&s[offset..], markup);
+                }
+            }
+            Event::Empty(e) => {
+                builder.open(&e);
+                builder.close_top();
+            }
+            Event::End(e) => builder.close(e.name().as_ref()),
+            Event::Text(t) => builder.current().push_text(&t.xml10_content()?),
+            Event::CData(t) => builder.current().push_text(&t.decode()?),
+            Event::GeneralRef(r) => builder.reference(&r),
+            Event::Eof => break,
+            Event::Comment(_)
+            | Event::Decl(_)
+            | Event::PI(_)
+            | Event::DocType(_) => {}
+        }
+    }
+    builder.finish()
+}
+
+fn to_usize(pos: u64) -> usize {
+    usize::try_from(pos).unwrap_or(usize::MAX)
+}
+
+// HTML void elements close immediately and raw-text elements swallow
+// their body verbatim (it may contain a bare `<`); everything else just
+// opens. Returns the offset to resume reading at after a raw-text body.
+fn start_element(
+    src: &str,
+    pos: usize,
+    builder: &mut MarkupTreeBuilder,
+    e: &BytesStart<'_>,
+) -> Option<usize> {
+    builder.open(e);
+    if builder.markup == Markup::Xml {
+        return None;
+    }
+    let name = builder.current().name.clone();
+    if VOID_ELEMENTS.contains(&name.as_str()) {
+        builder.close_top();
+        return None;
+    }
+    if !RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+        return None;
+    }
+    let rest = src.get(pos..).unwrap_or_default();
+    let (body_len, resume) = raw_text_end(rest, &name);
+    builder.current().push_text(&rest[..body_len]);
+    builder.close_top();
+    Some(pos + resume)
+}
+
+// Length of a raw-text body ending at `</name>` (any case), and the offset
+// just past that end tag. An unterminated body runs to the end of input.
+fn raw_text_end(rest: &str, name: &str) -> (usize, usize) {
+    let found = rest.match_indices("</").map(|(i, _)| i).find(|&i| {
+        rest.get(i + 2..i + 2 + name.len())
+            .is_some_and(|n| n.eq_ignore_ascii_case(name))
+    });
+    let Some(i) = found else {
+        return (rest.len(), rest.len());
+    };
+    let close = rest[i..].find('>').map_or(rest.len(), |j| i + j + 1);
+    (i, close)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_json(src: &str, markup: Markup) -> serde_json::Value {
+        let doc = parse_markup_document(src.as_bytes(), markup)
+            .expect("parse markup");
+        serde::Deserialize::deserialize(doc).expect("convert")
+    }
+
+    #[test]
+    fn elements_attributes_and_repeated_children() {
+        let src = r#"<?xml version="1.0"?>
+            <project xmlns="urn:x">
+              <!-- coordinates -->
+              <name>demo</name>
+              <dep id="a"/>
+              <dep id="b">text &amp; more</dep>
+              <empty/>
+            </project>"#;
+        assert_eq!(
+            to_json(src, Markup::Xml),
+            serde_json::json!({"project": {
+                "@xmlns": "urn:x",
+                "name": "demo",
+                "dep": [{"@id": "a"}, {"@id": "b", "#text": "text & more"}],
+                "empty": null,
+            }})
+        );
+    }
+
+    #[test]
+    fn mixed_content_and_cdata_become_text() {
+        let src = "<p>Hello <b>bold</b> world<![CDATA[ <raw> ]]></p>";
+        assert_eq!(
+            to_json(src, Markup::Xml),
+            serde_json::json!({"p": {"b": "bold", "#text": "Hello world <raw>"}})
+        );
+    }
+
+    #[test]
+    fn malformed_xml_is_an_error() {
+        let bad = |s: &str| {
+            parse_markup_document(s.as_bytes(), Markup::Xml).is_err()
+        };
+        assert!(bad("<a><b></a>"));
+        assert!(bad("<a>"));
+        assert!(bad("just text"));
+    }
+
+    #[test]
+    #[allow(
+        clippy::cognitive_complexity,
+        reason = "Single test covers multiple assertions compactly."
+    )]
+    fn html_is_parsed_leniently() {
+        let src = r#"<!DOCTYPE html>
+            <HTML><head><meta charset=utf-8><title>T&nbsp;1</title>
+            <script>if (a < b) { go(); }</script></head>
+            <body><ul><li>one<li>two</ul><p>x<br>y<p>z</body></html>"#;
+        let v = to_json(src, Markup::Html);
+        let html = &v["html"];
+        assert_eq!(
+            html["head"]["meta"],
+            serde_json::json!({"@charset": "utf-8"})
+        );
+        assert_eq!(html["head"]["title"], "T\u{a0}1");
+        assert_eq!(html["head"]["script"], "if (a < b) { go(); }");
+        assert_eq!(
+            html["body"]["ul"]["li"],
+            serde_json::json!(["one", "two"])
+        );
+        assert_eq!(
+            html["body"]["p"],
+            serde_json::json!([{"br": null, "#text": "x y"}, "z"])
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use yaml_rust2::Yaml;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser, Tag};
+use yaml_rust2::scanner::{
+    Marker, ScanError, Scanner, TScalarStyle, TokenType,
+};
+use yaml_rust2::yaml::Hash;
+
+use crate::utils::tree_arena::BlockScalar;
+
+/// The documents of one YAML input. Every anchored node is stored once in
+/// `anchors` and replaced by `Yaml::Alias(id)` wherever it appears,
+/// including its own definition, so shared structure is never copied.
+/// Nodes with a tag or block style are stored the same way under a private
+/// id (counting down from `usize::MAX`) so `styles` can describe them.
+#[derive(Default)]
+pub(crate) struct YamlDocs {
+    pub(crate) docs: Vec<Yaml>,
+    pub(crate) anchors: HashMap<usize, Yaml>,
+    pub(crate) styles: HashMap<usize, NodeStyle>,
+    // Anchor names by id (ids start at 1). Only collected when the input
+    // uses aliases, for placeholders of references that are not expanded.
+    pub(crate) anchor_names: Vec<String>,
+}
+
+impl YamlDocs {
+    pub(crate) fn anchor_name(&self, id: usize) -> Option<&str> {
+        self.anchor_names
+            .get(id.checked_sub(1)?)
+            .map(String::as_str)
+    }
+}
+
+/// Parse `source` like `YamlLoader::load_from_str`, but keep aliases as
+/// references to their anchors instead of deep copies.
+pub(crate) fn load(source: &str) -> Result<YamlDocs, ScanError> {
+    let mut loader = Loader::default();
+    Parser::new_from_str(source).load(&mut loader, true)?;
+    if let Some(e) = loader.error {
+        return Err(e);
+    }
+    let anchor_names = if loader.saw_alias {
+        anchor_names(source)
+    } else {
+        Vec::new()
+    };
+    Ok(YamlDocs {
+        docs: loader.docs,
+        anchors: loader.anchors,
+        styles: loader.styles,
+        anchor_names,
+    })
+}
+
+/// Presentation details of a source node that `Yaml` values do not keep.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct NodeStyle {
+    pub(crate) tag: Option<String>,
+    pub(crate) block_scalar: Option<BlockScalar>,
+}
+
+impl NodeStyle {
+    fn new(tag: Option<&Tag>, style: TScalarStyle) -> Self {
+        Self {
+            tag: tag.map(tag_text),
+            block_scalar: match style {
+                TScalarStyle::Literal => Some(BlockScalar::Literal),
+                TScalarStyle::Folded => Some(BlockScalar::Folded),
+                _ => None,
+            },
+        }
+    }
+
+    fn is_plain(&self) -> bool {
+        self.tag.is_none() && self.block_scalar.is_none()
+    }
+}
+
+// Spell a resolved tag the way it is usually written: `!!str` for the core
+// schema, `!Ref` for local tags and `!<uri>` for everything else.
+fn tag_text(tag: &Tag) -> String {
+    match tag.handle.as_str() {
+        "tag:yaml.org,2002:" => format!("!!{}", tag.suffix),
+        "!" => format!("!{}", tag.suffix),
+        "" if tag.suffix == "!" => "!".to_string(),
+        handle => format!("!<{handle}{}>", tag.suffix),
+    }
+}
+
+// The parser numbers anchors in the order their `&name` tokens appear.
+fn anchor_names(source: &str) -> Vec<String> {
+    Scanner::new(source.chars())
+        .filter_map(|token| match token.1 {
+            TokenType::Anchor(name) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Loader {
+    docs: Vec<Yaml>,
+    // Open collections with their anchor id (0 when not anchored).
+    doc_stack: Vec<(Yaml, usize, NodeStyle)>,
+    // Pending key of each open mapping; `BadValue` while a key is expected.
+    key_stack: Vec<Yaml>,
+    anchors: HashMap<usize, Yaml>,
+    styles: HashMap<usize, NodeStyle>,
+    saw_alias: bool,
+    error: Option<ScanError>,
+}
+
+impl MarkedEventReceiver for Loader {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(e) = self.on_event_impl(ev, mark) {
+            self.error = Some(e);
+        }
+    }
+}
+
+impl Loader {
+    fn on_event_impl(
+        &mut self,
+        ev: Event,
+        mark: Marker,
+    ) -> Result<(), ScanError> {
+        match ev {
+            Event::DocumentEnd => {
+                let doc = self.doc_stack.pop().map_or(Yaml::BadValue, |n| n.0);
+                self.docs.push(doc);
+            }
+            Event::SequenceStart(aid, tag) => {
+                let style = NodeStyle::new(tag.as_ref(), TScalarStyle::Plain);
+                self.doc_stack.push((Yaml::Array(Vec::new()), aid, style));
+            }
+            Event::MappingStart(aid, tag) => {
+                let style = NodeStyle::new(tag.as_ref(), TScalarStyle::Plain);
+                self.doc_stack.push((Yaml::Hash(Hash::new()), aid, style));
+                self.key_stack.push(Yaml::BadValue);
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                if matches!(ev, Event::MappingEnd) {
+                    self.key_stack.pop();
+                }
+                if let Some(node) = self.doc_stack.pop() {
+                    self.insert_new_node(node, mark)?;
+                }
+            }
+            Event::Scalar(v, style, aid, tag) => {
+                let node_style = NodeStyle::new(tag.as_ref(), style);
+                let node = resolve_scalar(v, style, tag.as_ref());
+                self.insert_new_node((node, aid, node_style), mark)?;
+            }
+            Event::Alias(id) => {
+                self.saw_alias = true;
+                let node = (Yaml::Alias(id), 0, NodeStyle::default());
+                self.insert_new_node(node, mark)?;
+            }
+            Event::DocumentStart
+            | Event::Nothing
+            | Event::StreamStart
+            | Event::StreamEnd => {}
+        }
+        Ok(())
+    }
+
+    fn insert_new_node(
+        &mut self,
+        (node, aid, style): (Yaml, usize, NodeStyle),
+        mark: Marker,
+    ) -> Result<(), ScanError> {
+        // Valid anchor ids start from 1.
+        let aid = if aid == 0 && !style.is_plain() {
+            usize::MAX - self.styles.len()
+        } else {
+            aid
+        };
+        let node = if aid > 0 {
+            if !style.is_plain() {
+                self.styles.insert(aid, style);
+            }
+            self.anchors.insert(aid, node);
+            Yaml::Alias(aid)
+        } else {
+            node
+        };
+        let Some((parent, ..)) = self.doc_stack.last_mut() else {
+            self.doc_stack.push((node, 0, NodeStyle::default()));
+            return Ok(());
+        };
+        match parent {
+            Yaml::Array(items) => items.push(node),
+            Yaml::Hash(map) => {
+                let Some(key) = self.key_stack.last_mut() else {
+                    return Ok(());
+                };
+                if key.is_badvalue() {
+                    *key = node;
+                } else {
+                    let key = std::mem::replace(key, Yaml::BadValue);
+                    if map.contains_key(&key) {
+                        return Err(ScanError::new_string(
+                            mark,
+                            format!("{key:?}: duplicated key in mapping"),
+                        ));
+                    }
+                    map.insert(key, node);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+// Same resolution rules as `YamlLoader`: quoted scalars are strings, core
+// schema tags are honoured and plain scalars are typed by their text.
+fn resolve_scalar(v: String, style: TScalarStyle, tag: Option<&Tag>) -> Yaml {
+    if style != TScalarStyle::Plain {
+        return Yaml::String(v);
+    }
+    match tag {
+        Some(tag) if tag.handle == "tag:yaml.org,2002:" => {
+            resolve_core_tag(v, &tag.suffix)
+        }
+        Some(_) => Yaml::String(v),
+        None => Yaml::from_str(&v),
+    }
+}
+
+fn resolve_core_tag(v: String, suffix: &str) -> Yaml {
+    match suffix {
+        "bool" => match v.as_str() {
+            "true" | "True" | "TRUE" => Yaml::Boolean(true),
+            "false" | "False" | "FALSE" => Yaml::Boolean(false),
+            _ => Yaml::BadValue,
+        },
+        "int" => v.parse::<i64>().map_or(Yaml::BadValue, Yaml::Integer),
+        "float" => match Yaml::from_str(&v) {
+            Yaml::Real(_) | Yaml::Integer(_) => Yaml::Real(v),
+            _ => Yaml::BadValue,
+        },
+        "null" => match v.as_str() {
+            "~" | "null" => Yaml::Null,
+            _ => Yaml::BadValue,
+        },
+        _ => Yaml::String(v),
+    }
+}
@@ -1,31 +1,24 @@
+use std::collections::{HashMap, HashSet};
+
 use anyhow::{Result, anyhow};
+use loader::YamlDocs;
 use yaml_rust2::Yaml;
+use yaml_rust2::yaml::Hash;
 
 use crate::PriorityConfig;
 use crate::order::NodeKind;
 use crate::utils::tree_arena::{JsonTreeArena, JsonTreeNode};
 
+mod loader;
+
 pub fn build_yaml_tree_arena_from_bytes(
-    bytes: Vec<u8>,
+    bytes: &[u8],
     config: &PriorityConfig,
 ) -> Result<JsonTreeArena> {
-    let s = String::from_utf8(bytes)
+    let s = std::str::from_utf8(bytes)
         .map_err(|_| anyhow!("input is not valid UTF-8 text"))?;
-    let docs = yaml_rust2::YamlLoader::load_from_str(&s)?;
     let mut b = YamlArenaBuilder::new(config.array_max_items);
-    let root_id = if docs.len() <= 1 {
-        match docs.first() {
-            Some(doc) => b.build(doc),
-            None => b.build(&Yaml::Array(vec![])),
-        }
-    } else {
-        // Multi-doc YAML in a single input -> wrap into an array root.
-        let mut children: Vec<usize> = Vec::with_capacity(docs.len());
-        for d in &docs {
-            children.push(b.build(d));
-        }
-        b.push_array(children, docs.len())
-    };
+    let root_id = b.build_input(loader::load(s)?);
     let mut arena = b.finish();
     arena.root_id = root_id;
     Ok(arena)
@@ -41,19 +34,7 @@ pub fn build_yaml_tree_arena_from_many(
     for (key, bytes) in inputs.drain(..) {
         let s = String::from_utf8(bytes)
             .map_err(|_| anyhow!("input is not valid UTF-8 text"))?;
-        let docs = yaml_rust2::YamlLoader::load_from_str(&s)?;
-        let child_id = if docs.len() <= 1 {
-            match docs.first() {
-                Some(doc) => b.build(doc),
-                None => b.build(&Yaml::Array(vec![])),
-            }
-        } else {
-            let mut arr_children: Vec<usize> = Vec::with_capacity(docs.len());
-            for d in &docs {
-                arr_children.push(b.build(d));
-            }
-            b.push_array(arr_children, docs.len())
-        };
+        let child_id = b.build_input(loader::load(&s)?);
         keys.push(key);
         children.push(child_id);
     }
@@ -64,9 +45,21 @@ pub fn build_yaml_tree_arena_from_many(
     Ok(arena)
 }
 
+// Upper bound on the nodes that alias references may add on top of the
+// input's own nodes; further references stay `*name` placeholders so
+// "billion laughs" inputs cannot blow up the preview.
+const MAX_ALIAS_EXPANSION: usize = 100_000;
+
 struct YamlArenaBuilder {
     arena: JsonTreeArena,
     array_cap: usize,
+    // Anchors of the input being built. An anchor is taken out while its
+    // node is being built, so a reference from inside it finds nothing.
+    input: YamlDocs,
+    // Built anchors: arena id and expanded size in nodes. Later aliases
+    // point at the same arena node instead of copying it.
+    shared: HashMap<usize, (usize, usize)>,
+    expanded: usize,
 }
 
 impl YamlArenaBuilder {
@@ -74,7 +67,30 @@ impl YamlArenaBuilder {
         Self {
             arena: JsonTreeArena::default(),
             array_cap,
+            input: YamlDocs::default(),
+            shared: HashMap::new(),
+            expanded: 0,
+        }
+    }
+
+    // Build every document of one input; several documents are wrapped
+    // into an array root.
+    fn build_input(&mut self, mut input: YamlDocs) -> usize {
+        let docs = std::mem::take(&mut input.docs);
+        self.input = input;
+        self.shared.clear();
+        self.expanded = 0;
+        if docs.len() <= 1 {
+            return match docs.first() {
+                Some(doc) => self.build(doc),
+                None => self.build(&Yaml::Array(vec![])),
+            };
+        }
+        let mut children: Vec<usize> = Vec::with_capacity(docs.len());
+        for d in &docs {
+            children.push(self.build(d));
         }
+        self.push_array(children, docs.len())
     }
 
     fn finish(self) -> JsonTreeArena {
@@ -158,17 +174,7 @@ impl YamlArenaBuilder {
                 }
                 self.push_array(child_ids, total)
             }
-            Yaml::Hash(hm) => {
-                let mut keys: Vec<String> = Vec::with_capacity(hm.len());
-                let mut children: Vec<usize> = Vec::with_capacity(hm.len());
-                for (k, v) in hm.iter() {
-                    let key = stringify_yaml_key(k);
-                    let cid = self.build(v);
-                    keys.push(key);
-                    children.push(cid);
-                }
-                self.push_object_root(keys, children)
-            }
+            Yaml::Hash(hm) => self.build_mapping(hm),
             Yaml::String(s) => {
                 let id = self.push_default();
                 let n = &mut self.arena.nodes[id];
@@ -205,16 +211,149 @@ impl YamlArenaBuilder {
                 n.atomic_token = Some("null".to_string());
                 id
             }
-            // Represent aliases as a fixed string to avoid unstable parser IDs
-            // and keep output deterministic.
-            Yaml::Alias(_n) => {
-                let id = self.push_default();
-                let node = &mut self.arena.nodes[id];
-                node.kind = NodeKind::String;
-                node.string_value = Some("*alias".to_string());
-                id
+            Yaml::Alias(aid) => self.build_alias(*aid),
+        }
+    }
+
+    // Merge keys (`<<: *defaults`, or `<<: [*a, *b]`) bring in the entries
+    // of other mappings where they stand. Keys of the mapping itself win
+    // over merged ones, and earlier merged mappings over later ones.
+    fn build_mapping(&mut self, hm: &Hash) -> usize {
+        let mut seen: HashSet<String> = hm
+            .iter()
+            .filter(|(k, v)| !self.merges(k, v))
+            .map(|(k, _)| self.key_text(k))
+            .collect();
+        let mut keys: Vec<String> = Vec::with_capacity(hm.len());
+        let mut children: Vec<usize> = Vec::with_capacity(hm.len());
+        for (k, v) in hm {
+            if self.merges(k, v) {
+                let merged = self.merged_entries(v);
+                let unseen = merged
+                    .into_iter()
+                    .filter(|(key, _)| seen.insert(key.clone()));
+                for (key, cid) in unseen {
+                    keys.push(key);
+                    children.push(cid);
+                }
+            } else {
+                keys.push(self.key_text(k));
+                children.push(self.build(v));
             }
         }
+        self.push_object_root(keys, children)
+    }
+
+    // A `<<` key whose value is a mapping or a sequence of mappings, as
+    // written or through aliases. Anything else stays a plain entry.
+    fn merges(&self, k: &Yaml, v: &Yaml) -> bool {
+        let is_merge_key = matches!(k, Yaml::String(s) if s == "<<");
+        is_merge_key
+            && match v {
+                Yaml::Array(items) => {
+                    !items.is_empty()
+                        && items.iter().all(|y| self.is_mapping(y))
+                }
+                other => self.is_mapping(other),
+            }
+    }
+
+    // An alias only counts while its anchor can be expanded, so a mapping
+    // merging itself keeps its `<<` entry.
+    fn is_mapping(&self, y: &Yaml) -> bool {
+        match y {
+            Yaml::Hash(_) => true,
+            Yaml::Alias(aid) => self
+                .input
+                .anchors
+                .get(aid)
+                .is_some_and(|node| self.is_mapping(node)),
+            _ => false,
+        }
+    }
+
+    // Entries of the mappings a merge key refers to, sharing their nodes.
+    fn merged_entries(&mut self, v: &Yaml) -> Vec<(String, usize)> {
+        let sources = match v {
+            Yaml::Array(items) => items.as_slice(),
+            other => std::slice::from_ref(other),
+        };
+        let mut entries = Vec::new();
+        for source in sources {
+            let id = self.build(source);
+            entries.extend(self.object_entries(id));
+        }
+        entries
+    }
+
+    // Keys and children of the object `id`; nothing for the placeholder
+    // of an alias past the expansion cap.
+    fn object_entries(&self, id: usize) -> Vec<(String, usize)> {
+        let n = &self.arena.nodes[id];
+        if n.kind != NodeKind::Object {
+            return Vec::new();
+        }
+        let keys = &self.arena.obj_keys[n.obj_keys_start..][..n.obj_keys_len];
+        let children =
+            &self.arena.children[n.children_start..][..n.children_len];
+        keys.iter().cloned().zip(children.iter().copied()).collect()
+    }
+
+    fn build_alias(&mut self, aid: usize) -> usize {
+        if let Some(&(id, size)) = self.shared.get(&aid) {
+            if self.expanded + size > MAX_ALIAS_EXPANSION {
+                return self.push_alias_placeholder(aid);
+            }
+            self.expanded += size;
+            if let Some(name) = self.input.anchor_name(aid) {
+                self.arena.anchors.insert(id, name.to_string());
+            }
+            return id;
+        }
+        // Unknown anchor, or a reference from inside the anchored node.
+        let Some(node) = self.input.anchors.remove(&aid) else {
+            return self.push_alias_placeholder(aid);
+        };
+        let (nodes_before, expanded_before) =
+            (self.arena.nodes.len(), self.expanded);
+        let id = self.build(&node);
+        self.record_style(aid, id);
+        let size = self.arena.nodes.len() - nodes_before + self.expanded
+            - expanded_before;
+        self.input.anchors.insert(aid, node);
+        self.shared.insert(aid, (id, size));
+        id
+    }
+
+    fn record_style(&mut self, aid: usize, id: usize) {
+        let Some(style) = self.input.styles.get(&aid) else {
+            return;
+        };
+        if let Some(tag) = &style.tag {
+            self.arena.tags.insert(id, tag.clone());
+        }
+        if let Some(block) = style.block_scalar {
+            self.arena.block_scalars.insert(id, block);
+        }
+    }
+
+    fn push_alias_placeholder(&mut self, aid: usize) -> usize {
+        let name = self.input.anchor_name(aid).unwrap_or("alias");
+        let text = format!("*{name}");
+        let id = self.push_default();
+        let node = &mut self.arena.nodes[id];
+        node.kind = NodeKind::String;
+        node.string_value = Some(text);
+        id
+    }
+
+    // Keys are stringified; anchored or aliased keys use their target.
+    fn key_text(&self, k: &Yaml) -> String {
+        let resolved = match k {
+            Yaml::Alias(aid) => self.input.anchors.get(aid),
+            _ => None,
+        };
+        stringify_yaml_key(resolved.unwrap_or(k))
     }
 }
 
@@ -250,6 +389,7 @@ fn stringify_yaml_key(y: &Yaml) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::tree_arena::BlockScalar;
 
     #[test]
     #[allow(
@@ -259,9 +399,8 @@ mod tests {
     fn yaml_arena_basic_mapping_and_sequence() {
         let y = "foo:\n  - list1\n  - 2\nbar: true\n";
         let cfg = PriorityConfig::new(usize::MAX, 10);
-        let arena =
-            build_yaml_tree_arena_from_bytes(y.as_bytes().to_vec(), &cfg)
-                .expect("parse yaml");
+        let arena = build_yaml_tree_arena_from_bytes(y.as_bytes(), &cfg)
+            .expect("parse yaml");
         let root = &arena.nodes[arena.root_id];
         assert_eq!(root.kind, NodeKind::Object);
         assert_eq!(root.object_len.unwrap_or(0), 2);
@@ -282,11 +421,92 @@ mod tests {
     fn yaml_arena_multi_document_wraps_in_array() {
         let y = "---\na: 1\n---\n- z\n";
         let cfg = PriorityConfig::new(usize::MAX, 10);
-        let arena =
-            build_yaml_tree_arena_from_bytes(y.as_bytes().to_vec(), &cfg)
-                .expect("parse yaml");
+        let arena = build_yaml_tree_arena_from_bytes(y.as_bytes(), &cfg)
+            .expect("parse yaml");
         let root = &arena.nodes[arena.root_id];
         assert_eq!(root.kind, NodeKind::Array);
         assert_eq!(root.children_len, 2);
     }
+
+    fn string_at(arena: &JsonTreeArena, id: usize) -> Option<&str> {
+        arena.nodes[id].string_value.as_deref()
+    }
+
+    #[test]
+    fn aliases_share_their_anchored_node() {
+        let y = "base: &b {x: 1}\nuse: *b\nself: &s [1, *s]\n";
+        let cfg = PriorityConfig::new(usize::MAX, 10);
+        let arena = build_yaml_tree_arena_from_bytes(y.as_bytes(), &cfg)
+            .expect("parse yaml");
+        let root = &arena.nodes[arena.root_id];
+        let child = |i: usize| arena.children[root.children_start + i];
+        assert_eq!(child(0), child(1), "alias points at the anchored node");
+        let cycle = &arena.nodes[child(2)];
+        let inner = arena.children[cycle.children_start + 1];
+        assert_eq!(string_at(&arena, inner), Some("*s"));
+    }
+
+    #[test]
+    fn tags_and_block_styles_are_kept_by_node() {
+        let y = "a: !Ref x\nb: !!binary aGk=\nc: !Sub [1]\nd: |\n  t\n";
+        let cfg = PriorityConfig::new(usize::MAX, 10);
+        let arena = build_yaml_tree_arena_from_bytes(y.as_bytes(), &cfg)
+            .expect("parse yaml");
+        let root = &arena.nodes[arena.root_id];
+        let child = |i: usize| arena.children[root.children_start + i];
+        let tags: Vec<Option<&str>> = (0..4)
+            .map(|i| arena.tags.get(&child(i)).map(String::as_str))
+            .collect();
+        assert_eq!(tags, [Some("!Ref"), Some("!!binary"), Some("!Sub"), None]);
+        assert_eq!(arena.nodes[child(2)].kind, NodeKind::Array);
+        assert_eq!(
+            arena.block_scalars.get(&child(3)),
+            Some(&BlockScalar::Literal)
+        );
+        assert_eq!(arena.nodes[child(3)].string_value.as_deref(), Some("t\n"));
+    }
+
+    #[test]
+    fn alias_expansion_is_capped() {
+        let mut y = String::from("a0: &a0 [x, x, x, x, x, x, x, x, x, x]\n");
+        for i in 1..8 {
+            let refs = vec![format!("*a{}", i - 1); 10].join(", ");
+            y.push_str(&format!("a{i}: &a{i} [{refs}]\n"));
+        }
+        let cfg = PriorityConfig::new(usize::MAX, usize::MAX);
+        let arena = build_yaml_tree_arena_from_bytes(y.as_bytes(), &cfg)
+            .expect("parse yaml");
+        assert!(arena.nodes.len() < 200, "nodes are shared, not copied");
+        let placeholders = (0..arena.nodes.len())
+            .filter(|&id| {
+                string_at(&arena, id).is_some_and(|s| s.starts_with("*a"))
+            })
+            .count();
+        assert!(placeholders > 0, "deep references stop at the cap");
+    }
+
+    #[test]
+    fn merge_keys_bring_in_entries_that_local_keys_override() {
+        let y = "base: &b {image: alpine, stage: test}\n\
+                 more: &m {retry: 2, stage: lint}\n\
+                 job:\n  stage: build\n  <<: [*b, *m]\n  script: make\n\
+                 self: &s {<<: *s}\n";
+        let cfg = PriorityConfig::new(usize::MAX, 10);
+        let arena = build_yaml_tree_arena_from_bytes(y.as_bytes(), &cfg)
+            .expect("parse yaml");
+        let root = &arena.nodes[arena.root_id];
+        let object =
+            |i: usize| &arena.nodes[arena.children[root.children_start + i]];
+        let keys = |n: &JsonTreeNode| {
+            arena.obj_keys[n.obj_keys_start..][..n.obj_keys_len].to_vec()
+        };
+        let job = object(2);
+        assert_eq!(keys(job), ["stage", "image", "retry", "script"]);
+        let stage = arena.children[job.children_start];
+        assert_eq!(string_at(&arena, stage), Some("build"));
+        let image = arena.children[job.children_start + 1];
+        let base_image = arena.children[object(0).children_start];
+        assert_eq!(image, base_image, "merged entries share their nodes");
+        assert_eq!(keys(object(3)), ["<<"]);
+    }
 }
@@ -0,0 +1,61 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+use std::io::{Cursor, Write};
+
+fn tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let gz = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+    let mut builder = tar::Builder::new(gz);
+    for (name, bytes) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, name, *bytes).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+#[test]
+#[allow(
+    clippy::cognitive_complexity,
+    reason = "test performs several assertions succinctly"
+)]
+fn tar_gz_members_become_fileset_entries() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let path = dir.path().join("export.tar.gz");
+    fs::write(
+        &path,
+        tar_gz(&[
+            ("export/users.json", b"{\"name\": \"ada\"}"),
+            ("export/conf.yaml", b"kind: tar\n"),
+            ("export/logo.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+        ]),
+    )
+    .unwrap();
+    let (ok, out, err) =
+        util::run_capture(b"", &["-n", "10000", path.to_str().unwrap()]);
+    assert!(ok, "{}", String::from_utf8_lossy(&err));
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("==> export/users.json <=="), "{out}");
+    assert!(out.contains("\"name\": \"ada\""), "{out}");
+    assert!(out.contains("==> export/conf.yaml <=="), "{out}");
+    assert!(out.contains("kind: tar"), "{out}");
+    let err = String::from_utf8(err).unwrap();
+    assert!(err.contains("Ignored binary file:"), "{err}");
+    assert!(err.contains("export/logo.png"), "{err}");
+}
+
+#[test]
+fn single_member_zip_still_renders_as_fileset() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let path = dir.path().join("bundle.zip");
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("data/a.toml", zip::write::SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(b"x = 1\n").unwrap();
+    fs::write(&path, zip.finish().unwrap().into_inner()).unwrap();
+    let out = util::run_stdout("", &["-n", "10000", path.to_str().unwrap()]);
+    assert!(out.contains("==> data/a.toml <=="), "{out}");
+    assert!(out.contains("x = 1"), "{out}");
+}
@@ -0,0 +1,132 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+fn parse_json(s: &[u8]) -> serde_json::Value {
+    serde_json::from_slice(s).unwrap_or_else(|e| {
+        panic!("invalid JSON output: {e}\n{}", String::from_utf8_lossy(s))
+    })
+}
+
+fn users() -> serde_json::Value {
+    let rows: Vec<serde_json::Value> = (0..100)
+        .map(|i| serde_json::json!({"id": i, "name": format!("user{i}")}))
+        .collect();
+    serde_json::json!({ "users": rows })
+}
+
+fn cbor(value: &serde_json::Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out).expect("encode cbor");
+    out
+}
+
+// A BSON document with an int32 `i` and a 12-byte ObjectId `_id`.
+fn bson_doc(i: i32) -> Vec<u8> {
+    let mut elements = vec![0x07];
+    elements.extend_from_slice(b"_id\0");
+    elements.extend_from_slice(&[0x65; 12]);
+    elements.extend_from_slice(b"\x10i\0");
+    elements.extend_from_slice(&i.to_le_bytes());
+    let len = i32::try_from(elements.len() + 5).unwrap();
+    let mut doc = len.to_le_bytes().to_vec();
+    doc.extend(elements);
+    doc.push(0);
+    doc
+}
+
+#[test]
+fn msgpack_file_is_previewed_instead_of_ignored() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let p = dir.path().join("users.msgpack");
+    fs::write(&p, rmp_serde::to_vec(&users()).unwrap()).unwrap();
+    let (ok, out, err) = util::run_capture(
+        b"",
+        &["-n", "300", "-t", "strict", p.to_str().unwrap()],
+    );
+    assert!(ok, "{}", String::from_utf8_lossy(&err));
+    assert!(err.is_empty(), "no ignore notice");
+    let v = parse_json(&out);
+    let users = v["users"].as_array().expect("users array");
+    assert!(!users.is_empty() && users.len() < 100, "sampled: {v}");
+    assert_eq!(users[0], serde_json::json!({"id": 0, "name": "user0"}));
+}
+
+#[test]
+fn binary_formats_are_sniffed_on_stdin() {
+    let value = serde_json::json!({"a": [1, 2.5, null], "b": "x"});
+    let packed = rmp_serde::to_vec(&value).unwrap();
+    for bytes in [packed, cbor(&value)] {
+        let (ok, out, err) =
+            util::run_capture(&bytes, &["-n", "10000", "-t", "strict"]);
+        assert!(ok, "{}", String::from_utf8_lossy(&err));
+        assert_eq!(parse_json(&out), value);
+    }
+}
+
+#[test]
+fn bson_dump_becomes_an_array_of_documents() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let p = dir.path().join("users.bson");
+    let dump: Vec<u8> = (0..3).flat_map(bson_doc).collect();
+    fs::write(&p, dump).unwrap();
+    let (ok, out, err) = util::run_capture(
+        b"",
+        &["-n", "10000", "-t", "strict", p.to_str().unwrap()],
+    );
+    assert!(ok, "{}", String::from_utf8_lossy(&err));
+    let v = parse_json(&out);
+    assert_eq!(v.as_array().map(Vec::len), Some(3));
+    assert_eq!(
+        v[2],
+        serde_json::json!({"_id": {"$oid": "656565656565656565656565"}, "i": 2})
+    );
+}
+
+#[test]
+fn explicit_input_format_reads_binary_files() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let p = dir.path().join("payload.bin");
+    fs::write(&p, cbor(&serde_json::json!({"k": "v"}))).unwrap();
+    let (ok, out, _err) = util::run_capture(
+        b"",
+        &[
+            "-i",
+            "cbor",
+            "-f",
+            "json",
+            "-t",
+            "strict",
+            p.to_str().unwrap(),
+        ],
+    );
+    assert!(ok);
+    assert_eq!(parse_json(&out), serde_json::json!({"k": "v"}));
+}
+
+#[test]
+fn fileset_mixes_binary_and_text_entries() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let a = dir.path().join("a.yaml");
+    let b = dir.path().join("b.cbor");
+    let c = dir.path().join("blob.bin");
+    fs::write(&a, "k: v\n").unwrap();
+    fs::write(&b, cbor(&serde_json::json!({"nested": {"x": 1}}))).unwrap();
+    fs::write(&c, [0x00, 0xFF, 0x00, 0x01, 0x02, 0x03]).unwrap();
+    let (ok, out, err) = util::run_capture(
+        b"",
+        &[
+            "-n",
+            "10000",
+            a.to_str().unwrap(),
+            b.to_str().unwrap(),
+            c.to_str().unwrap(),
+        ],
+    );
+    assert!(ok);
+    let out = String::from_utf8_lossy(&out);
+    assert!(out.contains("k: v"), "yaml entry: {out}");
+    assert!(out.contains("\"x\": 1"), "cbor entry: {out}");
+    let err = String::from_utf8_lossy(&err);
+    assert!(err.contains("Ignored binary file"), "other binaries: {err}");
+}
@@ -49,10 +49,22 @@ fn colored_and_plain_outputs_should_match_after_stripping() {
     // Use a tight budget so the number of kept items is sensitive to extra bytes.
     let budget = 50usize;
 
-    let plain = headson::headson(input.to_vec(), &cfg_plain, &prio, budget)
-        .expect("plain render");
-    let colored = headson::headson(input.to_vec(), &cfg_color, &prio, budget)
-        .expect("color render");
+    let plain = headson::headson(
+        input.to_vec(),
+        &headson::IngestOptions::default(),
+        &cfg_plain,
+        &prio,
+        budget,
+    )
+    .expect("plain render");
+    let colored = headson::headson(
+        input.to_vec(),
+        &headson::IngestOptions::default(),
+        &cfg_color,
+        &prio,
+        budget,
+    )
+    .expect("color render");
 
     let colored_stripped = strip_ansi(&colored);
 
@@ -0,0 +1,42 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+use std::io::Write;
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+    gz.write_all(bytes).unwrap();
+    gz.finish().unwrap()
+}
+
+#[test]
+fn compressed_files_use_the_inner_extension() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let json = dir.path().join("data.json.gz");
+    let yaml = dir.path().join("conf.yaml.zst");
+    fs::write(&json, gzip(b"{\"name\": \"gz\"}")).unwrap();
+    fs::write(&yaml, zstd::encode_all(&b"kind: zst\n"[..], 0).unwrap())
+        .unwrap();
+    let (ok, out, err) = util::run_capture(
+        b"",
+        &[
+            "-n",
+            "10000",
+            json.to_str().unwrap(),
+            yaml.to_str().unwrap(),
+        ],
+    );
+    assert!(ok, "{}", String::from_utf8_lossy(&err));
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("\"name\": \"gz\""), "{out}");
+    assert!(out.contains("kind: zst"), "{out}");
+    assert!(!String::from_utf8_lossy(&err).contains("Ignored"));
+}
+
+#[test]
+fn compressed_stdin_is_decoded_before_sniffing() {
+    let (ok, out, _) =
+        util::run_capture(&gzip(b"a: 1\nb: 2\n"), &["-n", "10000"]);
+    assert!(ok);
+    assert_eq!(String::from_utf8(out).unwrap().trim_end(), "a: 1\nb: 2");
+}
@@ -0,0 +1,46 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+#[test]
+fn stdin_yaml_is_sniffed_without_input_flag() {
+    let input = "apiVersion: v1\nkind: Pod\nmetadata:\n  name: web\n";
+    let out = util::run_stdout(input, &["-n", "10000"]);
+    assert!(out.contains("kind: Pod"), "yaml output: {out:?}");
+    assert!(!out.contains('{'), "should not render as JSON: {out:?}");
+}
+
+#[test]
+fn stdin_ndjson_is_sniffed_as_records() {
+    let out = util::run_stdout(
+        "{\"a\":1}\n{\"a\":2}\n",
+        &["-n", "10000", "-t", "strict"],
+    );
+    let v: serde_json::Value =
+        serde_json::from_str(&out).expect("strict json output");
+    assert_eq!(v, serde_json::json!([{"a": 1}, {"a": 2}]));
+}
+
+#[test]
+fn stdin_plain_text_is_sniffed_as_text() {
+    let out = util::run_stdout("hello there\nworld\n", &["-n", "10000"]);
+    assert!(
+        out.starts_with("hello there\nworld"),
+        "text output: {out:?}"
+    );
+}
+
+#[test]
+fn explicit_input_format_disables_sniffing() {
+    let (ok, _out, _err) = util::run_capture(b"k: 1\n", &["-i", "json"]);
+    assert!(!ok, "yaml content forced through json ingest should fail");
+}
+
+#[test]
+fn unknown_extension_file_is_sniffed() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let p = dir.path().join("data");
+    fs::write(&p, b"[1, 2, 3]").unwrap();
+    let out = util::run_stdout("", &["-n", "10000", p.to_str().unwrap()]);
+    assert!(out.contains('['), "json output: {out:?}");
+}
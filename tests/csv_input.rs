@@ -0,0 +1,151 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+fn rows(n: usize) -> String {
+    let mut s = String::from("id,name,city\n");
+    for i in 0..n {
+        s.push_str(&format!("{i},user{i},town{i}\n"));
+    }
+    s
+}
+
+fn parse_json(s: &str) -> serde_json::Value {
+    serde_json::from_str(s)
+        .unwrap_or_else(|e| panic!("invalid JSON output: {e}\n{s}"))
+}
+
+#[test]
+fn csv_rows_become_objects_in_column_order() {
+    let src = "name,age,city\nann,31,Oslo\nbob,42,\"Rome, IT\"\n";
+    let out = util::run_stdout(
+        src,
+        &["-n", "10000", "-i", "csv", "-f", "json", "-t", "strict"],
+    );
+    assert_eq!(
+        parse_json(&out),
+        serde_json::json!([
+            {"name": "ann", "age": "31", "city": "Oslo"},
+            {"name": "bob", "age": "42", "city": "Rome, IT"}
+        ])
+    );
+    let name = out.find("\"name\"").unwrap();
+    let age = out.find("\"age\"").unwrap();
+    assert!(name < age, "column order kept: {out}");
+}
+
+#[test]
+fn csv_round_trips_through_csv_template() {
+    let src = "name,note\nann,\"say \"\"hi\"\"\"\nbob,\"a,b\"\n";
+    let out = util::run_stdout(src, &["-n", "10000", "-i", "csv"]);
+    assert_eq!(out.trim_end(), src.trim_end());
+}
+
+#[test]
+fn tail_keeps_the_last_rows() {
+    let out = util::run_stdout(
+        &rows(100),
+        &["-n", "200", "-i", "csv", "--tail", "-t", "strict"],
+    );
+    let lines: Vec<&str> = out.trim_end().lines().collect();
+    assert_eq!(lines.first(), Some(&"id,name,city"));
+    assert!(lines.last().is_some_and(|l| l.starts_with("99,")), "{out}");
+    assert!(!out.contains("\n0,"), "head rows dropped: {out}");
+}
+
+#[test]
+#[allow(
+    clippy::cognitive_complexity,
+    reason = "Single test covers multiple assertions compactly."
+)]
+fn omitted_rows_are_marked_in_non_strict_styles() {
+    let src = rows(100);
+    let default = util::run_stdout(&src, &["-n", "200", "-i", "csv"]);
+    assert!(default.starts_with("id,name,city\n"), "header: {default}");
+    assert!(default.lines().any(|l| l == "…"), "row marker: {default}");
+    let detailed =
+        util::run_stdout(&src, &["-n", "200", "-i", "csv", "-t", "detailed"]);
+    assert!(detailed.contains("more rows …"), "count: {detailed}");
+    let strict =
+        util::run_stdout(&src, &["-n", "200", "-i", "csv", "-t", "strict"]);
+    assert!(!strict.lines().any(|l| l == "…"), "no markers: {strict}");
+}
+
+#[test]
+fn strict_drops_rows_with_cut_cells() {
+    let mut src = String::from("name,note\n");
+    for i in 0..50 {
+        src.push_str(&format!("person{i},a much longer note number {i}\n"));
+    }
+    let out =
+        util::run_stdout(&src, &["-n", "300", "-i", "csv", "-t", "strict"]);
+    let mut lines = out.trim_end().lines();
+    assert_eq!(lines.next(), Some("name,note"), "{out}");
+    for line in lines {
+        let (name, note) = line.split_once(',').expect("two cells");
+        let i = name.strip_prefix("person").expect("whole name");
+        assert_eq!(note, format!("a much longer note number {i}"), "{out}");
+    }
+}
+
+#[test]
+fn detailed_counts_a_single_omitted_row() {
+    let out = util::run_stdout(
+        &rows(2),
+        &["-n", "40", "-i", "csv", "-t", "detailed", "--tail"],
+    );
+    assert!(out.contains("… 1 more row …"), "{out}");
+}
+
+#[test]
+fn duplicate_headers_keep_every_column() {
+    let out =
+        util::run_stdout("a,a,b\n1,2,3\n", &["-n", "10000", "-i", "csv"]);
+    assert_eq!(out.trim_end(), "a,a_2,b\n1,2,3");
+}
+
+#[test]
+fn header_only_file_keeps_its_header_row() {
+    for style in ["strict", "default", "detailed"] {
+        let out = util::run_stdout(
+            "name,\"a,b\"\n",
+            &["-n", "10000", "-i", "csv", "-t", style],
+        );
+        assert_eq!(out.trim_end(), "name,\"a,b\"", "{style}");
+    }
+    let json = util::run_stdout("name\n", &["-i", "csv", "-f", "json"]);
+    assert_eq!(parse_json(&json), serde_json::json!([]));
+}
+
+#[test]
+fn tsv_extension_is_detected() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let p = dir.path().join("data.tsv");
+    fs::write(&p, "a\tb\nx y\t2\n").unwrap();
+    let out = util::run_stdout("", &["-n", "10000", p.to_str().unwrap()]);
+    assert_eq!(out.trim_end(), "a\tb\nx y\t2");
+}
+
+#[test]
+fn fileset_renders_csv_entries_as_csv() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let a = dir.path().join("a.json");
+    let b = dir.path().join("b.csv");
+    fs::write(&a, b"{\"k\": 1}").unwrap();
+    fs::write(&b, rows(3)).unwrap();
+    let out = util::run_stdout(
+        "",
+        &["-n", "10000", a.to_str().unwrap(), b.to_str().unwrap()],
+    );
+    let after_csv = out.split("b.csv").nth(1).expect("csv section");
+    assert!(after_csv.contains("id,name,city\n0,user0,town0"), "{out}");
+}
+
+#[test]
+fn json_arrays_of_objects_render_as_csv() {
+    let out = util::run_stdout(
+        r#"[{"a": 1, "b": {"c": [1, 2]}}, {"a": 2, "d": "x"}]"#,
+        &["-n", "10000", "-f", "csv"],
+    );
+    assert_eq!(out.trim_end(), "a,b,d\n1,\"{\"\"c\"\": [1, 2]}\",\n2,,x");
+}
@@ -0,0 +1,44 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+
+const INPUT: &str =
+    r#"{"users": [{"id": 1, "name": "a", "name": "b"}], "id": 7}"#;
+
+fn run(args: &[&str]) -> (String, String) {
+    let (ok, out, err) = util::run_capture(INPUT.as_bytes(), args);
+    assert!(ok, "{}", String::from_utf8_lossy(&err));
+    (
+        String::from_utf8(out).expect("utf8"),
+        String::from_utf8(err).expect("utf8"),
+    )
+}
+
+#[test]
+fn every_occurrence_is_kept_by_default() {
+    let (out, err) = run(&["-n", "1000"]);
+    assert!(out.contains("\"a\"") && out.contains("\"b\""), "{out}");
+    assert!(err.is_empty(), "{err}");
+}
+
+#[test]
+fn keep_first_and_keep_last_pick_one_value() {
+    let args = ["-f", "json", "-t", "strict", "-n", "1000"];
+    let (first, _) =
+        run(&[&args[..], &["--duplicate-keys", "first"]].concat());
+    let (last, _) = run(&[&args[..], &["--duplicate-keys", "last"]].concat());
+    let first: serde_json::Value = serde_json::from_str(&first).expect("json");
+    let last: serde_json::Value = serde_json::from_str(&last).expect("json");
+    assert_eq!(first["users"][0]["name"], "a");
+    assert_eq!(last["users"][0]["name"], "b");
+}
+
+#[test]
+fn duplicates_are_reported_with_their_path() {
+    let (out, err) =
+        run(&["-t", "detailed", "-n", "1000", "--duplicate-keys", "last"]);
+    assert!(out.contains("/* duplicate keys: \"name\" */"), "{out}");
+    assert_eq!(
+        err.trim(),
+        "Duplicate key .users[0].name: kept the last of 2 values"
+    );
+}
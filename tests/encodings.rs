@@ -0,0 +1,51 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+
+fn utf16le_with_bom(text: &str) -> Vec<u8> {
+    let mut bytes = vec![0xFF, 0xFE];
+    bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    bytes
+}
+
+fn run(input: &[u8], args: &[&str]) -> String {
+    let (ok, out, err) = util::run_capture(input, args);
+    assert!(ok, "{}", String::from_utf8_lossy(&err));
+    String::from_utf8(out).expect("utf8 output")
+}
+
+#[test]
+fn utf16_json_with_bom_is_transcoded() {
+    let input = utf16le_with_bom(r#"{"name": "Zoë", "tags": ["a", "b"]}"#);
+    let out = run(&input, &["-f", "json", "-t", "strict", "-n", "1000"]);
+    let v: serde_json::Value = serde_json::from_str(&out).expect("json");
+    assert_eq!(v["name"], "Zoë");
+    assert_eq!(v["tags"], serde_json::json!(["a", "b"]));
+}
+
+#[test]
+fn utf16_without_bom_is_detected_in_auto_mode() {
+    let input: Vec<u8> = "name: Zoë\ncount: 2\n"
+        .encode_utf16()
+        .flat_map(u16::to_be_bytes)
+        .collect();
+    let out = run(&input, &["-n", "1000"]);
+    assert!(out.contains("name: Zoë"), "{out}");
+    assert!(out.contains("count: 2"), "{out}");
+}
+
+#[test]
+fn encoding_flag_decodes_legacy_input() {
+    let out = run(
+        b"city,temp\nM\xFCnchen,12\n",
+        &["-i", "csv", "-f", "csv", "--encoding", "latin1"],
+    );
+    assert!(out.contains("München,12"), "{out}");
+}
+
+#[test]
+fn unknown_encoding_labels_are_rejected() {
+    let (ok, _, err) =
+        util::run_capture(b"{}", &["--encoding", "no-such-charset"]);
+    assert!(!ok);
+    assert!(String::from_utf8_lossy(&err).contains("no-such-charset"));
+}
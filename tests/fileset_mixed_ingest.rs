@@ -0,0 +1,57 @@
+use std::fs;
+
+fn run_auto(paths: &[&std::path::Path]) -> String {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("headson");
+    let mut args: Vec<String> = ["--no-color", "-n", "10000", "-f", "auto"]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    args.extend(paths.iter().map(|p| p.to_str().unwrap().to_string()));
+    let assert = cmd.args(args).assert().success();
+    String::from_utf8_lossy(&assert.get_output().stdout).into_owned()
+}
+
+#[test]
+fn mixed_fileset_parses_each_file_with_its_own_ingest() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let a = dir.path().join("a.json");
+    let b = dir.path().join("b.yaml");
+    let c = dir.path().join("notes.txt");
+    let d = dir.path().join("events.jsonl");
+    fs::write(&a, b"{\"name\": \"svc\", \"ports\": [80, 443]}").unwrap();
+    fs::write(&b, b"replicas: 3\nimage: nginx\n").unwrap();
+    fs::write(&c, b"first line\nsecond: not yaml\n").unwrap();
+    fs::write(&d, b"{\"id\":1}\n{\"id\":2}\n").unwrap();
+    let out = run_auto(&[&a, &b, &c, &d]);
+
+    // JSON file rendered structurally in the JSON family (pseudo).
+    assert!(out.contains("\"name\": \"svc\""), "json section: {out}");
+    // YAML file rendered as YAML mapping.
+    assert!(out.contains("replicas: 3"), "yaml section: {out}");
+    // Text file rendered verbatim as lines.
+    assert!(out.contains("second: not yaml\n"), "text section: {out}");
+    // JSON Lines file rendered as an array of records.
+    assert!(out.contains("\"id\": 2"), "jsonl section: {out}");
+}
+
+#[test]
+fn unparsable_file_is_shown_as_text_with_a_notice() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let a = dir.path().join("good.yaml");
+    let b = dir.path().join("broken.json");
+    fs::write(&a, b"k: v\n").unwrap();
+    fs::write(&b, b"{\"unterminated\": ").unwrap();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("headson");
+    let assert = cmd
+        .args(["--no-color", a.to_str().unwrap(), b.to_str().unwrap()])
+        .assert()
+        .success();
+    let out = String::from_utf8_lossy(&assert.get_output().stdout);
+    let err = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(out.contains("k: v"), "yaml section: {out}");
+    assert!(out.contains("{\"unterminated\": "), "text section: {out}");
+    assert_eq!(
+        err.trim_end(),
+        format!("Could not parse, shown as text: {}", b.display())
+    );
+}
@@ -0,0 +1,44 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+#[test]
+#[allow(
+    clippy::cognitive_complexity,
+    reason = "test performs several assertions succinctly"
+)]
+fn files_beyond_the_global_budget_are_counted_but_not_read() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    for i in 0..40 {
+        let body = format!("{{\"id\": {i}, \"tags\": [\"a\", \"b\"]}}");
+        fs::write(dir.path().join(format!("f{i:02}.json")), body).unwrap();
+    }
+    // Sorts last, so it is never read and never reported as binary.
+    fs::write(dir.path().join("zz.bin"), [0u8, 1, 2, 0xff]).unwrap();
+    let root = dir.path().to_str().unwrap();
+    let (ok, out, err) =
+        util::run_capture(b"", &["-r", "-N", "400", "-f", "json", root]);
+    assert!(ok);
+    let out = String::from_utf8(out).unwrap();
+    let shown = out.matches(".json <==").count();
+    assert!(shown > 0 && shown < 40, "{out}");
+    let summary = format!("==> {} more files <==", 41 - shown);
+    assert!(out.trim_end().ends_with(&summary), "{out}");
+    assert!(String::from_utf8(err).unwrap().is_empty());
+}
+
+#[test]
+fn a_generous_budget_still_reads_every_file() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    fs::write(dir.path().join("b.json"), "{\"b\": 1}").unwrap();
+    fs::write(dir.path().join("a.yaml"), "a: 1\n").unwrap();
+    fs::write(dir.path().join("c.bin"), [0u8, 1, 2, 0xff]).unwrap();
+    let root = dir.path().to_str().unwrap();
+    let (ok, out, err) = util::run_capture(b"", &["-r", "-N", "10000", root]);
+    assert!(ok);
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("a: 1") && out.contains("\"b\": 1"), "{out}");
+    assert!(!out.contains("more files"), "{out}");
+    let err = String::from_utf8(err).unwrap();
+    assert!(err.contains("Ignored binary file:"), "{err}");
+}
@@ -0,0 +1,31 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+#[test]
+fn terraform_files_are_detected_and_keep_expressions_verbatim() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let path = dir.path().join("main.tf");
+    fs::write(
+        &path,
+        "resource \"aws_s3_bucket\" \"logs\" {\n  bucket = \"logs-${var.env}\"\n  count  = var.enabled ? 1 : 0\n}\n",
+    )
+    .unwrap();
+    let out = util::run_stdout("", &["-n", "10000", path.to_str().unwrap()]);
+    assert!(out.contains("\"aws_s3_bucket\": {"), "{out}");
+    assert!(out.contains("\"bucket\": \"logs-${var.env}\""), "{out}");
+    assert!(out.contains("\"count\": var.enabled ? 1 : 0"), "{out}");
+}
+
+#[test]
+fn repeated_blocks_become_arrays() {
+    let out = util::run_stdout(
+        "rule { port = 80 }\nrule { port = 443 }\n",
+        &["-n", "10000", "-i", "hcl", "-f", "json", "-t", "strict"],
+    );
+    let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({"rule": [{"port": 80}, {"port": 443}]})
+    );
+}
@@ -0,0 +1,71 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+const TSCONFIG: &str = r#"{
+  // Type-check only.
+  "compilerOptions": {
+    "strict": true,
+    "noEmit": true, /* no output */
+  },
+  "include": ["src",],
+}
+"#;
+
+fn parse_json(s: &str) -> serde_json::Value {
+    serde_json::from_str(s)
+        .unwrap_or_else(|e| panic!("invalid JSON output: {e}\n{s}"))
+}
+
+#[test]
+fn json_file_with_comments_falls_back_to_json5() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let p = dir.path().join("tsconfig.json");
+    fs::write(&p, TSCONFIG).unwrap();
+    let out = util::run_stdout(
+        "",
+        &["-n", "10000", "-t", "strict", p.to_str().unwrap()],
+    );
+    assert_eq!(
+        parse_json(&out),
+        serde_json::json!({
+            "compilerOptions": {"strict": true, "noEmit": true},
+            "include": ["src"],
+        })
+    );
+}
+
+#[test]
+fn jsonc_alias_reads_stdin() {
+    let input = "{unquoted: 'single', hex: 0x1F, list: [.5, +1,],}";
+    let out = util::run_stdout(
+        input,
+        &["-i", "jsonc", "-f", "json", "-t", "strict", "-n", "10000"],
+    );
+    assert_eq!(
+        parse_json(&out),
+        serde_json::json!({"unquoted": "single", "hex": 31, "list": [0.5, 1]})
+    );
+}
+
+#[test]
+fn json5_extension_and_comment_sniffing() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let p = dir.path().join("config.json5");
+    fs::write(&p, "{a: 1, /* b */ c: [1, 2,],}").unwrap();
+    let out = util::run_stdout(
+        "",
+        &["-n", "10000", "-t", "strict", p.to_str().unwrap()],
+    );
+    assert_eq!(parse_json(&out), serde_json::json!({"a": 1, "c": [1, 2]}));
+    let sniffed = util::run_stdout(TSCONFIG, &["-n", "10000", "-t", "strict"]);
+    assert_eq!(parse_json(&sniffed)["include"], serde_json::json!(["src"]));
+}
+
+#[test]
+fn explicit_json_stays_strict() {
+    let (ok, _out, err) =
+        util::run_capture(TSCONFIG.as_bytes(), &["-i", "json"]);
+    assert!(!ok, "comments are rejected by -i json");
+    assert!(!err.is_empty());
+}
@@ -0,0 +1,64 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+
+#[test]
+fn numbers_print_exactly_as_written() {
+    let input = r#"{"big": 12345678901234567890.123, "id": 123456789012345678901234567890, "price": 10.50, "tiny": 1E-400, "n": [1, 2.5]}"#;
+    let out =
+        util::run_stdout(input, &["-f", "json", "-t", "strict", "-n", "1000"]);
+    for lexeme in [
+        "12345678901234567890.123",
+        "123456789012345678901234567890",
+        "10.50",
+        "1E-400",
+        "2.5",
+    ] {
+        assert!(out.contains(lexeme), "{lexeme} in {out}");
+    }
+}
+
+#[test]
+fn exact_numbers_work_in_concatenated_values() {
+    let out = util::run_stdout(
+        "{\"v\": 0.10}\n{\"v\": 18446744073709551616}\n",
+        &["-f", "json", "-t", "strict", "-n", "1000"],
+    );
+    assert!(out.contains("0.10") && out.contains("18446744073709551616"));
+}
+
+#[test]
+fn negative_zero_keeps_its_sign() {
+    let out = util::run_stdout("[-0]", &["-f", "json", "-t", "strict"]);
+    assert_eq!(out.trim_end(), "[\n  -0\n]");
+}
+
+#[test]
+fn json_lines_keep_number_lexemes() {
+    let out = util::run_stdout(
+        "{\"a\":1.50,\"b\":-0}\n{\"c\":12345678901234567890.123}\n",
+        &["-i", "jsonl", "-f", "json", "-t", "strict", "-n", "1000"],
+    );
+    for lexeme in ["1.50", "-0", "12345678901234567890.123"] {
+        assert!(out.contains(lexeme), "{lexeme} in {out}");
+    }
+}
+
+#[test]
+fn numbers_beyond_f64_keep_their_text() {
+    let out = util::run_stdout(
+        "{\"b\":1e400}\n{\"c\":[-1E+999]}",
+        &["-f", "json", "-t", "strict", "-n", "1000"],
+    );
+    assert!(out.contains("1e400") && out.contains("-1E+999"), "{out}");
+}
+
+#[test]
+fn json5_numbers_keep_their_text() {
+    let out = util::run_stdout(
+        "// c\n{a: 1.50, b: 12345678901234567890.123, c: -0, d: 0x10}",
+        &["-i", "json5", "-f", "json", "-t", "strict", "-n", "1000"],
+    );
+    for lexeme in ["1.50", "12345678901234567890.123", "-0", "16"] {
+        assert!(out.contains(lexeme), "{lexeme} in {out}");
+    }
+}
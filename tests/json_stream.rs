@@ -0,0 +1,61 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+fn strict(input: &str, extra: &[&str]) -> serde_json::Value {
+    let mut args = vec!["-f", "json", "-t", "strict", "-n", "10000"];
+    args.extend_from_slice(extra);
+    let out = util::run_stdout(input, &args);
+    serde_json::from_str(&out).expect("strict json output")
+}
+
+#[test]
+fn concatenated_values_become_root_array() {
+    let v = strict("{\"a\":1}{\"a\":2}[3]\"s\\u00e9\" 4\n", &[]);
+    assert_eq!(v, serde_json::json!([{"a": 1}, {"a": 2}, [3], "sé", 4]));
+}
+
+#[test]
+fn pretty_printed_stream_is_sniffed_in_auto_mode() {
+    let input = "{\n  \"id\": 1\n}\n{\n  \"id\": 2\n}\n";
+    let out = util::run_stdout(input, &["-n", "10000", "-t", "strict"]);
+    let v: serde_json::Value = serde_json::from_str(&out).expect("json");
+    assert_eq!(v, serde_json::json!([{"id": 1}, {"id": 2}]));
+}
+
+#[test]
+fn stream_in_json_file_of_fileset() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let a = dir.path().join("events.json");
+    let b = dir.path().join("single.json");
+    fs::write(&a, "{\"e\":1} {\"e\":2}").unwrap();
+    fs::write(&b, "{\"k\":true}").unwrap();
+    let out = util::run_stdout(
+        "",
+        &[
+            "-f",
+            "json",
+            "-t",
+            "strict",
+            "-n",
+            "10000",
+            a.to_str().unwrap(),
+            b.to_str().unwrap(),
+        ],
+    );
+    let section = out
+        .split("==>")
+        .find(|s| s.contains("events.json"))
+        .expect("events section");
+    let body = section.split_once("<==").expect("header").1;
+    let v: serde_json::Value =
+        serde_json::from_str(body.trim()).expect("json");
+    assert_eq!(v, serde_json::json!([{"e": 1}, {"e": 2}]));
+}
+
+#[test]
+fn garbage_after_value_still_fails() {
+    let (ok, _out, err) = util::run_capture(b"{\"a\":1} ]", &["-f", "json"]);
+    assert!(!ok);
+    assert!(!err.is_empty());
+}
@@ -0,0 +1,107 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+fn events(n: usize) -> String {
+    (0..n)
+        .map(|i| format!("{{\"id\":{i},\"level\":\"info\"}}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn stdin_jsonl_renders_records_as_array() {
+    let input = "{\"a\":1}\n{\"a\":2}\n\n{\"a\":3}\n";
+    let out =
+        util::run_template_budget(input, "json", 10_000, &["-i", "jsonl"]);
+    let v: serde_json::Value =
+        serde_json::from_str(&out).expect("strict json output");
+    assert_eq!(v, serde_json::json!([{"a": 1}, {"a": 2}, {"a": 3}]));
+}
+
+#[test]
+fn ndjson_alias_is_accepted() {
+    let out = util::run_template_budget(
+        "[1]\n[2]\n",
+        "json",
+        10_000,
+        &["-i", "ndjson"],
+    );
+    let v: serde_json::Value =
+        serde_json::from_str(&out).expect("strict json output");
+    assert_eq!(v, serde_json::json!([[1], [2]]));
+}
+
+#[test]
+fn jsonl_tail_keeps_last_records() {
+    let input = events(200);
+    let out = util::run_template_budget(
+        &input,
+        "json",
+        120,
+        &["-i", "jsonl", "--tail"],
+    );
+    let v: serde_json::Value =
+        serde_json::from_str(&out).expect("strict json output");
+    let arr = v.as_array().expect("root array");
+    assert!(!arr.is_empty() && arr.len() < 200);
+    let last = arr.last().unwrap();
+    assert_eq!(last["id"], serde_json::json!(199));
+}
+
+#[test]
+fn invalid_record_reports_line_number() {
+    let (ok, _out, err) =
+        util::run_capture(b"{\"a\":1}\n{oops}\n", &["-i", "jsonl"]);
+    assert!(!ok, "invalid record should fail");
+    let err = String::from_utf8_lossy(&err);
+    assert!(err.contains("line 2"), "stderr should name the line: {err}");
+}
+
+#[test]
+fn single_file_auto_detects_jsonl_extensions() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    for name in ["events.jsonl", "events.ndjson"] {
+        let p = dir.path().join(name);
+        fs::write(&p, events(3)).unwrap();
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("headson");
+        let assert = cmd
+            .args([
+                "--no-color",
+                "-n",
+                "10000",
+                "-t",
+                "strict",
+                p.to_str().unwrap(),
+            ])
+            .assert()
+            .success();
+        let out = String::from_utf8_lossy(&assert.get_output().stdout);
+        let v: serde_json::Value =
+            serde_json::from_str(&out).expect("strict json output");
+        assert_eq!(v.as_array().map(Vec::len), Some(3), "{name}");
+    }
+}
+
+#[test]
+fn fileset_of_jsonl_files_uses_jsonl_ingest() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let a = dir.path().join("a.jsonl");
+    let b = dir.path().join("b.jsonl");
+    fs::write(&a, events(2)).unwrap();
+    fs::write(&b, events(4)).unwrap();
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("headson");
+    let assert = cmd
+        .args([
+            "--no-color",
+            "-n",
+            "10000",
+            a.to_str().unwrap(),
+            b.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    let out = String::from_utf8_lossy(&assert.get_output().stdout);
+    assert!(out.contains("==> ") && out.contains("a.jsonl"));
+    assert!(out.contains("\"level\": \"info\""), "structured: {out:?}");
+}
@@ -0,0 +1,75 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+fn parse_json(s: &str) -> serde_json::Value {
+    serde_json::from_str(s)
+        .unwrap_or_else(|e| panic!("invalid JSON output: {e}\n{s}"))
+}
+
+fn strict(input: &str, format: &str, extra: &[&str]) -> serde_json::Value {
+    let args = [
+        &["-n", "10000", "-i", format, "-f", "json", "-t", "strict"],
+        extra,
+    ]
+    .concat();
+    parse_json(&util::run_stdout(input, &args))
+}
+
+#[test]
+fn ini_sections_render_as_toml_tables() {
+    let out = util::run_stdout(
+        "name = app\n[server]\nport = 8080\n",
+        &["-n", "10000", "-i", "ini"],
+    );
+    assert_eq!(
+        out.trim_end(),
+        "name = \"app\"\n\n[server]\nport = \"8080\""
+    );
+}
+
+#[test]
+fn typed_values_apply_to_every_key_value_format() {
+    let expected = serde_json::json!({"PORT": 8080, "DEBUG": true});
+    let env = "PORT=8080\nDEBUG=true\n";
+    assert_eq!(strict(env, "dotenv", &["--typed-values"]), expected);
+    let props = "PORT: 8080\nDEBUG = true\n";
+    assert_eq!(strict(props, "properties", &["--typed-values"]), expected);
+    assert_eq!(
+        strict(env, "env", &[]),
+        serde_json::json!({"PORT": "8080", "DEBUG": "true"})
+    );
+}
+
+#[test]
+fn properties_nest_dotted_keys_on_request() {
+    let props = "db.host=x\ndb.pool.size=5\n";
+    assert_eq!(
+        strict(props, "properties", &[]),
+        serde_json::json!({"db.host": "x", "db.pool.size": "5"})
+    );
+    assert_eq!(
+        strict(props, "properties", &["--nest-dotted-keys"]),
+        serde_json::json!({"db": {"host": "x", "pool": {"size": "5"}}})
+    );
+}
+
+#[test]
+fn config_files_are_detected_by_name() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let env = dir.path().join(".env.production");
+    let props = dir.path().join("app.properties");
+    fs::write(&env, "export TOKEN='abc'\n").unwrap();
+    fs::write(&props, "greeting = hello\n").unwrap();
+    let out = util::run_stdout(
+        "",
+        &[
+            "-n",
+            "10000",
+            env.to_str().unwrap(),
+            props.to_str().unwrap(),
+        ],
+    );
+    assert!(out.contains("TOKEN = \"abc\""), "{out}");
+    assert!(out.contains("greeting = \"hello\""), "{out}");
+}
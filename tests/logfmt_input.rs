@@ -0,0 +1,38 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+
+fn parse_json(s: &str) -> serde_json::Value {
+    serde_json::from_str(s)
+        .unwrap_or_else(|e| panic!("invalid JSON output: {e}\n{s}"))
+}
+
+const LOG: &str = "time=1 level=info msg=\"listening\" port=8080\n\
+                   time=2 level=error msg=\"db down\" retries=3\n\
+                   goroutine 1 [running]:\n";
+
+#[test]
+fn logfmt_lines_become_records() {
+    let out = util::run_stdout(
+        LOG,
+        &["-n", "10000", "-i", "logfmt", "-f", "json", "-t", "strict"],
+    );
+    assert_eq!(
+        parse_json(&out),
+        serde_json::json!([
+            {"time": "1", "level": "info", "msg": "listening", "port": "8080"},
+            {"time": "2", "level": "error", "msg": "db down", "retries": "3"},
+            "goroutine 1 [running]:"
+        ])
+    );
+}
+
+#[test]
+fn logfmt_is_sniffed_from_stdin_and_typed_on_request() {
+    let out = util::run_stdout(
+        LOG,
+        &["-n", "10000", "--typed-values", "-t", "strict"],
+    );
+    let records = parse_json(&out);
+    assert_eq!(records[0]["port"], serde_json::json!(8080));
+    assert_eq!(records[1]["msg"], serde_json::json!("db down"));
+}
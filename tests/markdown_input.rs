@@ -0,0 +1,78 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+const DOC: &str = "\
+---
+title: Design notes
+---
+# Cache
+
+Entries expire
+after an hour.
+
+## Options
+
+- `ttl`: lifetime
+- `size`: capacity
+
+```toml
+ttl = 3600
+```
+
+## Limits
+";
+
+fn parse_json(s: &str) -> serde_json::Value {
+    serde_json::from_str(s)
+        .unwrap_or_else(|e| panic!("invalid JSON output: {e}\n{s}"))
+}
+
+#[test]
+fn sections_nest_under_their_headings() {
+    let out = util::run_stdout(
+        DOC,
+        &[
+            "-n", "10000", "-i", "markdown", "-f", "json", "-t", "strict",
+        ],
+    );
+    assert_eq!(
+        parse_json(&out),
+        serde_json::json!({
+            "#front_matter": {"title": "Design notes"},
+            "Cache": {
+                "#content": ["Entries expire after an hour."],
+                "Options": {
+                    "#content": [
+                        ["`ttl`: lifetime", "`size`: capacity"],
+                        "```toml\nttl = 3600\n```"
+                    ]
+                },
+                "Limits": {}
+            }
+        })
+    );
+}
+
+#[test]
+fn md_extension_is_detected() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let p = dir.path().join("notes.md");
+    fs::write(&p, DOC).unwrap();
+    let out = util::run_stdout("", &["-n", "10000", p.to_str().unwrap()]);
+    assert!(out.contains("\"Options\": {"), "{out}");
+    let keys: Vec<usize> = ["#front_matter", "Cache", "Options", "Limits"]
+        .iter()
+        .map(|k| out.find(&format!("\"{k}\"")).expect(k))
+        .collect();
+    assert!(keys.windows(2).all(|w| w[0] < w[1]), "source order: {out}");
+}
+
+#[test]
+fn small_budgets_keep_the_outline() {
+    let out = util::run_stdout(DOC, &["-n", "150", "-i", "md"]);
+    for heading in ["\"Cache\"", "\"Options\"", "\"Limits\""] {
+        assert!(out.contains(heading), "{heading} kept: {out}");
+    }
+    assert!(!out.contains("ttl = 3600"), "{out}");
+}
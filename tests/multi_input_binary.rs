@@ -60,7 +60,7 @@ fn binary_file_is_ignored_and_reported_in_stderr() {
     reason = "test composes several assertions; splitting would reduce clarity"
 )]
 #[test]
-fn multiple_binary_files_each_reported_once_at_end() {
+fn multiple_binary_files_summarized_once_at_end() {
     let dir = tempdir().expect("tempdir");
 
     let json_path = dir.path().join("ok.json");
@@ -84,12 +84,6 @@ fn multiple_binary_files_each_reported_once_at_end() {
     assert!(!out.contains(&format!("==> {bin1_s} <==")));
     assert!(!out.contains(&format!("==> {bin2_s} <==")));
 
-    let lines: Vec<&str> = err.trim().lines().collect();
-    assert!(
-        lines.len() >= 2,
-        "stderr should have at least two lines: {err}"
-    );
-    let last_two = &lines[lines.len().saturating_sub(2)..];
-    assert_eq!(last_two[0], format!("Ignored binary file: {bin1_s}"));
-    assert_eq!(last_two[1], format!("Ignored binary file: {bin2_s}"));
+    let last = err.trim().lines().last().unwrap_or_default();
+    assert_eq!(last, format!("Ignored 2 binary files: {bin1_s}, {bin2_s}"));
 }
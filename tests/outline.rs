@@ -0,0 +1,72 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+const PYTHON: &str = "\
+import os
+
+
+class Greeter:
+    def __init__(self, name):
+        self.name = name
+        self.count = 0
+
+    def greet(self):
+        self.count += 1
+        return self.name
+
+
+def main():
+    for i in range(10):
+        print(Greeter(\"x\").greet())
+";
+
+#[test]
+fn outline_round_trips_with_a_large_budget() {
+    let out = util::run_stdout(PYTHON, &["-n", "10000", "-i", "outline"]);
+    assert_eq!(out.trim_end(), PYTHON.trim_end());
+}
+
+#[test]
+fn bodies_are_elided_before_top_level_lines() {
+    let out = util::run_stdout(PYTHON, &["-n", "120", "-i", "code"]);
+    for line in ["import os", "class Greeter:", "def main():"] {
+        assert!(out.lines().any(|l| l == line), "{line} kept: {out}");
+    }
+    assert!(!out.contains("self.count += 1"), "{out}");
+    assert!(out.lines().any(|l| l == "    …"), "indented marker: {out}");
+}
+
+#[test]
+fn detailed_markers_count_the_omitted_lines() {
+    let out = util::run_stdout(
+        PYTHON,
+        &["-n", "120", "-i", "outline", "-t", "detailed"],
+    );
+    assert!(
+        out.lines()
+            .any(|l| l.starts_with("    … ") && l.ends_with(" more lines …")),
+        "{out}"
+    );
+}
+
+#[test]
+fn source_files_are_outlined_only_on_request() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let p = dir.path().join("main.rs");
+    let src = "fn main() {\n    if ready() {\n        run();\n        run();\n        run();\n    } else {\n        wait();\n    }\n}\n\nfn ready() -> bool {\n    true\n}\n";
+    fs::write(&p, src).unwrap();
+    let path = p.to_str().unwrap();
+    let outline = util::run_stdout("", &["-n", "70", "-i", "outline", path]);
+    assert!(outline.starts_with("fn main() {\n"), "{outline}");
+    assert!(
+        outline.lines().any(|l| l == "fn ready() -> bool {"),
+        "{outline}"
+    );
+    assert!(!outline.contains("run();"), "{outline}");
+    let text = util::run_stdout("", &["-n", "70", path]);
+    assert!(
+        text.lines().nth(1).is_some_and(|l| l.starts_with("    if")),
+        "read as text: {text}"
+    );
+}
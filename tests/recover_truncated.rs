@@ -0,0 +1,49 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+
+const CUT_OFF: &str =
+    r#"{"name": "x", "items": [1, 2, {"a": [true, "unterminated"#;
+
+#[test]
+fn truncated_input_is_previewed_with_recover() {
+    let (ok, out, err) = util::run_capture(
+        CUT_OFF.as_bytes(),
+        &["--recover", "-f", "json", "-t", "strict", "-n", "10000"],
+    );
+    assert!(ok, "{}", String::from_utf8_lossy(&err));
+    let v: serde_json::Value =
+        serde_json::from_slice(&out).expect("strict json output");
+    assert_eq!(
+        v,
+        serde_json::json!({"name": "x", "items": [1, 2, {"a": [true]}]})
+    );
+    let err = String::from_utf8_lossy(&err);
+    assert!(
+        err.contains(&format!("at byte {}", CUT_OFF.len())),
+        "offset reported: {err}"
+    );
+}
+
+#[test]
+fn detailed_style_marks_truncated_containers() {
+    let out = util::run_stdout(
+        "[1, 2, oops, 4]",
+        &["--recover", "-f", "json", "-t", "detailed", "-n", "10000"],
+    );
+    assert_eq!(out.matches("/* truncated input */").count(), 1, "{out}");
+    assert!(out.contains('2') && !out.contains('4'), "{out}");
+}
+
+#[test]
+fn truncated_input_fails_without_recover() {
+    let (ok, _out, _err) =
+        util::run_capture(CUT_OFF.as_bytes(), &["-f", "json"]);
+    assert!(!ok);
+}
+
+#[test]
+fn valid_input_has_no_recovery_notice() {
+    let (ok, _out, err) = util::run_capture(b"{\"a\": [1]}", &["--recover"]);
+    assert!(ok);
+    assert!(err.is_empty(), "{}", String::from_utf8_lossy(&err));
+}
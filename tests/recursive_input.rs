@@ -0,0 +1,103 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+use std::path::Path;
+
+fn tree() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let root = dir.path();
+    for (path, bytes) in [
+        ("conf/app.yaml", &b"name: app\n"[..]),
+        ("data/one.json", b"{\"id\": 1}"),
+        ("data/two.json", b"{\"id\": 2}"),
+        ("build/out.json", b"{\"built\": true}"),
+        (".cache/c.json", b"{\"cached\": true}"),
+        (".gitignore", b"build/\n"),
+    ] {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, bytes).unwrap();
+    }
+    for name in ["a.bin", "b.bin", "c.bin"] {
+        fs::write(root.join("data").join(name), [0u8, 1, 2, 0xff]).unwrap();
+    }
+    dir
+}
+
+fn run(root: &Path, args: &[&str]) -> (String, String) {
+    let root = root.to_str().unwrap();
+    let mut cli_args = vec!["-n", "10000", "-r"];
+    cli_args.extend_from_slice(args);
+    cli_args.push(root);
+    let (ok, out, err) = util::run_capture(b"", &cli_args);
+    let err = String::from_utf8(err).unwrap();
+    assert!(ok, "{err}");
+    (String::from_utf8(out).unwrap(), err)
+}
+
+#[test]
+#[allow(
+    clippy::cognitive_complexity,
+    reason = "test performs several assertions succinctly"
+)]
+fn walks_tree_in_path_order_honoring_gitignore() {
+    let dir = tree();
+    let root = dir.path().to_str().unwrap().to_string();
+    let (out, err) = run(dir.path(), &[]);
+    let app = out.find(&format!("==> {root}/conf/app.yaml <==")).unwrap();
+    let one = out.find(&format!("==> {root}/data/one.json <==")).unwrap();
+    let two = out.find(&format!("==> {root}/data/two.json <==")).unwrap();
+    assert!(app < one && one < two, "{out}");
+    assert!(out.contains("name: app"), "{out}");
+    assert!(!out.contains("built") && !out.contains("cached"), "{out}");
+    assert_eq!(
+        err.trim(),
+        format!(
+            "Ignored 3 binary files: {root}/data/a.bin, {root}/data/b.bin, \
+             {root}/data/c.bin"
+        )
+    );
+}
+
+#[test]
+fn include_exclude_and_no_ignore_filter_the_walk() {
+    let dir = tree();
+    let (out, err) = run(
+        dir.path(),
+        &[
+            "--include",
+            "*.json",
+            "--exclude",
+            "two.json",
+            "--no-ignore",
+        ],
+    );
+    assert!(
+        out.contains("\"id\": 1") && !out.contains("\"id\": 2"),
+        "{out}"
+    );
+    assert!(out.contains("built") && out.contains("cached"), "{out}");
+    assert!(!out.contains("name: app"), "{out}");
+    assert!(err.trim().is_empty(), "{err}");
+}
+
+#[test]
+fn directories_without_recursive_are_summarized() {
+    let dir = tree();
+    let conf = dir.path().join("conf");
+    let data = dir.path().join("data");
+    let (ok, out, err) = util::run_capture(
+        b"",
+        &["-n", "100", conf.to_str().unwrap(), data.to_str().unwrap()],
+    );
+    assert!(ok);
+    assert_eq!(String::from_utf8(out).unwrap().trim(), "");
+    assert_eq!(
+        String::from_utf8(err).unwrap().trim(),
+        format!(
+            "Ignored 2 directories: {}, {}",
+            conf.display(),
+            data.display()
+        )
+    );
+}
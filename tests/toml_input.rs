@@ -0,0 +1,182 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+const CARGO: &str = r#"[package]
+name = "demo"
+version = "0.1.0"
+authors = ["a", "b"]
+
+[dependencies]
+anyhow = "1"
+serde = { version = "1", features = ["derive"] }
+
+[[bin]]
+name = "x"
+path = "src/x.rs"
+
+[[bin]]
+name = "y"
+
+[bin.meta]
+k = 1
+
+[profile.release]
+lto = true
+"#;
+
+fn parse(s: &str) -> toml::Table {
+    s.parse::<toml::Table>()
+        .unwrap_or_else(|e| panic!("invalid TOML output: {e}\n{s}"))
+}
+
+#[test]
+#[allow(
+    clippy::cognitive_complexity,
+    reason = "Single test covers multiple assertions compactly."
+)]
+fn toml_round_trips_through_toml_template() {
+    let out = util::run_stdout(CARGO, &["-n", "10000", "-i", "toml"]);
+    assert_eq!(parse(&out), parse(CARGO));
+    assert!(out.contains("[package]\n"), "table header: {out}");
+    assert!(out.contains("[[bin]]\n"), "array of tables: {out}");
+    assert!(out.contains("[dependencies.serde]\n"), "nested: {out}");
+    assert!(!out.contains("[profile]\n"), "implicit parent: {out}");
+}
+
+#[test]
+fn strict_truncated_output_stays_valid_toml() {
+    for budget in [40, 80, 160, 320] {
+        let budget = budget.to_string();
+        let out = util::run_stdout(
+            CARGO,
+            &["-n", &budget, "-i", "toml", "-t", "strict"],
+        );
+        parse(&out);
+    }
+}
+
+#[test]
+fn default_style_marks_omissions_with_comments() {
+    let out = util::run_stdout(CARGO, &["-n", "120", "-i", "toml"]);
+    assert!(out.contains("# …"), "omission comment: {out}");
+}
+
+#[test]
+fn json_input_renders_as_toml_with_inline_tables() {
+    let out = util::run_stdout(
+        r#"{"a": [{"b": 1}, 2], "c": {"d": [{"e": 1}]}}"#,
+        &["-n", "10000", "-f", "toml"],
+    );
+    assert!(out.contains("a = [{ b = 1 }, 2]"), "inline table: {out}");
+    assert!(out.contains("[[c.d]]"), "array of tables: {out}");
+    parse(&out);
+}
+
+#[test]
+fn tables_keep_their_source_order() {
+    let out = util::run_stdout(CARGO, &["-n", "10000", "-i", "toml"]);
+    let package = out.find("[package]").expect("package");
+    let dependencies = out.find("[dependencies]").expect("dependencies");
+    let profile = out.find("[profile.release]").expect("profile");
+    assert!(package < dependencies && dependencies < profile, "{out}");
+    let name = out.find("name = \"demo\"").expect("name");
+    let version = out.find("version = ").expect("version");
+    assert!(name < version, "{out}");
+}
+
+#[test]
+fn null_values_become_comments_or_are_left_out() {
+    let src = r#"{"d": null, "e": {"x": null, "y": [1, null]}}"#;
+    let out = util::run_stdout(src, &["-n", "10000", "-f", "toml"]);
+    assert!(out.starts_with("# d = null\n"), "comment: {out}");
+    assert!(out.contains("y = [1]"), "array item: {out}");
+    parse(&out);
+    let strict =
+        util::run_stdout(src, &["-n", "10000", "-f", "toml", "-t", "strict"]);
+    assert!(!strict.contains("null"), "left out: {strict}");
+    parse(&strict);
+}
+
+#[test]
+fn non_table_roots_are_wrapped_in_a_key() {
+    for (src, expected) in [
+        ("[1, 2, 3]", "value = [1, 2, 3]"),
+        ("\"scalar\"", "value = \"scalar\""),
+        (
+            "[{\"a\": 1}, {\"a\": 2}]",
+            "[[value]]\na = 1\n\n[[value]]\na = 2",
+        ),
+    ] {
+        let out = util::run_stdout(src, &["-n", "10000", "-f", "toml"]);
+        assert_eq!(out.trim_end(), expected);
+        parse(&out);
+    }
+}
+
+#[test]
+fn toml_extension_and_content_are_detected() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let toml_file = dir.path().join("Cargo.toml");
+    let lock_file = dir.path().join("Cargo.lock");
+    fs::write(&toml_file, CARGO).unwrap();
+    fs::write(&lock_file, "version = 4\n\n[[package]]\nname = \"a\"\n")
+        .unwrap();
+    for p in [&toml_file, &lock_file] {
+        let out = util::run_stdout("", &["-n", "10000", p.to_str().unwrap()]);
+        assert!(out.contains("name = \""), "toml body: {out}");
+        parse(&out);
+    }
+}
+
+#[test]
+fn fileset_renders_toml_entries_as_toml() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let a = dir.path().join("a.json");
+    let b = dir.path().join("b.toml");
+    fs::write(&a, b"{\"k\": 1}").unwrap();
+    fs::write(&b, CARGO).unwrap();
+    let out = util::run_stdout(
+        "",
+        &["-n", "10000", a.to_str().unwrap(), b.to_str().unwrap()],
+    );
+    let after_toml = out.split("b.toml").nth(1).expect("toml section");
+    assert!(after_toml.contains("[package]"), "toml section: {out}");
+    let before_toml = out.split("b.toml").next().unwrap();
+    assert!(before_toml.contains("\"k\": 1"), "json section: {out}");
+}
+
+#[test]
+fn invalid_toml_fails() {
+    let (ok, _out, err) = util::run_capture(b"a = \n", &["-i", "toml"]);
+    assert!(!ok, "invalid toml should fail");
+    assert!(!err.is_empty());
+}
+
+#[test]
+fn datetimes_stay_datetimes_in_toml_output() {
+    let src = "dob = 1979-05-27T07:32:00-08:00\nday = 1979-05-27\n\
+               at = [07:32:00]\n";
+    let out = util::run_stdout(src, &["-n", "10000", "-i", "toml"]);
+    assert!(out.contains("dob = 1979-05-27T07:32:00-08:00\n"), "{out}");
+    assert_eq!(parse(&out), parse(src));
+    let json =
+        util::run_stdout(src, &["-n", "10000", "-i", "toml", "-f", "json"]);
+    assert!(json.contains("\"day\": \"1979-05-27\""), "{json}");
+}
+
+#[test]
+fn omitted_tables_get_one_summary_line() {
+    let src = (1..=4)
+        .map(|i| format!("[[products]]\nname = \"p{i}\"\nsku = {i}\n\n"))
+        .collect::<String>();
+    let out = util::run_stdout(&src, &["-n", "60", "-i", "toml"]);
+    assert_eq!(out.matches("# [[products]] …").count(), 1, "{out}");
+    assert!(out.contains("\n\n# [[products]] …\n"), "{out}");
+    let detailed =
+        util::run_stdout(&src, &["-n", "60", "-i", "toml", "-t", "detailed"]);
+    assert!(
+        detailed.contains("\n\n# 3 more [[products]]\n"),
+        "{detailed}"
+    );
+}
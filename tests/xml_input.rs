@@ -0,0 +1,98 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+use std::fs;
+
+const POM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <artifactId>demo</artifactId>
+  <dependencies>
+    <dependency scope="test"><artifactId>junit</artifactId></dependency>
+    <dependency><artifactId>slf4j-api</artifactId></dependency>
+  </dependencies>
+</project>
+"#;
+
+fn parse_json(s: &str) -> serde_json::Value {
+    serde_json::from_str(s)
+        .unwrap_or_else(|e| panic!("invalid JSON output: {e}\n{s}"))
+}
+
+#[test]
+fn xml_elements_map_to_objects_and_arrays() {
+    let out = util::run_stdout(
+        POM,
+        &["-n", "10000", "-i", "xml", "-f", "json", "-t", "strict"],
+    );
+    assert_eq!(
+        parse_json(&out),
+        serde_json::json!({"project": {
+            "@xmlns": "http://maven.apache.org/POM/4.0.0",
+            "artifactId": "demo",
+            "dependencies": {"dependency": [
+                {"@scope": "test", "artifactId": "junit"},
+                {"artifactId": "slf4j-api"}
+            ]}
+        }})
+    );
+    let artifact = out.find("artifactId").unwrap();
+    let deps = out.find("dependencies").unwrap();
+    assert!(artifact < deps, "document order kept: {out}");
+}
+
+#[test]
+fn repeated_elements_are_sampled_like_arrays() {
+    let mut src = String::from("<log>");
+    for i in 0..200 {
+        src.push_str(&format!("<entry>line {i}</entry>"));
+    }
+    src.push_str("</log>");
+    let out = util::run_stdout(
+        &src,
+        &["-n", "300", "-i", "xml", "--tail", "-t", "strict"],
+    );
+    let v = parse_json(&out);
+    let entries = v["log"]["entry"].as_array().expect("entry array");
+    assert!(entries.len() < 200, "sampled: {out}");
+    assert_eq!(entries.last(), Some(&serde_json::json!("line 199")));
+}
+
+#[test]
+fn html_extension_and_content_are_detected() {
+    let page = "<!DOCTYPE html>\n<html><head><title>Hi</title>\
+                <meta charset=utf-8></head><body><p>a<br>b</body></html>";
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let file = dir.path().join("index.html");
+    fs::write(&file, page).unwrap();
+    let from_file = util::run_stdout(
+        "",
+        &["-n", "10000", "-t", "strict", file.to_str().unwrap()],
+    );
+    let from_stdin = util::run_stdout(page, &["-n", "10000", "-t", "strict"]);
+    assert_eq!(from_file, from_stdin);
+    let v = parse_json(&from_file);
+    assert_eq!(v["html"]["head"]["title"], "Hi");
+    assert_eq!(v["html"]["body"]["p"]["#text"], "a b");
+}
+
+#[test]
+fn fileset_mixes_xml_with_other_formats() {
+    let dir = tempfile::tempdir().expect("tmpdir");
+    let a = dir.path().join("pom.xml");
+    let b = dir.path().join("b.yaml");
+    fs::write(&a, POM).unwrap();
+    fs::write(&b, "k: v\n").unwrap();
+    let out = util::run_stdout(
+        "",
+        &["-n", "10000", a.to_str().unwrap(), b.to_str().unwrap()],
+    );
+    assert!(out.contains("\"artifactId\": \"demo\""), "xml entry: {out}");
+    assert!(out.contains("k: v"), "yaml entry: {out}");
+}
+
+#[test]
+fn malformed_xml_fails() {
+    let (ok, _out, err) =
+        util::run_capture(b"<a><b></a>", &["-i", "xml", "-n", "100"]);
+    assert!(!ok, "mismatched tags should fail");
+    assert!(!err.is_empty());
+}
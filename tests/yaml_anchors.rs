@@ -0,0 +1,53 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+
+const CI: &str = "\
+defaults: &defaults
+  image: rust:1.80
+  retries: 2
+build:
+  settings: *defaults
+  script: [cargo build]
+test:
+  settings: *defaults
+  script: [cargo test]
+";
+
+#[test]
+fn aliases_are_expanded_in_the_preview() {
+    let out = util::run_stdout(
+        CI,
+        &["-i", "yaml", "-f", "json", "-t", "strict", "-n", "10000"],
+    );
+    let v: serde_json::Value = serde_json::from_str(&out).expect("json");
+    let defaults = serde_json::json!({"image": "rust:1.80", "retries": 2});
+    assert_eq!(v["build"]["settings"], defaults);
+    assert_eq!(v["test"]["settings"], defaults);
+    assert!(!out.contains("*alias"), "{out}");
+}
+
+#[test]
+fn recursive_alias_stays_a_placeholder() {
+    let out = util::run_stdout(
+        "node: &n\n  child: *n\n",
+        &["-i", "yaml", "-f", "yaml", "-n", "10000"],
+    );
+    assert!(out.contains("\"*n\"") || out.contains("'*n'"), "{out}");
+}
+
+#[test]
+fn detailed_style_names_the_anchor_of_reused_subtrees() {
+    let args = ["-i", "yaml", "-f", "yaml", "-n", "10000", "-t"];
+    let detailed = util::run_stdout(CI, &[&args[..], &["detailed"]].concat());
+    assert!(
+        detailed.contains("defaults: # &defaults\n  image: \"rust:1.80\"\n"),
+        "{detailed}"
+    );
+    assert!(
+        detailed
+            .contains("  settings: # &defaults\n    image: \"rust:1.80\"\n"),
+        "{detailed}"
+    );
+    let default = util::run_stdout(CI, &[&args[..], &["default"]].concat());
+    assert!(!default.contains("&defaults"), "{default}");
+}
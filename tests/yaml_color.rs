@@ -46,11 +46,22 @@ fn yaml_coloring_applies_and_strips_to_plain() {
     let prio = PriorityConfig::new(usize::MAX, usize::MAX);
 
     let budget = 10_000usize;
-    let plain =
-        headson::headson_yaml(input.clone(), &cfg_plain, &prio, budget)
-            .expect("plain yaml");
-    let colored = headson::headson_yaml(input, &cfg_color, &prio, budget)
-        .expect("colored yaml");
+    let plain = headson::headson_yaml(
+        input.clone(),
+        &headson::IngestOptions::default(),
+        &cfg_plain,
+        &prio,
+        budget,
+    )
+    .expect("plain yaml");
+    let colored = headson::headson_yaml(
+        input,
+        &headson::IngestOptions::default(),
+        &cfg_color,
+        &prio,
+        budget,
+    )
+    .expect("colored yaml");
 
     // Contains ANSI SGR and specific roles (blue for keys, green for strings).
     assert!(
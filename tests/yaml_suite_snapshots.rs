@@ -50,10 +50,10 @@ fn yaml_snapshot_case(path: &Path) {
     let name = stem_str(path);
     let tight = run_cli_yaml_with_budget(&input, BUDGET_TIGHT);
     assert_snapshot!(format!("yaml_suite_{}_tight", name), tight);
-    let med = run_cli_yaml_with_budget(&input, BUDGET_MED);
-    assert_snapshot!(format!("yaml_suite_{}_med", name), med);
-    let full = run_cli_yaml_with_budget(&input, BUDGET_FULL);
-    assert_snapshot!(format!("yaml_suite_{}_full", name), full);
+    let out_med = run_cli_yaml_with_budget(&input, BUDGET_MED);
+    assert_snapshot!(format!("yaml_suite_{}_med", name), out_med);
+    let out_full = run_cli_yaml_with_budget(&input, BUDGET_FULL);
+    assert_snapshot!(format!("yaml_suite_{}_full", name), out_full);
 }
 
-// No output normalization: runtime behavior is deterministic (aliases -> "*alias").
+// No output normalization: runtime behavior is deterministic.
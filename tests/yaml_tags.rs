@@ -0,0 +1,67 @@
+#[path = "../test_support/mod.rs"]
+mod util;
+
+const TEMPLATE: &str = "\
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+    Properties:
+      BucketName: !Sub \"${AWS::StackName}-data\"
+      Tags: !If
+        - IsProd
+        - !Ref AWS::NoValue
+  Handler:
+    Properties:
+      Code: |
+        def handler(event, context):
+            return event
+      Description: >
+        Resizes uploaded
+        images.
+
+        Runs on upload.
+";
+
+#[test]
+#[allow(
+    clippy::cognitive_complexity,
+    reason = "test performs several assertions succinctly"
+)]
+fn tags_and_block_scalars_survive_yaml_output() {
+    let out = util::run_stdout(
+        TEMPLATE,
+        &["-i", "yaml", "-f", "yaml", "-n", "10000"],
+    );
+    assert!(out.contains("BucketName: !Sub \"${AWS::StackName}-data\""));
+    assert!(out.contains("Tags: !If\n"), "{out}");
+    assert!(out.contains("- !Ref \"AWS::NoValue\""), "{out}");
+    let code = "Code: |\n        def handler(event, context):\n            \
+                return event\n";
+    assert!(out.contains(code), "{out}");
+    assert!(
+        out.contains(
+            "Description: >\n        Resizes uploaded images.\n\n        \
+             Runs on upload.\n"
+        ),
+        "{out}"
+    );
+}
+
+#[test]
+fn truncated_block_scalar_keeps_its_style() {
+    let out = util::run_stdout(
+        "script: |\n  make\n  make test\n  make install\n",
+        &["-i", "yaml", "-f", "yaml", "--string-cap", "12"],
+    );
+    assert_eq!(out.trim_end(), "script: |-\n  make\n  make te…");
+}
+
+#[test]
+fn json_output_drops_yaml_tags() {
+    let out = util::run_stdout(
+        "id: !Ref Bucket\n",
+        &["-i", "yaml", "-f", "json", "-t", "strict"],
+    );
+    let v: serde_json::Value = serde_json::from_str(&out).expect("json");
+    assert_eq!(v, serde_json::json!({"id": "Bucket"}));
+}